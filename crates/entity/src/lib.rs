@@ -1,9 +1,8 @@
 mod entity_type;
 pub mod types;
 
-use ahash::AHashMap;
 pub use entity_type::EntityType;
-use nbt::{Compound, Value};
+use nbt::{Compound, IntArray, compound, list};
 
 #[derive(Debug, Clone, Copy, PartialEq, Default)]
 pub struct Transform {
@@ -63,19 +62,6 @@ impl BaseEntity {
 
 impl BaseEntity {
     pub fn save_to_nbt(&self) -> Compound {
-        let mut nbt = AHashMap::new();
-
-        let pos = vec![
-            Value::Double(self.transform.x),
-            Value::Double(self.transform.y),
-            Value::Double(self.transform.z),
-        ];
-
-        let rot = vec![
-            Value::Float(self.transform.yaw as f32),
-            Value::Float(self.transform.pitch as f32),
-        ];
-
         let uuid = self.uuid.as_u128();
         let uuid = vec![
             (uuid >> 96) as i32,
@@ -84,47 +70,31 @@ impl BaseEntity {
             uuid as i32,
         ];
 
-        nbt.insert("Pos".to_string(), Value::List(pos));
-        nbt.insert("Rotation".to_string(), Value::List(rot));
-        nbt.insert("UUID".to_string(), Value::IntArray(uuid));
-
-        nbt
+        compound! {
+            "Pos" => list![self.transform.x, self.transform.y, self.transform.z],
+            "Rotation" => list![self.transform.yaw, self.transform.pitch],
+            "UUID" => IntArray(uuid),
+        }
     }
 
     pub fn load_from_nbt(&mut self, nbt: &Compound) {
-        if let Some(Value::List(pos)) = nbt.get("Pos") {
-            if pos.len() == 3 {
-                if let Value::Double(v) = pos[0] {
-                    self.transform.x = v;
-                }
-                if let Value::Double(v) = pos[1] {
-                    self.transform.y = v;
-                }
-                if let Value::Double(v) = pos[2] {
-                    self.transform.z = v;
-                }
-            }
+        if let Some(&[x, y, z]) = nbt.get_list_of::<f64>("Pos").as_deref() {
+            self.transform.x = x;
+            self.transform.y = y;
+            self.transform.z = z;
         }
 
-        if let Some(Value::List(rot)) = nbt.get("Rotation") {
-            if rot.len() == 2 {
-                if let Value::Float(v) = rot[0] {
-                    self.transform.yaw = v;
-                }
-                if let Value::Float(v) = rot[1] {
-                    self.transform.pitch = v;
-                }
-            }
+        if let Some(&[yaw, pitch]) = nbt.get_list_of::<f32>("Rotation").as_deref() {
+            self.transform.yaw = yaw;
+            self.transform.pitch = pitch;
         }
 
-        if let Some(Value::IntArray(ints)) = nbt.get("UUID") {
-            if ints.len() == 4 {
-                let uuid = ((ints[0] as u128) << 96)
-                    | ((ints[1] as u128) << 64)
-                    | ((ints[2] as u128) << 32)
-                    | (ints[3] as u128);
-                self.uuid = uuid::Uuid::from_u128(uuid);
-            }
+        if let Some(&[a, b, c, d]) = nbt.get_int_array("UUID") {
+            let uuid = ((a as u32 as u128) << 96)
+                | ((b as u32 as u128) << 64)
+                | ((c as u32 as u128) << 32)
+                | (d as u32 as u128);
+            self.uuid = uuid::Uuid::from_u128(uuid);
         }
     }
 }
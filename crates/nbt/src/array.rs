@@ -0,0 +1,101 @@
+use std::ops::{Deref, DerefMut};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer, de};
+
+pub(crate) const BYTE_ARRAY_TOKEN: &str = "__nbt_byte_array";
+pub(crate) const INT_ARRAY_TOKEN: &str = "__nbt_int_array";
+pub(crate) const LONG_ARRAY_TOKEN: &str = "__nbt_long_array";
+
+macro_rules! array_type {
+    ($name:ident, $module:ident, $element:ty, $token:ident) => {
+        #[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+        pub struct $name(pub Vec<$element>);
+
+        impl Deref for $name {
+            type Target = Vec<$element>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl DerefMut for $name {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.0
+            }
+        }
+
+        impl From<Vec<$element>> for $name {
+            fn from(values: Vec<$element>) -> Self {
+                $name(values)
+            }
+        }
+
+        impl From<$name> for Vec<$element> {
+            fn from(array: $name) -> Self {
+                array.0
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $module::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                $module::deserialize(deserializer).map($name)
+            }
+        }
+
+        pub mod $module {
+            use super::*;
+
+            pub fn serialize<S: Serializer>(
+                values: &[$element],
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                serializer.serialize_newtype_struct($token, values)
+            }
+
+            pub fn deserialize<'de, D: Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Vec<$element>, D::Error> {
+                struct ArrayVisitor;
+
+                impl<'de> de::Visitor<'de> for ArrayVisitor {
+                    type Value = Vec<$element>;
+
+                    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                        formatter.write_str(stringify!($name))
+                    }
+
+                    fn visit_newtype_struct<D: Deserializer<'de>>(
+                        self,
+                        deserializer: D,
+                    ) -> Result<Self::Value, D::Error> {
+                        Vec::deserialize(deserializer)
+                    }
+
+                    fn visit_seq<A: de::SeqAccess<'de>>(
+                        self,
+                        mut seq: A,
+                    ) -> Result<Self::Value, A::Error> {
+                        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+                        while let Some(value) = seq.next_element()? {
+                            values.push(value);
+                        }
+                        Ok(values)
+                    }
+                }
+
+                deserializer.deserialize_newtype_struct($token, ArrayVisitor)
+            }
+        }
+    };
+}
+
+array_type!(ByteArray, byte_array, u8, BYTE_ARRAY_TOKEN);
+array_type!(IntArray, int_array, i32, INT_ARRAY_TOKEN);
+array_type!(LongArray, long_array, i64, LONG_ARRAY_TOKEN);
@@ -0,0 +1,207 @@
+use std::ops::Index;
+
+use ahash::RandomState;
+use indexmap::IndexMap;
+
+use crate::{FromValue, Value, path::NbtPath};
+
+// Keys keep their insertion order so that writing a compound is stable and a
+// file that is read and written back comes out byte for byte the same.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Compound {
+    entries: IndexMap<String, Value, RandomState>,
+}
+
+impl Compound {
+    pub fn new() -> Self {
+        Compound::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Compound {
+            entries: IndexMap::with_capacity_and_hasher(capacity, RandomState::new()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.entries.get_mut(key)
+    }
+
+    // Replacing an existing key keeps its original position.
+    pub fn insert<K: Into<String>, V: Into<Value>>(&mut self, key: K, value: V) -> Option<Value> {
+        self.entries.insert(key.into(), value.into())
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.entries.shift_remove(key)
+    }
+
+    pub fn retain<F: FnMut(&str, &mut Value) -> bool>(&mut self, mut keep: F) {
+        self.entries.retain(|key, value| keep(key, value));
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> indexmap::map::Iter<'_, String, Value> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> indexmap::map::IterMut<'_, String, Value> {
+        self.entries.iter_mut()
+    }
+
+    pub fn keys(&self) -> indexmap::map::Keys<'_, String, Value> {
+        self.entries.keys()
+    }
+
+    pub fn values(&self) -> indexmap::map::Values<'_, String, Value> {
+        self.entries.values()
+    }
+
+    pub fn get_as<'a, T: FromValue<'a>>(&'a self, key: &str) -> Option<T> {
+        self.get(key).and_then(T::from_value)
+    }
+
+    pub fn get_i8(&self, key: &str) -> Option<i8> {
+        self.get_as(key)
+    }
+
+    pub fn get_i16(&self, key: &str) -> Option<i16> {
+        self.get_as(key)
+    }
+
+    pub fn get_i32(&self, key: &str) -> Option<i32> {
+        self.get_as(key)
+    }
+
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get_as(key)
+    }
+
+    pub fn get_f32(&self, key: &str) -> Option<f32> {
+        self.get_as(key)
+    }
+
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get_as(key)
+    }
+
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        self.get_as(key)
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get_as(key)
+    }
+
+    pub fn get_list(&self, key: &str) -> Option<&[Value]> {
+        self.get_as(key)
+    }
+
+    pub fn get_compound(&self, key: &str) -> Option<&Compound> {
+        self.get_as(key)
+    }
+
+    pub fn get_byte_array(&self, key: &str) -> Option<&[u8]> {
+        self.get_as(key)
+    }
+
+    pub fn get_int_array(&self, key: &str) -> Option<&[i32]> {
+        self.get_as(key)
+    }
+
+    pub fn get_long_array(&self, key: &str) -> Option<&[i64]> {
+        self.get_as(key)
+    }
+
+    // None if the key is missing or any element has the wrong type.
+    pub fn get_list_of<'a, T: FromValue<'a>>(&'a self, key: &str) -> Option<Vec<T>> {
+        self.get_list(key)?.iter().map(T::from_value).collect()
+    }
+
+    pub fn get_list_mut(&mut self, key: &str) -> Option<&mut Vec<Value>> {
+        self.get_mut(key)?.as_list_mut()
+    }
+
+    pub fn get_compound_mut(&mut self, key: &str) -> Option<&mut Compound> {
+        self.get_mut(key)?.as_compound_mut()
+    }
+
+    pub fn query(&self, path: &str) -> Option<&Value> {
+        NbtPath::parse(path).ok()?.get_in(self)
+    }
+
+    pub fn query_mut(&mut self, path: &str) -> Option<&mut Value> {
+        NbtPath::parse(path).ok()?.get_in_mut(self)
+    }
+}
+
+impl Index<&str> for Compound {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key)
+            .unwrap_or_else(|| panic!("Compound has no key {:?}", key))
+    }
+}
+
+impl IntoIterator for Compound {
+    type Item = (String, Value);
+    type IntoIter = indexmap::map::IntoIter<String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Compound {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = indexmap::map::Iter<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Compound {
+    type Item = (&'a String, &'a mut Value);
+    type IntoIter = indexmap::map::IterMut<'a, String, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter_mut()
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> FromIterator<(K, V)> for Compound {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut compound = Compound::new();
+        compound.extend(iter);
+        compound
+    }
+}
+
+impl<K: Into<String>, V: Into<Value>> Extend<(K, V)> for Compound {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
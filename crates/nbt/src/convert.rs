@@ -0,0 +1,150 @@
+use crate::{ByteArray, Compound, IntArray, LongArray, Value};
+
+pub trait FromValue<'a>: Sized {
+    fn from_value(value: &'a Value) -> Option<Self>;
+}
+
+macro_rules! numeric {
+    ($($ty:ty => $method:ident),* $(,)?) => {
+        impl Value {
+            $(
+                // Like vanilla's numeric tags, any number converts with `as`.
+                pub fn $method(&self) -> Option<$ty> {
+                    match *self {
+                        Value::Byte(v) => Some(v as $ty),
+                        Value::Short(v) => Some(v as $ty),
+                        Value::Int(v) => Some(v as $ty),
+                        Value::Long(v) => Some(v as $ty),
+                        Value::Float(v) => Some(v as $ty),
+                        Value::Double(v) => Some(v as $ty),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl FromValue<'_> for $ty {
+                fn from_value(value: &Value) -> Option<Self> {
+                    value.$method()
+                }
+            }
+        )*
+    };
+}
+
+numeric! {
+    i8 => as_i8,
+    i16 => as_i16,
+    i32 => as_i32,
+    i64 => as_i64,
+    f32 => as_f32,
+    f64 => as_f64,
+}
+
+macro_rules! borrowed {
+    ($($ty:ty => $method:ident($variant:ident)),* $(,)?) => {
+        impl Value {
+            $(
+                pub fn $method(&self) -> Option<&$ty> {
+                    match self {
+                        Value::$variant(v) => Some(v),
+                        _ => None,
+                    }
+                }
+            )*
+        }
+
+        $(
+            impl<'a> FromValue<'a> for &'a $ty {
+                fn from_value(value: &'a Value) -> Option<Self> {
+                    value.$method()
+                }
+            }
+        )*
+    };
+}
+
+borrowed! {
+    str => as_str(String),
+    [Value] => as_list(List),
+    Compound => as_compound(Compound),
+    [u8] => as_byte_array(ByteArray),
+    [i32] => as_int_array(IntArray),
+    [i64] => as_long_array(LongArray),
+}
+
+impl Value {
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Byte(v) => Some(*v != 0),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<Value>> {
+        match self {
+            Value::List(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut Compound> {
+        match self {
+            Value::Compound(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+impl FromValue<'_> for bool {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_bool()
+    }
+}
+
+impl<'a> FromValue<'a> for &'a Value {
+    fn from_value(value: &'a Value) -> Option<Self> {
+        Some(value)
+    }
+}
+
+impl FromValue<'_> for String {
+    fn from_value(value: &Value) -> Option<Self> {
+        value.as_str().map(str::to_string)
+    }
+}
+
+macro_rules! from_impl {
+    ($($ty:ty => $variant:ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Value::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+from_impl! {
+    i8 => Byte,
+    i16 => Short,
+    i32 => Int,
+    i64 => Long,
+    f32 => Float,
+    f64 => Double,
+    String => String,
+    &str => String,
+    Vec<Value> => List,
+    Compound => Compound,
+    ByteArray => ByteArray,
+    IntArray => IntArray,
+    LongArray => LongArray,
+}
+
+impl From<bool> for Value {
+    fn from(value: bool) -> Self {
+        Value::Byte(value as i8)
+    }
+}
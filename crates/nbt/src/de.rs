@@ -0,0 +1,435 @@
+use std::io::Read;
+
+use serde::{
+    Deserialize,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, IntoDeserializer, MapAccess,
+        SeqAccess, Unexpected, VariantAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{
+    Compound, NbtError, Result, Value,
+    array::{BYTE_ARRAY_TOKEN, INT_ARRAY_TOKEN, LONG_ARRAY_TOKEN},
+};
+
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T> {
+    T::deserialize(value)
+}
+
+pub fn from_reader<R: Read, T: DeserializeOwned>(reader: &mut R) -> Result<T> {
+    let (_, root) = Value::read_named(reader)?;
+    from_value(Value::Compound(root))
+}
+
+impl de::Error for NbtError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        NbtError::Message(msg.to_string())
+    }
+}
+
+impl Value {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Byte(v) => Unexpected::Signed(*v as i64),
+            Value::Short(v) => Unexpected::Signed(*v as i64),
+            Value::Int(v) => Unexpected::Signed(*v as i64),
+            Value::Long(v) => Unexpected::Signed(*v),
+            Value::Float(v) => Unexpected::Float(*v as f64),
+            Value::Double(v) => Unexpected::Float(*v),
+            Value::String(v) => Unexpected::Str(v),
+            Value::List(_) => Unexpected::Seq,
+            Value::Compound(_) => Unexpected::Map,
+            Value::ByteArray(v) => Unexpected::Bytes(v),
+            Value::IntArray(_) | Value::LongArray(_) => Unexpected::Seq,
+        }
+    }
+}
+
+impl<'de> IntoDeserializer<'de, NbtError> for Value {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+macro_rules! deserialize_unsigned {
+    ($method:ident, $variant:ident, $visit:ident, $type:ty) => {
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            match self {
+                Value::$variant(v) => visitor.$visit(v as $type),
+                value => value.deserialize_any(visitor),
+            }
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = NbtError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Byte(v) => visitor.visit_i8(v),
+            Value::Short(v) => visitor.visit_i16(v),
+            Value::Int(v) => visitor.visit_i32(v),
+            Value::Long(v) => visitor.visit_i64(v),
+            Value::Float(v) => visitor.visit_f32(v),
+            Value::Double(v) => visitor.visit_f64(v),
+            Value::String(v) => visitor.visit_string(v),
+            Value::List(v) => visitor.visit_seq(SeqDeserializer::new(v)),
+            Value::Compound(v) => visitor.visit_map(CompoundDeserializer::new(v)),
+            array => {
+                let token = match array {
+                    Value::ByteArray(_) => BYTE_ARRAY_TOKEN,
+                    Value::IntArray(_) => INT_ARRAY_TOKEN,
+                    _ => LONG_ARRAY_TOKEN,
+                };
+                visitor.visit_map(ArrayDeserializer {
+                    token: Some(token),
+                    array: Some(array),
+                })
+            }
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::Byte(v) => visitor.visit_bool(v != 0),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    deserialize_unsigned!(deserialize_u8, Byte, visit_u8, u8);
+    deserialize_unsigned!(deserialize_u16, Short, visit_u16, u16);
+    deserialize_unsigned!(deserialize_u32, Int, visit_u32, u32);
+    deserialize_unsigned!(deserialize_u64, Long, visit_u64, u64);
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::ByteArray(v) => visitor.visit_byte_buf(v),
+            value => value.deserialize_seq(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Value::List(v) => visitor.visit_seq(SeqDeserializer::new(v)),
+            Value::ByteArray(v) => visitor.visit_seq(SeqDeserializer::new(
+                v.into_iter().map(|v| Value::Byte(v as i8)).collect(),
+            )),
+            Value::IntArray(v) => visitor.visit_seq(SeqDeserializer::new(
+                v.into_iter().map(Value::Int).collect(),
+            )),
+            Value::LongArray(v) => visitor.visit_seq(SeqDeserializer::new(
+                v.into_iter().map(Value::Long).collect(),
+            )),
+            value => Err(de::Error::invalid_type(value.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        match self {
+            Value::String(variant) => visitor.visit_enum(variant.into_deserializer()),
+            Value::Compound(map) if map.len() == 1 => match map.into_iter().next() {
+                Some((variant, value)) => visitor.visit_enum(EnumDeserializer { variant, value }),
+                None => Err(de::Error::invalid_length(0, &visitor)),
+            },
+            value => Err(de::Error::invalid_type(value.unexpected(), &visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u128 f32 f64 char str string
+        map struct identifier
+    }
+}
+
+struct SeqDeserializer {
+    values: std::vec::IntoIter<Value>,
+}
+
+impl SeqDeserializer {
+    fn new(values: Vec<Value>) -> Self {
+        SeqDeserializer {
+            values: values.into_iter(),
+        }
+    }
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer {
+    type Error = NbtError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.values
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+struct CompoundDeserializer {
+    entries: <Compound as IntoIterator>::IntoIter,
+    value: Option<Value>,
+}
+
+impl CompoundDeserializer {
+    fn new(map: Compound) -> Self {
+        CompoundDeserializer {
+            entries: map.into_iter(),
+            value: None,
+        }
+    }
+}
+
+impl<'de> MapAccess<'de> for CompoundDeserializer {
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(Value::String(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| NbtError::Message("Value requested before key".to_string()))?;
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+// Arrays are exposed to self-describing visitors as a single-entry map keyed
+// by the array token so `Value` can tell them apart from plain lists.
+struct ArrayDeserializer {
+    token: Option<&'static str>,
+    array: Option<Value>,
+}
+
+impl<'de> MapAccess<'de> for ArrayDeserializer {
+    type Error = NbtError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        self.token
+            .take()
+            .map(|token| seed.deserialize(Value::String(token.to_string())))
+            .transpose()
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let array = self
+            .array
+            .take()
+            .ok_or_else(|| NbtError::Message("Value requested before key".to_string()))?;
+        seed.deserialize(array)
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Value,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer {
+    type Error = NbtError;
+    type Variant = Value;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Value)> {
+        let variant = seed.deserialize(Value::String(self.variant))?;
+        Ok((variant, self.value))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Value {
+    type Error = NbtError;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an NBT value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> std::result::Result<Value, E> {
+        Ok(Value::Byte(v as i8))
+    }
+
+    fn visit_i8<E>(self, v: i8) -> std::result::Result<Value, E> {
+        Ok(Value::Byte(v))
+    }
+
+    fn visit_i16<E>(self, v: i16) -> std::result::Result<Value, E> {
+        Ok(Value::Short(v))
+    }
+
+    fn visit_i32<E>(self, v: i32) -> std::result::Result<Value, E> {
+        Ok(Value::Int(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> std::result::Result<Value, E> {
+        Ok(Value::Long(v))
+    }
+
+    fn visit_u8<E>(self, v: u8) -> std::result::Result<Value, E> {
+        Ok(Value::Byte(v as i8))
+    }
+
+    fn visit_u16<E>(self, v: u16) -> std::result::Result<Value, E> {
+        Ok(Value::Short(v as i16))
+    }
+
+    fn visit_u32<E>(self, v: u32) -> std::result::Result<Value, E> {
+        Ok(Value::Int(v as i32))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> std::result::Result<Value, E> {
+        Ok(Value::Long(v as i64))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> std::result::Result<Value, E> {
+        Ok(Value::Float(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> std::result::Result<Value, E> {
+        Ok(Value::Double(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> std::result::Result<Value, E> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn visit_string<E>(self, v: String) -> std::result::Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> std::result::Result<Value, E> {
+        Ok(Value::ByteArray(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> std::result::Result<Value, E> {
+        Ok(Value::ByteArray(v))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> std::result::Result<Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element::<Value>()? {
+            values.push(value);
+        }
+        Ok(Value::List(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Value, A::Error> {
+        let Some(first) = map.next_key::<String>()? else {
+            return Ok(Value::new_compound());
+        };
+
+        match first.as_str() {
+            BYTE_ARRAY_TOKEN => return Ok(Value::ByteArray(map.next_value()?)),
+            INT_ARRAY_TOKEN => return Ok(Value::IntArray(map.next_value()?)),
+            LONG_ARRAY_TOKEN => return Ok(Value::LongArray(map.next_value()?)),
+            _ => {}
+        }
+
+        let mut compound = Compound::default();
+        compound.insert(first, map.next_value::<Value>()?);
+        while let Some((key, value)) = map.next_entry::<String, Value>()? {
+            compound.insert(key, value);
+        }
+        Ok(Value::Compound(compound))
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Compound, NbtPath, PathNode, Value};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change<'a> {
+    Added {
+        path: NbtPath,
+        value: &'a Value,
+    },
+    Removed {
+        path: NbtPath,
+        value: &'a Value,
+    },
+    Changed {
+        path: NbtPath,
+        old: &'a Value,
+        new: &'a Value,
+    },
+}
+
+impl Change<'_> {
+    pub fn path(&self) -> &NbtPath {
+        match self {
+            Change::Added { path, .. }
+            | Change::Removed { path, .. }
+            | Change::Changed { path, .. } => path,
+        }
+    }
+}
+
+// Compounds are compared key by key and lists index by index; anything else,
+// including arrays and values whose tag type changed, is reported whole.
+pub fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<Change<'a>> {
+    let mut changes = Vec::new();
+    diff_value(&mut Vec::new(), old, new, &mut changes);
+    changes
+}
+
+fn diff_value<'a>(
+    path: &mut Vec<PathNode>,
+    old: &'a Value,
+    new: &'a Value,
+    changes: &mut Vec<Change<'a>>,
+) {
+    match (old, new) {
+        (Value::Compound(old), Value::Compound(new)) => diff_compound(path, old, new, changes),
+        (Value::List(old), Value::List(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                path.push(PathNode::Index(index as i32));
+                match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => diff_value(path, old, new, changes),
+                    (Some(value), None) => changes.push(Change::Removed {
+                        path: NbtPath::from(path.clone()),
+                        value,
+                    }),
+                    (None, Some(value)) => changes.push(Change::Added {
+                        path: NbtPath::from(path.clone()),
+                        value,
+                    }),
+                    (None, None) => unreachable!(),
+                }
+                path.pop();
+            }
+        }
+        (old, new) if old != new => changes.push(Change::Changed {
+            path: NbtPath::from(path.clone()),
+            old,
+            new,
+        }),
+        _ => {}
+    }
+}
+
+fn diff_compound<'a>(
+    path: &mut Vec<PathNode>,
+    old: &'a Compound,
+    new: &'a Compound,
+    changes: &mut Vec<Change<'a>>,
+) {
+    for (key, old_value) in old {
+        path.push(PathNode::Key(key.clone()));
+        match new.get(key) {
+            Some(new_value) => diff_value(path, old_value, new_value, changes),
+            None => changes.push(Change::Removed {
+                path: NbtPath::from(path.clone()),
+                value: old_value,
+            }),
+        }
+        path.pop();
+    }
+
+    for (key, value) in new {
+        if !old.contains_key(key) {
+            path.push(PathNode::Key(key.clone()));
+            changes.push(Change::Added {
+                path: NbtPath::from(path.clone()),
+                value,
+            });
+            path.pop();
+        }
+    }
+}
+
+impl Compound {
+    // Same rules as `/data merge`: nested compounds merge key by key, every
+    // other tag (lists included) replaces what was there.
+    pub fn merge(&mut self, patch: &Compound) {
+        for (key, value) in patch {
+            match (self.get_mut(key), value) {
+                (Some(Value::Compound(target)), Value::Compound(patch)) => target.merge(patch),
+                _ => {
+                    self.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+impl Value {
+    pub fn merge(&mut self, patch: &Value) {
+        match (self, patch) {
+            (Value::Compound(target), Value::Compound(patch)) => target.merge(patch),
+            (target, patch) => *target = patch.clone(),
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::TagId;
+
+#[derive(thiserror::Error, Debug)]
+pub enum NbtError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid modified UTF-8 string")]
+    InvalidString,
+
+    #[error("Invalid tag id {0}")]
+    InvalidTagId(u8),
+
+    #[error("Unexpected end tag")]
+    UnexpectedEnd,
+
+    #[error("Root tag must be a compound, found {0:?}")]
+    InvalidRoot(TagId),
+
+    #[error("Negative length {0}")]
+    NegativeLength(i32),
+
+    #[error("String too long ({0} bytes)")]
+    StringTooLong(usize),
+
+    #[error("List of {expected:?} contains a {found:?} element")]
+    MixedList { expected: TagId, found: TagId },
+
+    #[error("{message} at column {column}: {context}<--[HERE]")]
+    Snbt {
+        message: String,
+        column: usize,
+        context: String,
+    },
+
+    #[error("NBT is nested deeper than {0} levels")]
+    DepthLimit(usize),
+
+    #[error("NBT exceeds the {0} byte quota")]
+    SizeLimit(usize),
+
+    #[error("{0}")]
+    Message(String),
+
+    #[error("Collection too long ({0} elements)")]
+    LengthTooLong(usize),
+
+    #[error("Invalid NBT path {path:?}: {message}")]
+    InvalidPath { path: String, message: String },
+
+    #[error("Invalid JSON for NBT: {0}")]
+    InvalidJson(String),
+}
+
+pub type Result<T> = std::result::Result<T, NbtError>;
@@ -0,0 +1,83 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::Path,
+};
+
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+
+use crate::{Compound, Result, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Compression {
+    None,
+    #[default]
+    Gzip,
+    Zlib,
+}
+
+impl Compression {
+    pub fn detect(header: &[u8]) -> Self {
+        match header {
+            [0x1f, 0x8b, ..] => Compression::Gzip,
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8 && ((*cmf as u16) << 8 | *flg as u16).is_multiple_of(31) =>
+            {
+                Compression::Zlib
+            }
+            _ => Compression::None,
+        }
+    }
+}
+
+pub fn read_compressed<R: BufRead>(reader: &mut R) -> Result<(String, Compound)> {
+    let compression = Compression::detect(reader.fill_buf()?);
+    match compression {
+        Compression::None => Value::read_named(reader),
+        Compression::Gzip => Value::read_named(&mut BufReader::new(GzDecoder::new(reader))),
+        Compression::Zlib => Value::read_named(&mut BufReader::new(ZlibDecoder::new(reader))),
+    }
+}
+
+pub fn write_compressed<W: Write>(
+    writer: &mut W,
+    name: &str,
+    root: &Compound,
+    compression: Compression,
+) -> Result<()> {
+    match compression {
+        Compression::None => crate::write_named(writer, name, root),
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+            crate::write_named(&mut encoder, name, root)?;
+            encoder.finish()?;
+            Ok(())
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(writer, flate2::Compression::default());
+            crate::write_named(&mut encoder, name, root)?;
+            encoder.finish()?;
+            Ok(())
+        }
+    }
+}
+
+pub fn read_file<P: AsRef<Path>>(path: P) -> Result<(String, Compound)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    read_compressed(&mut reader)
+}
+
+pub fn write_file<P: AsRef<Path>>(
+    path: P,
+    name: &str,
+    root: &Compound,
+    compression: Compression,
+) -> Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_compressed(&mut writer, name, root, compression)?;
+    writer.flush()?;
+    Ok(())
+}
@@ -0,0 +1,207 @@
+use serde_json::{Map, Number, Value as Json};
+
+use crate::{Compound, NbtError, Result, TagId, Value};
+
+// Plain JSON keeps values but not tag types: arrays become JSON arrays and
+// every number reads back as the smallest of Int/Long/Double that holds it.
+// Typed JSON wraps each tag as `{"type": "short", "value": 3}` so it can be
+// converted back exactly.
+impl Value {
+    pub fn to_json(&self) -> Json {
+        match self {
+            Value::Byte(v) => Json::from(*v),
+            Value::Short(v) => Json::from(*v),
+            Value::Int(v) => Json::from(*v),
+            Value::Long(v) => Json::from(*v),
+            Value::Float(v) => float_to_json(*v as f64, Some(*v)),
+            Value::Double(v) => float_to_json(*v, None),
+            Value::String(v) => Json::from(v.as_str()),
+            Value::List(list) => list.iter().map(Value::to_json).collect(),
+            Value::Compound(map) => Json::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_json()))
+                    .collect(),
+            ),
+            Value::ByteArray(v) => v.iter().map(|&v| Json::from(v as i8)).collect(),
+            Value::IntArray(v) => Json::from(v.as_slice()),
+            Value::LongArray(v) => Json::from(v.as_slice()),
+        }
+    }
+
+    pub fn from_json(json: &Json) -> Result<Self> {
+        match json {
+            Json::Null => Err(invalid("null has no NBT representation")),
+            Json::Bool(v) => Ok(Value::from(*v)),
+            Json::Number(v) => Ok(number_from_json(v)),
+            Json::String(v) => Ok(Value::String(v.clone())),
+            Json::Array(values) => {
+                let values = values.iter().map(Value::from_json).collect::<Result<_>>()?;
+                unify_list(values).map(Value::List)
+            }
+            Json::Object(map) => map
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), Value::from_json(value)?)))
+                .collect::<Result<Compound>>()
+                .map(Value::Compound),
+        }
+    }
+
+    pub fn to_typed_json(&self) -> Json {
+        let value = match self {
+            Value::List(list) => list.iter().map(Value::to_typed_json).collect(),
+            Value::Compound(map) => Json::Object(
+                map.iter()
+                    .map(|(key, value)| (key.clone(), value.to_typed_json()))
+                    .collect(),
+            ),
+            Value::Float(v) if !v.is_finite() => Json::from(v.to_string()),
+            Value::Double(v) if !v.is_finite() => Json::from(v.to_string()),
+            value => value.to_json(),
+        };
+
+        let mut tagged = Map::new();
+        tagged.insert("type".to_string(), Json::from(type_name(self.id())));
+        tagged.insert("value".to_string(), value);
+        Json::Object(tagged)
+    }
+
+    pub fn from_typed_json(json: &Json) -> Result<Self> {
+        let (Some(Json::String(name)), Some(value)) = (json.get("type"), json.get("value")) else {
+            return Err(invalid("expected an object with \"type\" and \"value\""));
+        };
+
+        let value = match name.as_str() {
+            "byte" => Value::Byte(integer(value)?),
+            "short" => Value::Short(integer(value)?),
+            "int" => Value::Int(integer(value)?),
+            "long" => Value::Long(integer(value)?),
+            "float" => Value::Float(float(value)? as f32),
+            "double" => Value::Double(float(value)?),
+            "string" => match value {
+                Json::String(v) => Value::String(v.clone()),
+                _ => return Err(invalid("expected a string")),
+            },
+            "list" => {
+                let values = array(value)?
+                    .iter()
+                    .map(Value::from_typed_json)
+                    .collect::<Result<Vec<_>>>()?;
+                if let Some(first) = values.first()
+                    && let Some(other) = values.iter().find(|v| v.id() != first.id())
+                {
+                    return Err(NbtError::MixedList {
+                        expected: first.id(),
+                        found: other.id(),
+                    });
+                }
+                Value::List(values)
+            }
+            "compound" => match value {
+                Json::Object(map) => map
+                    .iter()
+                    .map(|(key, value)| Ok((key.clone(), Value::from_typed_json(value)?)))
+                    .collect::<Result<Compound>>()
+                    .map(Value::Compound)?,
+                _ => return Err(invalid("expected an object")),
+            },
+            "byte_array" => Value::ByteArray(
+                array(value)?
+                    .iter()
+                    .map(|v| integer::<i8>(v).map(|v| v as u8))
+                    .collect::<Result<_>>()?,
+            ),
+            "int_array" => {
+                Value::IntArray(array(value)?.iter().map(integer).collect::<Result<_>>()?)
+            }
+            "long_array" => {
+                Value::LongArray(array(value)?.iter().map(integer).collect::<Result<_>>()?)
+            }
+            other => return Err(invalid(&format!("unknown tag type {:?}", other))),
+        };
+        Ok(value)
+    }
+}
+
+fn type_name(id: TagId) -> &'static str {
+    match id {
+        TagId::End => "end",
+        TagId::Byte => "byte",
+        TagId::Short => "short",
+        TagId::Int => "int",
+        TagId::Long => "long",
+        TagId::Float => "float",
+        TagId::Double => "double",
+        TagId::ByteArray => "byte_array",
+        TagId::String => "string",
+        TagId::List => "list",
+        TagId::Compound => "compound",
+        TagId::IntArray => "int_array",
+        TagId::LongArray => "long_array",
+    }
+}
+
+// Floats go through their shortest decimal form so 0.1f32 exports as 0.1
+// rather than 0.10000000149011612. Non-finite values have no JSON number.
+fn float_to_json(value: f64, float: Option<f32>) -> Json {
+    let value = match float {
+        Some(float) => float.to_string().parse().unwrap_or(value),
+        None => value,
+    };
+    Number::from_f64(value).map_or(Json::Null, Json::Number)
+}
+
+fn number_from_json(number: &Number) -> Value {
+    match number.as_i64() {
+        Some(v) => i32::try_from(v).map_or(Value::Long(v), Value::Int),
+        None => Value::Double(number.as_f64().unwrap_or(f64::NAN)),
+    }
+}
+
+// JSON arrays may mix integer widths and decimals; widen them to one numeric
+// tag the way a list of numbers would be written by hand in SNBT.
+fn unify_list(mut values: Vec<Value>) -> Result<Vec<Value>> {
+    let Some(widest) = values.iter().map(Value::id).max() else {
+        return Ok(values);
+    };
+    let numeric = |id: TagId| matches!(id, TagId::Int | TagId::Long | TagId::Double);
+
+    if numeric(widest) && values.iter().all(|v| numeric(v.id())) {
+        for value in &mut values {
+            *value = match (widest, &*value) {
+                (TagId::Long, v) => Value::Long(v.as_i64().unwrap_or_default()),
+                (TagId::Double, v) => Value::Double(v.as_f64().unwrap_or_default()),
+                (_, v) => v.clone(),
+            };
+        }
+    } else if let Some(other) = values.iter().find(|v| v.id() != widest) {
+        return Err(NbtError::MixedList {
+            expected: widest,
+            found: other.id(),
+        });
+    }
+    Ok(values)
+}
+
+fn integer<T: TryFrom<i64>>(json: &Json) -> Result<T> {
+    json.as_i64()
+        .and_then(|v| T::try_from(v).ok())
+        .ok_or_else(|| invalid(&format!("{} is not a valid integer for this tag", json)))
+}
+
+fn float(json: &Json) -> Result<f64> {
+    match json {
+        Json::Number(v) => v.as_f64().ok_or_else(|| invalid("invalid number")),
+        Json::String(v) => v
+            .parse()
+            .map_err(|_| invalid(&format!("{:?} is not a number", v))),
+        _ => Err(invalid("expected a number")),
+    }
+}
+
+fn array(json: &Json) -> Result<&Vec<Json>> {
+    json.as_array().ok_or_else(|| invalid("expected an array"))
+}
+
+fn invalid(message: &str) -> NbtError {
+    NbtError::InvalidJson(message.to_string())
+}
@@ -1,9 +1,32 @@
-use std::io::{Error, ErrorKind, Read, Result, Write};
-
-use ahash::AHashMap;
-use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
-
-pub type Compound = AHashMap<String, Value>;
+use std::io::Write;
+
+use byteorder::{BigEndian, WriteBytesExt};
+
+mod array;
+mod compound;
+mod convert;
+mod de;
+mod diff;
+mod error;
+mod file;
+mod json;
+mod macros;
+mod mutf8;
+mod path;
+mod read;
+mod ser;
+mod snbt;
+
+pub use array::{ByteArray, IntArray, LongArray, byte_array, int_array, long_array};
+pub use compound::Compound;
+pub use convert::FromValue;
+pub use de::{from_reader, from_value};
+pub use diff::{Change, diff};
+pub use error::{NbtError, Result};
+pub use file::{Compression, read_compressed, read_file, write_compressed, write_file};
+pub use path::{NbtPath, PathNode};
+pub use read::{DEFAULT_MAX_DEPTH, NETWORK_MAX_BYTES, NbtReader};
+pub use ser::{to_value, to_writer};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Value {
@@ -39,22 +62,25 @@ pub enum TagId {
     LongArray = 12,
 }
 
-impl From<u8> for TagId {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for TagId {
+    type Error = NbtError;
+
+    fn try_from(value: u8) -> Result<Self> {
         match value {
-            1 => TagId::Byte,
-            2 => TagId::Short,
-            3 => TagId::Int,
-            4 => TagId::Long,
-            5 => TagId::Float,
-            6 => TagId::Double,
-            7 => TagId::ByteArray,
-            8 => TagId::String,
-            9 => TagId::List,
-            10 => TagId::Compound,
-            11 => TagId::IntArray,
-            12 => TagId::LongArray,
-            _ => TagId::End,
+            0 => Ok(TagId::End),
+            1 => Ok(TagId::Byte),
+            2 => Ok(TagId::Short),
+            3 => Ok(TagId::Int),
+            4 => Ok(TagId::Long),
+            5 => Ok(TagId::Float),
+            6 => Ok(TagId::Double),
+            7 => Ok(TagId::ByteArray),
+            8 => Ok(TagId::String),
+            9 => Ok(TagId::List),
+            10 => Ok(TagId::Compound),
+            11 => Ok(TagId::IntArray),
+            12 => Ok(TagId::LongArray),
+            _ => Err(NbtError::InvalidTagId(value)),
         }
     }
 }
@@ -78,91 +104,7 @@ impl Value {
     }
 
     pub fn new_compound() -> Self {
-        Value::Compound(AHashMap::new())
-    }
-}
-
-impl Value {
-    pub fn read_unnamed<R: Read>(reader: &mut R, type_id: TagId) -> Result<Self> {
-        match type_id {
-            TagId::Byte => Ok(Value::Byte(reader.read_i8()?)),
-            TagId::Short => Ok(Value::Short(reader.read_i16::<BigEndian>()?)),
-            TagId::Int => Ok(Value::Int(reader.read_i32::<BigEndian>()?)),
-            TagId::Long => Ok(Value::Long(reader.read_i64::<BigEndian>()?)),
-            TagId::Float => Ok(Value::Float(reader.read_f32::<BigEndian>()?)),
-            TagId::Double => Ok(Value::Double(reader.read_f64::<BigEndian>()?)),
-
-            TagId::ByteArray => {
-                let len = reader.read_i32::<BigEndian>()?;
-                let mut bytes = vec![0u8; len as usize];
-                reader.read_exact(&mut bytes)?;
-                let bytes = unsafe { std::mem::transmute(bytes) };
-                Ok(Value::ByteArray(bytes))
-            }
-
-            TagId::String => {
-                let len = reader.read_u16::<BigEndian>()?;
-                let mut bytes = vec![0u8; len as usize];
-                reader.read_exact(&mut bytes)?;
-                let bytes =
-                    String::from_utf8(bytes).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
-                Ok(Value::String(bytes))
-            }
-
-            TagId::List => {
-                let element_id = TagId::from(reader.read_u8()?);
-                let len = reader.read_i32::<BigEndian>()?;
-                let mut values = Vec::with_capacity(len as usize);
-                if len > 0 && element_id != TagId::End {
-                    for _ in 0..len {
-                        values.push(Value::read_unnamed(reader, element_id)?);
-                    }
-                }
-                Ok(Value::List(values))
-            }
-
-            TagId::Compound => {
-                let mut map = AHashMap::new();
-
-                loop {
-                    let tag_id = reader.read_u8()?;
-                    if tag_id == 0 {
-                        break;
-                    }
-                    let tag_id = TagId::from(tag_id);
-                    let name_len = reader.read_u16::<BigEndian>()?;
-
-                    let mut name_buf = vec![0u8; name_len as usize];
-                    reader.read_exact(&mut name_buf)?;
-                    let name = String::from_utf8(name_buf)
-                        .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
-                    let value = Value::read_unnamed(reader, tag_id)?;
-                    map.insert(name, value);
-                }
-
-                Ok(Value::Compound(map))
-            }
-
-            TagId::IntArray => {
-                let len = reader.read_i32::<BigEndian>()?;
-                let mut values = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    values.push(reader.read_i32::<BigEndian>()?);
-                }
-                Ok(Value::IntArray(values))
-            }
-
-            TagId::LongArray => {
-                let len = reader.read_i32::<BigEndian>()?;
-                let mut values = Vec::with_capacity(len as usize);
-                for _ in 0..len {
-                    values.push(reader.read_i64::<BigEndian>()?);
-                }
-                Ok(Value::LongArray(values))
-            }
-
-            TagId::End => Err(Error::new(ErrorKind::InvalidData, "Unexpected end tag")),
-        }
+        Value::Compound(Compound::new())
     }
 }
 
@@ -175,43 +117,38 @@ impl Value {
             Value::Long(v) => writer.write_i64::<BigEndian>(*v)?,
             Value::Float(v) => writer.write_f32::<BigEndian>(*v)?,
             Value::Double(v) => writer.write_f64::<BigEndian>(*v)?,
-            Value::String(v) => {
-                writer.write_u16::<BigEndian>(v.len() as u16)?;
-                writer.write_all(v.as_bytes())?;
-            }
+            Value::String(v) => write_string(writer, v)?,
             Value::ByteArray(v) => {
-                writer.write_i32::<BigEndian>(v.len() as i32)?;
+                write_len(writer, v.len())?;
                 writer.write_all(v)?;
             }
             Value::IntArray(v) => {
-                writer.write_i32::<BigEndian>(v.len() as i32)?;
+                write_len(writer, v.len())?;
                 for &value in v {
                     writer.write_i32::<BigEndian>(value)?;
                 }
             }
             Value::LongArray(v) => {
-                writer.write_i32::<BigEndian>(v.len() as i32)?;
+                write_len(writer, v.len())?;
                 for &value in v {
                     writer.write_i64::<BigEndian>(value)?;
                 }
             }
-            Value::Compound(map) => {
-                for (name, value) in map {
-                    writer.write_u8(TagId::Compound as u8)?;
-                    writer.write_u16::<BigEndian>(name.len() as u16)?;
-                    writer.write_all(name.as_bytes())?;
-                    value.write_unnamed(writer)?;
-                }
-                writer.write_u8(TagId::End as u8)?;
-            }
+            Value::Compound(map) => write_compound(writer, map)?,
             Value::List(list) => {
                 if list.is_empty() {
                     writer.write_i8(TagId::End as i8)?;
                     writer.write_i32::<BigEndian>(0)?
                 } else {
                     let type_id = list[0].id();
+                    if let Some(value) = list.iter().find(|value| value.id() != type_id) {
+                        return Err(NbtError::MixedList {
+                            expected: type_id,
+                            found: value.id(),
+                        });
+                    }
                     writer.write_i8(type_id as i8)?;
-                    writer.write_i32::<BigEndian>(list.len() as i32)?;
+                    write_len(writer, list.len())?;
                     for value in list {
                         value.write_unnamed(writer)?;
                     }
@@ -220,4 +157,46 @@ impl Value {
         }
         Ok(())
     }
+
+    pub fn write_named<W: Write>(&self, writer: &mut W, name: &str) -> Result<()> {
+        match self {
+            Value::Compound(map) => write_named(writer, name, map),
+            value => Err(NbtError::InvalidRoot(value.id())),
+        }
+    }
+
+    pub fn write_network<W: Write>(&self, writer: &mut W) -> Result<()> {
+        writer.write_u8(self.id() as u8)?;
+        self.write_unnamed(writer)
+    }
+}
+
+pub(crate) fn write_named<W: Write>(writer: &mut W, name: &str, root: &Compound) -> Result<()> {
+    writer.write_u8(TagId::Compound as u8)?;
+    write_string(writer, name)?;
+    write_compound(writer, root)
+}
+
+fn write_compound<W: Write>(writer: &mut W, map: &Compound) -> Result<()> {
+    for (name, value) in map {
+        writer.write_u8(value.id() as u8)?;
+        write_string(writer, name)?;
+        value.write_unnamed(writer)?;
+    }
+    writer.write_u8(TagId::End as u8)?;
+    Ok(())
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> Result<()> {
+    let len = i32::try_from(len).map_err(|_| NbtError::LengthTooLong(len))?;
+    writer.write_i32::<BigEndian>(len)?;
+    Ok(())
+}
+
+fn write_string<W: Write>(writer: &mut W, value: &str) -> Result<()> {
+    let bytes = mutf8::encode(value);
+    let len = u16::try_from(bytes.len()).map_err(|_| NbtError::StringTooLong(bytes.len()))?;
+    writer.write_u16::<BigEndian>(len)?;
+    writer.write_all(&bytes)?;
+    Ok(())
 }
@@ -0,0 +1,19 @@
+// `compound! { "Health" => 20.0f32, "Pos" => list![0.5, 64.0, 0.5] }`
+#[macro_export]
+macro_rules! compound {
+    () => {
+        $crate::Compound::new()
+    };
+    ($($key:expr => $value:expr),+ $(,)?) => {{
+        let mut compound = $crate::Compound::new();
+        $(compound.insert($key, $value);)+
+        compound
+    }};
+}
+
+#[macro_export]
+macro_rules! list {
+    ($($value:expr),* $(,)?) => {
+        $crate::Value::List(vec![$($crate::Value::from($value)),*])
+    };
+}
@@ -0,0 +1,65 @@
+use std::borrow::Cow;
+
+use crate::{NbtError, Result};
+
+// Java writes NBT strings as Modified UTF-8: U+0000 becomes `C0 80` and
+// characters outside the BMP are stored as two 3-byte surrogates.
+pub(crate) fn decode(bytes: Vec<u8>) -> Result<String> {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(value) => return Ok(value),
+        Err(error) => error.into_bytes(),
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = bytes[index];
+        let (unit, width) = match byte {
+            0x00..=0x7f => (byte as u16, 1),
+            0xc0..=0xdf => {
+                let second = continuation(&bytes, index + 1)?;
+                (((byte as u16 & 0x1f) << 6) | second, 2)
+            }
+            0xe0..=0xef => {
+                let second = continuation(&bytes, index + 1)?;
+                let third = continuation(&bytes, index + 2)?;
+                (((byte as u16 & 0x0f) << 12) | (second << 6) | third, 3)
+            }
+            _ => return Err(NbtError::InvalidString),
+        };
+        units.push(unit);
+        index += width;
+    }
+
+    String::from_utf16(&units).map_err(|_| NbtError::InvalidString)
+}
+
+fn continuation(bytes: &[u8], index: usize) -> Result<u16> {
+    match bytes.get(index) {
+        Some(byte) if byte & 0xc0 == 0x80 => Ok((byte & 0x3f) as u16),
+        _ => Err(NbtError::InvalidString),
+    }
+}
+
+pub(crate) fn encode(value: &str) -> Cow<'_, [u8]> {
+    if !value.chars().any(|c| c == '\0' || c as u32 > 0xffff) {
+        return Cow::Borrowed(value.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(value.len() + 4);
+    for unit in value.encode_utf16() {
+        match unit {
+            0x0001..=0x007f => bytes.push(unit as u8),
+            0x0000 | 0x0080..=0x07ff => {
+                bytes.push(0xc0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+            _ => {
+                bytes.push(0xe0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3f) as u8);
+                bytes.push(0x80 | (unit & 0x3f) as u8);
+            }
+        }
+    }
+    Cow::Owned(bytes)
+}
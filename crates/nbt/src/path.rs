@@ -0,0 +1,193 @@
+use std::{fmt, str::FromStr};
+
+use crate::{Compound, NbtError, Result, Value};
+
+// A subset of the vanilla NBT path syntax: `Inventory[0].tag.Damage`,
+// `"key.with.dots"[-1]`. Indices only select list elements; negative ones
+// count from the end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathNode {
+    Key(String),
+    Index(i32),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NbtPath {
+    nodes: Vec<PathNode>,
+}
+
+impl NbtPath {
+    pub fn parse(path: &str) -> Result<Self> {
+        let error = |message: &str| NbtError::InvalidPath {
+            path: path.to_string(),
+            message: message.to_string(),
+        };
+
+        let mut nodes = Vec::new();
+        let mut chars = path.chars().peekable();
+        while let Some(&c) = chars.peek() {
+            match c {
+                '[' => {
+                    chars.next();
+                    let mut index = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => index.push(c),
+                            None => return Err(error("Unterminated list index")),
+                        }
+                    }
+                    let index = index
+                        .trim()
+                        .parse()
+                        .map_err(|_| error("Expected a list index"))?;
+                    nodes.push(PathNode::Index(index));
+                }
+                '.' if !nodes.is_empty() => {
+                    chars.next();
+                    if matches!(chars.peek(), None | Some('.' | '[')) {
+                        return Err(error("Expected a key after '.'"));
+                    }
+                }
+                '"' | '\'' => {
+                    chars.next();
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('\\') => key.extend(chars.next()),
+                            Some(end) if end == c => break,
+                            Some(other) => key.push(other),
+                            None => return Err(error("Unterminated quoted key")),
+                        }
+                    }
+                    nodes.push(PathNode::Key(key));
+                }
+                _ => {
+                    let mut key = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if matches!(c, '.' | '[' | ']' | '"' | '\'') {
+                            break;
+                        }
+                        key.push(c);
+                        chars.next();
+                    }
+                    if key.is_empty() {
+                        return Err(error("Expected a key"));
+                    }
+                    nodes.push(PathNode::Key(key));
+                }
+            }
+        }
+
+        if nodes.is_empty() {
+            return Err(error("Path is empty"));
+        }
+        Ok(NbtPath { nodes })
+    }
+
+    pub fn nodes(&self) -> &[PathNode] {
+        &self.nodes
+    }
+
+    pub fn get<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.nodes
+            .iter()
+            .try_fold(value, |value, node| step(value, node))
+    }
+
+    pub fn get_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.nodes
+            .iter()
+            .try_fold(value, |value, node| step_mut(value, node))
+    }
+
+    pub fn get_in<'a>(&self, root: &'a Compound) -> Option<&'a Value> {
+        let (PathNode::Key(key), rest) = self.nodes.split_first()? else {
+            return None;
+        };
+        rest.iter()
+            .try_fold(root.get(key)?, |value, node| step(value, node))
+    }
+
+    pub fn get_in_mut<'a>(&self, root: &'a mut Compound) -> Option<&'a mut Value> {
+        let (PathNode::Key(key), rest) = self.nodes.split_first()? else {
+            return None;
+        };
+        rest.iter()
+            .try_fold(root.get_mut(key)?, |value, node| step_mut(value, node))
+    }
+}
+
+fn step<'a>(value: &'a Value, node: &PathNode) -> Option<&'a Value> {
+    match (value, node) {
+        (Value::Compound(map), PathNode::Key(key)) => map.get(key),
+        (Value::List(list), PathNode::Index(index)) => {
+            list_index(list.len(), *index).map(|i| &list[i])
+        }
+        _ => None,
+    }
+}
+
+fn step_mut<'a>(value: &'a mut Value, node: &PathNode) -> Option<&'a mut Value> {
+    match (value, node) {
+        (Value::Compound(map), PathNode::Key(key)) => map.get_mut(key),
+        (Value::List(list), PathNode::Index(index)) => {
+            list_index(list.len(), *index).map(|i| &mut list[i])
+        }
+        _ => None,
+    }
+}
+
+fn list_index(len: usize, index: i32) -> Option<usize> {
+    let index = if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)?
+    } else {
+        index as usize
+    };
+    (index < len).then_some(index)
+}
+
+impl From<Vec<PathNode>> for NbtPath {
+    fn from(nodes: Vec<PathNode>) -> Self {
+        NbtPath { nodes }
+    }
+}
+
+impl FromStr for NbtPath {
+    type Err = NbtError;
+
+    fn from_str(path: &str) -> Result<Self> {
+        NbtPath::parse(path)
+    }
+}
+
+impl fmt::Display for NbtPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, node) in self.nodes.iter().enumerate() {
+            match node {
+                PathNode::Index(index) => write!(f, "[{}]", index)?,
+                PathNode::Key(key) => {
+                    if i > 0 {
+                        f.write_str(".")?;
+                    }
+                    if key.is_empty() || key.contains(['.', '[', ']', '"', '\'']) {
+                        write!(f, "{:?}", key)?;
+                    } else {
+                        f.write_str(key)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    pub fn query(&self, path: &str) -> Option<&Value> {
+        NbtPath::parse(path).ok()?.get(self)
+    }
+
+    pub fn query_mut(&mut self, path: &str) -> Option<&mut Value> {
+        NbtPath::parse(path).ok()?.get_mut(self)
+    }
+}
@@ -0,0 +1,233 @@
+use std::io::Read;
+
+use byteorder::{BigEndian, ReadBytesExt};
+
+use crate::{Compound, NbtError, Result, TagId, Value, mutf8};
+
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+pub const NETWORK_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+// Rough in-memory cost of one decoded tag, charged on top of its payload so a
+// list of millions of empty compounds can't dodge the byte quota.
+const TAG_COST: usize = size_of::<Value>();
+
+// The most elements allocated ahead of reading them. Longer collections grow
+// as their data actually arrives, so a forged length can't reserve memory the
+// input never backs.
+const PREALLOCATE: usize = 4096;
+
+pub struct NbtReader<'a, R> {
+    reader: &'a mut R,
+    max_depth: usize,
+    max_bytes: usize,
+    depth: usize,
+    bytes: usize,
+}
+
+impl<'a, R: Read> NbtReader<'a, R> {
+    pub fn new(reader: &'a mut R) -> Self {
+        NbtReader {
+            reader,
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_bytes: usize::MAX,
+            depth: 0,
+            bytes: 0,
+        }
+    }
+
+    pub fn network(reader: &'a mut R) -> Self {
+        NbtReader::new(reader).max_bytes(NETWORK_MAX_BYTES)
+    }
+
+    pub fn max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    pub fn bytes_read(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn read_named(&mut self) -> Result<(String, Compound)> {
+        let type_id = self.read_tag_id()?;
+        if type_id != TagId::Compound {
+            return Err(NbtError::InvalidRoot(type_id));
+        }
+        let name = self.read_string()?;
+        Ok((name, self.read_compound()?))
+    }
+
+    // A lone TAG_End is how the protocol sends "no NBT".
+    pub fn read_network(&mut self) -> Result<Option<Value>> {
+        match self.read_tag_id()? {
+            TagId::End => Ok(None),
+            type_id => self.read_unnamed(type_id).map(Some),
+        }
+    }
+
+    pub fn read_unnamed(&mut self, type_id: TagId) -> Result<Value> {
+        self.charge(TAG_COST)?;
+        self.read_payload(type_id)
+    }
+
+    // Leaf tags are decoded out of line to keep the recursive frames small.
+    fn read_payload(&mut self, type_id: TagId) -> Result<Value> {
+        match type_id {
+            TagId::List => self.read_list(),
+            TagId::Compound => Ok(Value::Compound(self.read_compound()?)),
+            TagId::End => Err(NbtError::UnexpectedEnd),
+            _ => self.read_leaf(type_id),
+        }
+    }
+
+    fn read_leaf(&mut self, type_id: TagId) -> Result<Value> {
+        match type_id {
+            TagId::Byte => Ok(Value::Byte(self.reader.read_i8()?)),
+            TagId::Short => Ok(Value::Short(self.reader.read_i16::<BigEndian>()?)),
+            TagId::Int => Ok(Value::Int(self.reader.read_i32::<BigEndian>()?)),
+            TagId::Long => Ok(Value::Long(self.reader.read_i64::<BigEndian>()?)),
+            TagId::Float => Ok(Value::Float(self.reader.read_f32::<BigEndian>()?)),
+            TagId::Double => Ok(Value::Double(self.reader.read_f64::<BigEndian>()?)),
+            TagId::String => Ok(Value::String(self.read_string()?)),
+            TagId::ByteArray => self.read_byte_array(),
+            TagId::IntArray => self.read_int_array(),
+            TagId::LongArray => self.read_long_array(),
+            _ => unreachable!("nested tags are read by read_unnamed"),
+        }
+    }
+
+    fn read_byte_array(&mut self) -> Result<Value> {
+        let len = self.read_len(1)?;
+        Ok(Value::ByteArray(self.read_bytes(len)?))
+    }
+
+    fn read_int_array(&mut self) -> Result<Value> {
+        let len = self.read_len(4)?;
+        let values = self.read_chunked(len, |reader, chunk: &mut [i32]| {
+            reader.read_i32_into::<BigEndian>(chunk)
+        })?;
+        Ok(Value::IntArray(values))
+    }
+
+    fn read_long_array(&mut self) -> Result<Value> {
+        let len = self.read_len(8)?;
+        let values = self.read_chunked(len, |reader, chunk: &mut [i64]| {
+            reader.read_i64_into::<BigEndian>(chunk)
+        })?;
+        Ok(Value::LongArray(values))
+    }
+
+    fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>> {
+        self.read_chunked(len, |reader, chunk: &mut [u8]| reader.read_exact(chunk))
+    }
+
+    // Reads `len` elements at most `PREALLOCATE` at a time, so input that
+    // ends early fails before the whole length is allocated.
+    fn read_chunked<T: Default + Clone>(
+        &mut self,
+        len: usize,
+        mut read: impl FnMut(&mut R, &mut [T]) -> std::io::Result<()>,
+    ) -> Result<Vec<T>> {
+        let mut values = Vec::with_capacity(len.min(PREALLOCATE));
+        while values.len() < len {
+            let start = values.len();
+            values.resize(start + (len - start).min(PREALLOCATE), T::default());
+            read(self.reader, &mut values[start..])?;
+        }
+        Ok(values)
+    }
+
+    fn read_list(&mut self) -> Result<Value> {
+        let element_id = self.read_tag_id()?;
+        let len = self.read_len(TAG_COST)?;
+        if element_id == TagId::End && len > 0 {
+            return Err(NbtError::UnexpectedEnd);
+        }
+
+        // The elements were charged with the length.
+        self.enter()?;
+        let mut values = Vec::with_capacity(len.min(PREALLOCATE));
+        for _ in 0..len {
+            values.push(self.read_payload(element_id)?);
+        }
+        self.depth -= 1;
+
+        Ok(Value::List(values))
+    }
+
+    fn read_compound(&mut self) -> Result<Compound> {
+        self.enter()?;
+        let mut map = Compound::default();
+
+        loop {
+            let tag_id = self.read_tag_id()?;
+            if tag_id == TagId::End {
+                break;
+            }
+            let name = self.read_string()?;
+            let value = self.read_unnamed(tag_id)?;
+            map.insert(name, value);
+        }
+
+        self.depth -= 1;
+        Ok(map)
+    }
+
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= self.max_depth {
+            return Err(NbtError::DepthLimit(self.max_depth));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn charge(&mut self, bytes: usize) -> Result<()> {
+        self.bytes = self.bytes.saturating_add(bytes);
+        if self.bytes > self.max_bytes {
+            return Err(NbtError::SizeLimit(self.max_bytes));
+        }
+        Ok(())
+    }
+
+    fn read_tag_id(&mut self) -> Result<TagId> {
+        TagId::try_from(self.reader.read_u8()?)
+    }
+
+    // Lengths are charged up front so a forged length is rejected before
+    // anything is allocated for it.
+    fn read_len(&mut self, element_size: usize) -> Result<usize> {
+        let len = self.reader.read_i32::<BigEndian>()?;
+        if len < 0 {
+            return Err(NbtError::NegativeLength(len));
+        }
+        let len = len as usize;
+        self.charge(len.saturating_mul(element_size))?;
+        Ok(len)
+    }
+
+    fn read_string(&mut self) -> Result<String> {
+        let len = self.reader.read_u16::<BigEndian>()? as usize;
+        self.charge(len)?;
+        let bytes = self.read_bytes(len)?;
+        mutf8::decode(bytes)
+    }
+}
+
+impl Value {
+    pub fn read_unnamed<R: Read>(reader: &mut R, type_id: TagId) -> Result<Self> {
+        NbtReader::new(reader).read_unnamed(type_id)
+    }
+
+    pub fn read_named<R: Read>(reader: &mut R) -> Result<(String, Compound)> {
+        NbtReader::new(reader).read_named()
+    }
+
+    pub fn read_network<R: Read>(reader: &mut R) -> Result<Option<Self>> {
+        NbtReader::network(reader).read_network()
+    }
+}
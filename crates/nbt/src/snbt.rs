@@ -0,0 +1,534 @@
+use std::fmt::Write;
+
+use crate::{Compound, DEFAULT_MAX_DEPTH, NbtError, Result, TagId, Value};
+
+const CONTEXT_LEN: usize = 35;
+const INDENT: &str = "    ";
+
+impl Value {
+    pub fn from_snbt(input: &str) -> Result<Self> {
+        let mut parser = Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        };
+        parser.skip_whitespace();
+        let value = parser.parse_value()?;
+        parser.skip_whitespace();
+        if parser.pos < input.len() {
+            return Err(parser.error("Trailing data found"));
+        }
+        Ok(value)
+    }
+
+    pub fn to_snbt(&self) -> String {
+        let mut out = String::new();
+        write_value(&mut out, self, None);
+        out
+    }
+
+    pub fn to_snbt_pretty(&self) -> String {
+        let mut out = String::new();
+        write_value(&mut out, self, Some(0));
+        out
+    }
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, message: impl Into<String>) -> NbtError {
+        let column = self.input[..self.pos].chars().count() + 1;
+        let start = self.input[..self.pos]
+            .char_indices()
+            .rev()
+            .nth(CONTEXT_LEN - 1)
+            .map_or(0, |(index, _)| index);
+        let prefix = if start > 0 { "..." } else { "" };
+
+        NbtError::Snbt {
+            message: message.into(),
+            column,
+            context: format!("{}{}", prefix, &self.input[start..self.pos]),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.input[self.pos..].chars().nth(offset)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.bump();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<()> {
+        self.skip_whitespace();
+        if self.peek() == Some(expected) {
+            self.bump();
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected '{}'", expected)))
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<Value> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('{') => self.parse_compound(),
+            Some('[') => self.parse_list(),
+            Some('"') | Some('\'') => Ok(Value::String(self.parse_quoted()?)),
+            Some(_) => {
+                let start = self.pos;
+                let token = self.parse_unquoted();
+                if token.is_empty() {
+                    return Err(self.error("Expected value"));
+                }
+                parse_scalar(token).map_err(|message| {
+                    self.pos = start;
+                    self.error(message)
+                })
+            }
+            None => Err(self.error("Expected value")),
+        }
+    }
+
+    // SNBT comes from commands, so nesting is capped like binary NBT is.
+    fn enter(&mut self) -> Result<()> {
+        if self.depth >= DEFAULT_MAX_DEPTH {
+            return Err(NbtError::DepthLimit(DEFAULT_MAX_DEPTH));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn parse_compound(&mut self) -> Result<Value> {
+        self.expect('{')?;
+        self.enter()?;
+        let mut map = Compound::default();
+
+        self.skip_whitespace();
+        while self.peek() != Some('}') {
+            let key = match self.peek() {
+                Some('"') | Some('\'') => self.parse_quoted()?,
+                _ => {
+                    let key = self.parse_unquoted();
+                    if key.is_empty() {
+                        return Err(self.error("Expected key"));
+                    }
+                    key.to_string()
+                }
+            };
+            self.expect(':')?;
+            let value = self.parse_value()?;
+            map.insert(key, value);
+
+            if !self.separator('}')? {
+                break;
+            }
+        }
+        self.expect('}')?;
+        self.depth -= 1;
+
+        Ok(Value::Compound(map))
+    }
+
+    fn parse_list(&mut self) -> Result<Value> {
+        self.expect('[')?;
+
+        if let (Some(kind @ ('B' | 'I' | 'L')), Some(';')) = (self.peek(), self.peek_at(1)) {
+            self.bump();
+            self.bump();
+            return self.parse_array(kind);
+        }
+
+        self.enter()?;
+        let mut values: Vec<Value> = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.pos;
+            let value = self.parse_value()?;
+            if let Some(first) = values.first()
+                && first.id() != value.id()
+            {
+                self.pos = start;
+                return Err(self.error(format!(
+                    "Can't insert {:?} into list of {:?}",
+                    value.id(),
+                    first.id()
+                )));
+            }
+            values.push(value);
+
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+        self.depth -= 1;
+
+        Ok(Value::List(values))
+    }
+
+    fn parse_array(&mut self, kind: char) -> Result<Value> {
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        while self.peek() != Some(']') {
+            let start = self.pos;
+            let value = self.parse_value()?;
+            let number = match value {
+                Value::Byte(v) => Some(v as i64),
+                Value::Short(v) => Some(v as i64),
+                Value::Int(v) => Some(v as i64),
+                Value::Long(v) => Some(v),
+                _ => None,
+            };
+            let fits = match (kind, number) {
+                ('B', Some(v)) => i8::try_from(v).is_ok() || u8::try_from(v).is_ok(),
+                ('I', Some(v)) => i32::try_from(v).is_ok(),
+                ('L', Some(_)) => true,
+                _ => false,
+            };
+            if !fits {
+                self.pos = start;
+                return Err(self.error(format!(
+                    "Can't insert {:?} into {} array",
+                    value.id(),
+                    kind
+                )));
+            }
+            values.push(number.unwrap_or_default());
+
+            if !self.separator(']')? {
+                break;
+            }
+        }
+        self.expect(']')?;
+
+        Ok(match kind {
+            'B' => Value::ByteArray(values.into_iter().map(|v| v as u8).collect()),
+            'I' => Value::IntArray(values.into_iter().map(|v| v as i32).collect()),
+            _ => Value::LongArray(values),
+        })
+    }
+
+    fn separator(&mut self, close: char) -> Result<bool> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(',') => {
+                self.bump();
+                self.skip_whitespace();
+                Ok(true)
+            }
+            Some(c) if c == close => Ok(false),
+            _ => Err(self.error(format!("Expected ',' or '{}'", close))),
+        }
+    }
+
+    fn parse_unquoted(&mut self) -> &'a str {
+        let start = self.pos;
+        while self.peek().is_some_and(is_unquoted_char) {
+            self.bump();
+        }
+        &self.input[start..self.pos]
+    }
+
+    fn parse_quoted(&mut self) -> Result<String> {
+        let quote = self.bump().unwrap_or('"');
+        let mut out = String::new();
+
+        loop {
+            match self.bump() {
+                None => return Err(self.error("Unterminated string")),
+                Some(c) if c == quote => return Ok(out),
+                Some('\\') => {
+                    let start = self.pos;
+                    let escaped = match self.bump() {
+                        Some(c @ ('\\' | '"' | '\'')) => c,
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('s') => ' ',
+                        Some('x') => self.parse_unicode(2)?,
+                        Some('u') => self.parse_unicode(4)?,
+                        Some('U') => self.parse_unicode(8)?,
+                        _ => {
+                            self.pos = start;
+                            return Err(self.error("Invalid escape sequence"));
+                        }
+                    };
+                    out.push(escaped);
+                }
+                Some(c) => out.push(c),
+            }
+        }
+    }
+
+    fn parse_unicode(&mut self, digits: usize) -> Result<char> {
+        let start = self.pos;
+        let end = self.input[start..]
+            .char_indices()
+            .nth(digits)
+            .map_or(self.input.len(), |(index, _)| start + index);
+        let hex = &self.input[start..end];
+
+        let c = if hex.len() == digits {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else {
+            None
+        };
+        match c {
+            Some(c) => {
+                self.pos = end;
+                Ok(c)
+            }
+            None => Err(self.error("Invalid unicode escape")),
+        }
+    }
+}
+
+fn is_unquoted_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '+')
+}
+
+fn parse_scalar(token: &str) -> std::result::Result<Value, String> {
+    match token {
+        "true" => return Ok(Value::Byte(1)),
+        "false" => return Ok(Value::Byte(0)),
+        _ => {}
+    }
+
+    let (body, suffix) = match token.char_indices().last() {
+        Some((index, c)) if c.is_ascii_alphabetic() && index > 0 => {
+            (&token[..index], Some(c.to_ascii_lowercase()))
+        }
+        _ => (token, None),
+    };
+
+    // Not vanilla SNBT, but what the printer writes for them so they
+    // round-trip.
+    if let Some(value) = non_finite(body) {
+        match suffix {
+            Some('f') => return Ok(Value::Float(value as f32)),
+            Some('d') => return Ok(Value::Double(value)),
+            _ => {}
+        }
+    }
+
+    if is_integer(body) {
+        let out_of_range = || format!("Number {} is out of range", token);
+        return match suffix {
+            Some('b') => body.parse().map(Value::Byte).map_err(|_| out_of_range()),
+            Some('s') => body.parse().map(Value::Short).map_err(|_| out_of_range()),
+            Some('l') => body.parse().map(Value::Long).map_err(|_| out_of_range()),
+            Some('f') => body.parse().map(Value::Float).map_err(|_| out_of_range()),
+            Some('d') => body.parse().map(Value::Double).map_err(|_| out_of_range()),
+            None => Ok(body
+                .parse()
+                .map(Value::Int)
+                .unwrap_or_else(|_| Value::String(token.to_string()))),
+            Some(_) => Ok(Value::String(token.to_string())),
+        };
+    }
+
+    if is_decimal(body) {
+        return match suffix {
+            Some('f') => body
+                .parse()
+                .map(Value::Float)
+                .map_err(|_| format!("Invalid float {}", token)),
+            Some('d') | None => body
+                .parse()
+                .map(Value::Double)
+                .map_err(|_| format!("Invalid double {}", token)),
+            Some(_) => Ok(Value::String(token.to_string())),
+        };
+    }
+
+    Ok(Value::String(token.to_string()))
+}
+
+fn non_finite(body: &str) -> Option<f64> {
+    match body {
+        "NaN" => Some(f64::NAN),
+        "Infinity" | "+Infinity" => Some(f64::INFINITY),
+        "-Infinity" => Some(f64::NEG_INFINITY),
+        _ => None,
+    }
+}
+
+fn is_integer(body: &str) -> bool {
+    let digits = body.strip_prefix(['-', '+']).unwrap_or(body);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_decimal(body: &str) -> bool {
+    let body = body.strip_prefix(['-', '+']).unwrap_or(body);
+    let (mantissa, exponent) = match body.find(['e', 'E']) {
+        Some(index) => (&body[..index], Some(&body[index + 1..])),
+        None => (body, None),
+    };
+
+    let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let valid_mantissa = (!whole.is_empty() || !fraction.is_empty())
+        && whole.bytes().all(|b| b.is_ascii_digit())
+        && fraction.bytes().all(|b| b.is_ascii_digit());
+
+    valid_mantissa && exponent.is_none_or(is_integer)
+}
+
+fn write_value(out: &mut String, value: &Value, indent: Option<usize>) {
+    match value {
+        Value::Byte(v) => _ = write!(out, "{}b", v),
+        Value::Short(v) => _ = write!(out, "{}s", v),
+        Value::Int(v) => _ = write!(out, "{}", v),
+        Value::Long(v) => _ = write!(out, "{}L", v),
+        Value::Float(v) if v.is_finite() => _ = write!(out, "{:?}f", v),
+        Value::Double(v) if v.is_finite() => _ = write!(out, "{:?}d", v),
+        Value::Float(v) => write_non_finite(out, *v as f64, 'f'),
+        Value::Double(v) => write_non_finite(out, *v, 'd'),
+        Value::String(v) => write_quoted(out, v),
+        Value::ByteArray(v) => {
+            let values = v.iter().map(|v| format!("{}b", *v as i8));
+            write_array(out, 'B', values, indent);
+        }
+        Value::IntArray(v) => write_array(out, 'I', v.iter().map(|v| v.to_string()), indent),
+        Value::LongArray(v) => write_array(out, 'L', v.iter().map(|v| format!("{}L", v)), indent),
+        Value::List(list) => {
+            let nested = list
+                .first()
+                .is_some_and(|v| matches!(v.id(), TagId::Compound | TagId::List));
+            write_sequence(out, '[', ']', list, indent.filter(|_| nested), write_value);
+        }
+        Value::Compound(map) => {
+            write_sequence(out, '{', '}', map, indent, |out, (key, v), i| {
+                write_key(out, key);
+                out.push(':');
+                if i.is_some() {
+                    out.push(' ');
+                }
+                write_value(out, v, i);
+            });
+        }
+    }
+}
+
+fn write_non_finite(out: &mut String, value: f64, suffix: char) {
+    let body = if value.is_nan() {
+        "NaN"
+    } else if value > 0.0 {
+        "Infinity"
+    } else {
+        "-Infinity"
+    };
+    out.push_str(body);
+    out.push(suffix);
+}
+
+fn write_sequence<I, F>(
+    out: &mut String,
+    open: char,
+    close: char,
+    items: I,
+    indent: Option<usize>,
+    mut write_item: F,
+) where
+    I: IntoIterator,
+    F: FnMut(&mut String, I::Item, Option<usize>),
+{
+    let mut items = items.into_iter().peekable();
+    out.push(open);
+    if items.peek().is_none() {
+        out.push(close);
+        return;
+    }
+
+    let inner = indent.map(|level| level + 1);
+    while let Some(item) = items.next() {
+        if let Some(level) = inner {
+            out.push('\n');
+            out.push_str(&INDENT.repeat(level));
+        }
+        write_item(out, item, inner);
+        if items.peek().is_some() {
+            out.push(',');
+        }
+    }
+    if let Some(level) = indent {
+        out.push('\n');
+        out.push_str(&INDENT.repeat(level));
+    }
+    out.push(close);
+}
+
+fn write_array(
+    out: &mut String,
+    kind: char,
+    values: impl Iterator<Item = String>,
+    indent: Option<usize>,
+) {
+    let (first, rest) = if indent.is_some() {
+        (" ", ", ")
+    } else {
+        ("", ",")
+    };
+
+    out.push('[');
+    out.push(kind);
+    out.push(';');
+    for (index, value) in values.enumerate() {
+        out.push_str(if index == 0 { first } else { rest });
+        out.push_str(&value);
+    }
+    out.push(']');
+}
+
+fn write_key(out: &mut String, key: &str) {
+    if !key.is_empty() && key.chars().all(is_unquoted_char) {
+        out.push_str(key);
+    } else {
+        write_quoted(out, key);
+    }
+}
+
+fn write_quoted(out: &mut String, value: &str) {
+    let quote = if value.contains('"') && !value.contains('\'') {
+        '\''
+    } else {
+        '"'
+    };
+
+    out.push(quote);
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\t' => out.push_str("\\t"),
+            '\r' => out.push_str("\\r"),
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_control() => _ = write!(out, "\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push(quote);
+}
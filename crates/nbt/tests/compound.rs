@@ -0,0 +1,124 @@
+use nbt::{Compound, IntArray, NbtError, NbtPath, PathNode, Value, compound, list};
+
+fn player() -> Compound {
+    compound! {
+        "Health" => 20.0f32,
+        "OnGround" => true,
+        "Pos" => list![0.5, 64.0, -3.5],
+        "UUID" => IntArray(vec![1, 2, 3, 4]),
+        "Inventory" => list![
+            compound! {
+                "Slot" => 0i8,
+                "id" => "minecraft:diamond_sword",
+                "tag" => compound! { "Damage" => 12 },
+            },
+            compound! { "Slot" => 1i8, "id" => "minecraft:stone" },
+        ],
+        "weird.key" => compound! { "[x]" => 1i16 },
+    }
+}
+
+#[test]
+fn keys_keep_insertion_order() {
+    let mut map = Compound::new();
+    for key in ["zeta", "alpha", "mid", "beta", "omega"] {
+        map.insert(key, key.len() as i32);
+    }
+    map.insert("alpha", 0);
+    map.remove("mid");
+
+    let keys: Vec<_> = map.keys().map(String::as_str).collect();
+    assert_eq!(keys, ["zeta", "alpha", "beta", "omega"]);
+
+    let mut first = Vec::new();
+    Value::Compound(map.clone())
+        .write_named(&mut first, "")
+        .unwrap();
+    let (_, decoded) = Value::read_named(&mut &first[..]).unwrap();
+    let mut second = Vec::new();
+    Value::Compound(decoded)
+        .write_named(&mut second, "")
+        .unwrap();
+    assert_eq!(first, second);
+    assert_eq!(
+        Value::Compound(map).to_snbt(),
+        "{zeta:4,alpha:0,beta:4,omega:5}"
+    );
+}
+
+#[test]
+fn typed_getters() {
+    let player = player();
+
+    assert_eq!(player.get_f32("Health"), Some(20.0));
+    assert_eq!(player.get_i32("Health"), Some(20));
+    assert_eq!(player.get_bool("OnGround"), Some(true));
+    assert_eq!(
+        player.get_list_of::<f64>("Pos"),
+        Some(vec![0.5, 64.0, -3.5])
+    );
+    assert_eq!(player.get_list_of::<&str>("Pos"), None);
+    assert_eq!(player.get_int_array("UUID"), Some(&[1, 2, 3, 4][..]));
+    assert_eq!(player.get_str("Health"), None);
+    assert_eq!(player.get_i32("Missing"), None);
+
+    let inventory = player.get_list_of::<&Compound>("Inventory").unwrap();
+    assert_eq!(inventory[1].get_str("id"), Some("minecraft:stone"));
+    assert_eq!(
+        inventory[0]
+            .get_compound("tag")
+            .and_then(|tag| tag.get_i64("Damage")),
+        Some(12)
+    );
+}
+
+#[test]
+fn path_queries() {
+    let mut player = player();
+
+    assert_eq!(
+        player.query("Inventory[0].tag.Damage"),
+        Some(&Value::Int(12))
+    );
+    assert_eq!(
+        player.query("Inventory[-1].id"),
+        Some(&Value::String("minecraft:stone".to_string()))
+    );
+    assert_eq!(player.query("\"weird.key\".'[x]'"), Some(&Value::Short(1)));
+    assert_eq!(player.query("Pos[3]"), None);
+    assert_eq!(player.query("Inventory[-3]"), None);
+    assert_eq!(player.query("Health.value"), None);
+    assert_eq!(player.query("Inventory[0"), None);
+
+    *player.query_mut("Inventory[0].tag.Damage").unwrap() = Value::Int(0);
+    assert_eq!(
+        player.query("Inventory[0].tag.Damage"),
+        Some(&Value::Int(0))
+    );
+
+    let value = Value::Compound(player);
+    assert_eq!(value.query("Pos[1]").and_then(Value::as_f64), Some(64.0));
+}
+
+#[test]
+fn path_parsing() {
+    let path: NbtPath = "Inventory[0].tag.\"a b\"".parse().unwrap();
+    assert_eq!(
+        path.nodes(),
+        [
+            PathNode::Key("Inventory".to_string()),
+            PathNode::Index(0),
+            PathNode::Key("tag".to_string()),
+            PathNode::Key("a b".to_string()),
+        ]
+    );
+    assert_eq!(path.to_string(), "Inventory[0].tag.a b");
+
+    for invalid in ["", ".a", "a..b", "a.", "a[x]", "a[1", "'open"] {
+        assert!(
+            matches!(NbtPath::parse(invalid), Err(NbtError::InvalidPath { .. })),
+            "{:?}",
+            invalid
+        );
+    }
+}
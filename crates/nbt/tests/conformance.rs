@@ -0,0 +1,223 @@
+use std::io::{Cursor, Read};
+
+use nbt::{Compound, Compression, NbtError, TagId, Value};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn fixture(name: &str) -> String {
+    format!("{}/{}", FIXTURES, name)
+}
+
+fn compound(entries: Vec<(&str, Value)>) -> Compound {
+    entries
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect()
+}
+
+fn encode(value: &Value) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value.write_unnamed(&mut bytes).unwrap();
+    bytes
+}
+
+fn round_trip(value: &Value) -> Value {
+    let bytes = encode(value);
+    let mut cursor = Cursor::new(&bytes[..]);
+    let decoded = Value::read_unnamed(&mut cursor, value.id()).unwrap();
+    assert_eq!(cursor.position() as usize, bytes.len());
+    decoded
+}
+
+fn bigtest() -> Compound {
+    let named = |name: &str, value: f32| {
+        Value::Compound(compound(vec![
+            ("name", Value::String(name.to_string())),
+            ("value", Value::Float(value)),
+        ]))
+    };
+    let created = |index: i32| {
+        Value::Compound(compound(vec![
+            ("created-on", Value::Long(1264099775885)),
+            ("name", Value::String(format!("Compound tag #{}", index))),
+        ]))
+    };
+    let bytes = (0..1000u32).map(|n| ((n * n * 255 + n * 7) % 100) as u8);
+
+    compound(vec![
+        (
+            "nested compound test",
+            Value::Compound(compound(vec![
+                ("egg", named("Eggbert", 0.5)),
+                ("ham", named("Hampus", 0.75)),
+            ])),
+        ),
+        ("intTest", Value::Int(2147483647)),
+        ("byteTest", Value::Byte(127)),
+        (
+            "stringTest",
+            Value::String("HELLO WORLD THIS IS A TEST STRING ÅÄÖ!".to_string()),
+        ),
+        (
+            "listTest (long)",
+            Value::List((11..=15).map(Value::Long).collect()),
+        ),
+        ("doubleTest", Value::Double(0.4931287132182315)),
+        ("floatTest", Value::Float(0.49823147)),
+        ("longTest", Value::Long(9223372036854775807)),
+        (
+            "listTest (compound)",
+            Value::List(vec![created(0), created(1)]),
+        ),
+        (
+            "byteArrayTest (the first 1000 values of (n*n*255+n*7)%100, starting with n=0 (0, 62, 34, 16, 8, ...))",
+            Value::ByteArray(bytes.collect()),
+        ),
+        ("shortTest", Value::Short(32767)),
+    ])
+}
+
+#[test]
+fn reads_bigtest_fixture() {
+    let (name, root) = nbt::read_file(fixture("bigtest.nbt")).unwrap();
+
+    assert_eq!(name, "Level");
+    assert_eq!(root, bigtest());
+}
+
+#[test]
+fn bigtest_round_trips_through_every_compression() {
+    for compression in [Compression::None, Compression::Gzip, Compression::Zlib] {
+        let mut bytes = Vec::new();
+        nbt::write_compressed(&mut bytes, "Level", &bigtest(), compression).unwrap();
+        assert_eq!(Compression::detect(&bytes), compression);
+
+        let (name, root) = nbt::read_compressed(&mut Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(name, "Level");
+        assert_eq!(root, bigtest());
+    }
+}
+
+#[test]
+fn writes_fixture_byte_for_byte() {
+    let expected = std::fs::read(fixture("hello_world.nbt")).unwrap();
+    let root = compound(vec![("name", Value::String("Bananrama".to_string()))]);
+
+    let mut bytes = Vec::new();
+    Value::Compound(root.clone())
+        .write_named(&mut bytes, "hello world")
+        .unwrap();
+    assert_eq!(bytes, expected);
+
+    let (name, decoded) = Value::read_named(&mut Cursor::new(&expected[..])).unwrap();
+    assert_eq!(name, "hello world");
+    assert_eq!(decoded, root);
+}
+
+#[test]
+fn bigtest_fixture_writes_back_byte_for_byte() {
+    let compressed = std::fs::read(fixture("bigtest.nbt")).unwrap();
+    let mut expected = Vec::new();
+    flate2::read::GzDecoder::new(&compressed[..])
+        .read_to_end(&mut expected)
+        .unwrap();
+
+    let (name, root) = nbt::read_compressed(&mut Cursor::new(&compressed[..])).unwrap();
+    let mut bytes = Vec::new();
+    nbt::write_compressed(&mut bytes, &name, &root, Compression::None).unwrap();
+    assert_eq!(bytes, expected);
+}
+
+#[test]
+fn compound_children_keep_their_tag_type() {
+    let value = Value::Compound(compound(vec![("count", Value::Int(7))]));
+
+    assert_eq!(
+        encode(&value),
+        [&[3, 0, 5][..], b"count", &[0, 0, 0, 7, 0]].concat()
+    );
+}
+
+#[test]
+fn every_tag_type_round_trips() {
+    let values = vec![
+        Value::Byte(-128),
+        Value::Short(i16::MIN),
+        Value::Int(i32::MIN),
+        Value::Long(i64::MIN),
+        Value::Float(f32::MAX),
+        Value::Double(f64::MIN_POSITIVE),
+        Value::String(String::new()),
+        Value::String("unicode ✓".to_string()),
+        Value::List(vec![]),
+        Value::List(vec![Value::Short(1), Value::Short(2)]),
+        Value::List(vec![Value::List(vec![Value::Int(1)]), Value::List(vec![])]),
+        Value::new_compound(),
+        Value::ByteArray(vec![0, 1, 255]),
+        Value::IntArray(vec![i32::MIN, 0, i32::MAX]),
+        Value::LongArray(vec![i64::MIN, 0, i64::MAX]),
+    ];
+
+    for value in &values {
+        assert_eq!(&round_trip(value), value);
+    }
+
+    let all = Value::Compound(
+        values
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (format!("tag{}", index), value.clone()))
+            .collect(),
+    );
+    assert_eq!(round_trip(&all), all);
+}
+
+#[test]
+fn encoding_is_stable() {
+    let value = Value::List(vec![Value::Compound(bigtest())]);
+    let decoded = round_trip(&value);
+
+    assert_eq!(round_trip(&decoded), value);
+    assert_eq!(
+        encode(&Value::List(vec![Value::Int(1), Value::Int(2)])),
+        [3, 0, 0, 0, 2, 0, 0, 0, 1, 0, 0, 0, 2]
+    );
+    assert_eq!(encode(&Value::List(vec![])), [0, 0, 0, 0, 0]);
+}
+
+#[test]
+fn mixed_list_is_rejected() {
+    let value = Value::List(vec![Value::Int(1), Value::String("two".to_string())]);
+
+    let mut bytes = Vec::new();
+    let error = value.write_unnamed(&mut bytes).unwrap_err();
+    assert!(matches!(
+        error,
+        NbtError::MixedList {
+            expected: TagId::Int,
+            found: TagId::String
+        }
+    ));
+    assert!(bytes.is_empty());
+}
+
+#[test]
+fn malformed_input_is_a_typed_error() {
+    let invalid_tag = [10, 0, 0, 13, 0, 0];
+    assert!(matches!(
+        Value::read_named(&mut Cursor::new(&invalid_tag[..])),
+        Err(NbtError::InvalidTagId(13))
+    ));
+
+    let negative = [255, 255, 255, 255];
+    assert!(matches!(
+        Value::read_unnamed(&mut Cursor::new(&negative[..]), TagId::IntArray),
+        Err(NbtError::NegativeLength(-1))
+    ));
+
+    let not_compound = [8, 0, 0, 0, 0];
+    assert!(matches!(
+        Value::read_named(&mut Cursor::new(&not_compound[..])),
+        Err(NbtError::InvalidRoot(TagId::String))
+    ));
+}
@@ -0,0 +1,124 @@
+use std::io::Cursor;
+
+use nbt::{NbtError, NbtReader, TagId, Value};
+
+fn nested_lists(depth: usize) -> Vec<u8> {
+    let mut bytes = vec![TagId::List as u8];
+    for _ in 0..depth {
+        bytes.extend_from_slice(&[TagId::List as u8, 0, 0, 0, 1]);
+    }
+    bytes.extend_from_slice(&[TagId::End as u8, 0, 0, 0, 0]);
+    bytes
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    let bytes = nested_lists(100_000);
+    let result = Value::read_network(&mut Cursor::new(&bytes[..]));
+    assert!(matches!(result, Err(NbtError::DepthLimit(512))));
+
+    let bytes = nested_lists(10);
+    let mut cursor = Cursor::new(&bytes[..]);
+    let result = NbtReader::new(&mut cursor).max_depth(4).read_network();
+    assert!(matches!(result, Err(NbtError::DepthLimit(4))));
+
+    let mut cursor = Cursor::new(&bytes[..]);
+    assert!(NbtReader::new(&mut cursor).read_network().is_ok());
+}
+
+#[test]
+fn forged_lengths_are_rejected_before_allocating() {
+    for tag in [TagId::ByteArray, TagId::IntArray, TagId::LongArray] {
+        let bytes = [tag as u8, 0x7f, 0xff, 0xff, 0xff];
+        let result = Value::read_network(&mut Cursor::new(&bytes[..]));
+        assert!(matches!(result, Err(NbtError::SizeLimit(_))), "{:?}", tag);
+    }
+
+    let list = [
+        TagId::List as u8,
+        TagId::Compound as u8,
+        0x7f,
+        0xff,
+        0xff,
+        0xff,
+    ];
+    let result = Value::read_network(&mut Cursor::new(&list[..]));
+    assert!(matches!(result, Err(NbtError::SizeLimit(_))));
+
+    // Without a quota the length is only trusted as far as the input goes.
+    for tag in [TagId::ByteArray, TagId::IntArray, TagId::LongArray] {
+        let unbounded = [tag as u8, 0x7f, 0xff, 0xff, 0xff, 1, 2, 3];
+        let mut cursor = Cursor::new(&unbounded[..]);
+        let result = NbtReader::new(&mut cursor).read_network();
+        assert!(matches!(result, Err(NbtError::Io(_))), "{:?}", tag);
+    }
+    let unbounded = [TagId::List as u8, TagId::Long as u8, 0x7f, 0xff, 0xff, 0xff];
+    let mut cursor = Cursor::new(&unbounded[..]);
+    let result = NbtReader::new(&mut cursor).read_network();
+    assert!(matches!(result, Err(NbtError::Io(_))));
+
+    let negative = [TagId::List as u8, TagId::Int as u8, 0xff, 0xff, 0xff, 0xfe];
+    let result = Value::read_network(&mut Cursor::new(&negative[..]));
+    assert!(matches!(result, Err(NbtError::NegativeLength(-2))));
+}
+
+#[test]
+fn byte_quota_counts_the_whole_tree() {
+    let value = Value::List(vec![Value::String("x".repeat(1000)); 10]);
+    let mut bytes = Vec::new();
+    value.write_network(&mut bytes).unwrap();
+
+    let mut cursor = Cursor::new(&bytes[..]);
+    let result = NbtReader::new(&mut cursor).max_bytes(5000).read_network();
+    assert!(matches!(result, Err(NbtError::SizeLimit(5000))));
+
+    let mut cursor = Cursor::new(&bytes[..]);
+    let mut reader = NbtReader::new(&mut cursor).max_bytes(20_000);
+    assert_eq!(reader.read_network().unwrap(), Some(value));
+    assert!(reader.bytes_read() > 10_000);
+}
+
+#[test]
+fn list_elements_are_charged_once() {
+    let bytes_read = |len: usize| {
+        let mut bytes = Vec::new();
+        Value::List(vec![Value::Int(0); len])
+            .write_network(&mut bytes)
+            .unwrap();
+        let mut cursor = Cursor::new(&bytes[..]);
+        let mut reader = NbtReader::new(&mut cursor);
+        reader.read_network().unwrap();
+        reader.bytes_read()
+    };
+
+    assert_eq!(bytes_read(20) - bytes_read(10), 10 * size_of::<Value>());
+}
+
+#[test]
+fn strings_use_modified_utf8() {
+    let value = Value::String("a\0b😀".to_string());
+    let mut bytes = Vec::new();
+    value.write_network(&mut bytes).unwrap();
+
+    assert_eq!(
+        bytes,
+        [
+            8, 0, 10, b'a', 0xc0, 0x80, b'b', 0xed, 0xa0, 0xbd, 0xed, 0xb8, 0x80
+        ]
+    );
+    assert_eq!(
+        Value::read_network(&mut Cursor::new(&bytes[..])).unwrap(),
+        Some(value)
+    );
+
+    let plain = Value::String("plain é".to_string());
+    let mut bytes = Vec::new();
+    plain.write_network(&mut bytes).unwrap();
+    assert_eq!(&bytes[3..], "plain é".as_bytes());
+
+    let invalid = [8, 0, 2, 0xe0, 0x80];
+    assert!(matches!(
+        Value::read_network(&mut Cursor::new(&invalid[..])),
+        Err(NbtError::InvalidString)
+    ));
+}
@@ -0,0 +1,164 @@
+use nbt::{Change, NbtError, Value, compound, list};
+use serde_json::json;
+
+fn entity() -> Value {
+    Value::from_snbt(
+        "{id:\"minecraft:zombie\",Health:20.0f,Age:-3s,Pos:[1.5d,64.0d,-2.25d],\
+         UUID:[I;1,-2,3,4],Seed:9000000000L,Flags:[B;1b,-1b],Tags:[],\
+         Equipment:{head:{id:\"minecraft:carved_pumpkin\",count:1b}}}",
+    )
+    .unwrap()
+}
+
+#[test]
+fn plain_json_keeps_values() {
+    let json = entity().to_json();
+
+    assert_eq!(
+        json,
+        json!({
+            "id": "minecraft:zombie",
+            "Health": 20.0,
+            "Age": -3,
+            "Pos": [1.5, 64.0, -2.25],
+            "UUID": [1, -2, 3, 4],
+            "Seed": 9000000000i64,
+            "Flags": [1, -1],
+            "Tags": [],
+            "Equipment": {"head": {"id": "minecraft:carved_pumpkin", "count": 1}},
+        })
+    );
+    assert_eq!(Value::Float(0.1).to_json(), json!(0.1));
+    assert_eq!(Value::Double(f64::NAN).to_json(), json!(null));
+
+    let back = Value::from_json(&json).unwrap();
+    assert_eq!(back.query("Age"), Some(&Value::Int(-3)));
+    assert_eq!(back.query("Seed"), Some(&Value::Long(9000000000)));
+    assert_eq!(back.query("Health"), Some(&Value::Double(20.0)));
+    assert_eq!(back.query("UUID[1]"), Some(&Value::Int(-2)));
+}
+
+#[test]
+fn plain_json_widens_numeric_lists() {
+    assert_eq!(
+        Value::from_json(&json!([1, 9000000000i64])).unwrap(),
+        Value::List(vec![Value::Long(1), Value::Long(9000000000)])
+    );
+    assert_eq!(Value::from_json(&json!([1, 2.5])).unwrap(), list![1.0, 2.5]);
+    assert_eq!(
+        Value::from_json(&json!({"on": true})).unwrap(),
+        Value::Compound(compound! { "on" => 1i8 })
+    );
+    assert!(matches!(
+        Value::from_json(&json!([1, "two"])),
+        Err(NbtError::MixedList { .. })
+    ));
+    assert!(matches!(
+        Value::from_json(&json!({"a": null})),
+        Err(NbtError::InvalidJson(_))
+    ));
+}
+
+#[test]
+fn typed_json_round_trips_exactly() {
+    let mut value = entity();
+    value
+        .as_compound_mut()
+        .unwrap()
+        .insert("Odd", list![f32::INFINITY, f32::NAN, 0.1f32]);
+
+    let json = value.to_typed_json();
+    assert_eq!(json["value"]["Age"], json!({"type": "short", "value": -3}));
+    assert_eq!(
+        json["value"]["Odd"]["value"][0],
+        json!({"type": "float", "value": "inf"})
+    );
+
+    let back = Value::from_typed_json(&json).unwrap();
+    assert_eq!(back.to_snbt(), value.to_snbt());
+
+    let text = serde_json::to_string(&json).unwrap();
+    let reparsed: serde_json::Value = serde_json::from_str(&text).unwrap();
+    assert_eq!(
+        Value::from_typed_json(&reparsed).unwrap().to_snbt(),
+        value.to_snbt()
+    );
+
+    assert!(Value::from_typed_json(&json!({"type": "byte", "value": 300})).is_err());
+    assert!(Value::from_typed_json(&json!({"type": "nope", "value": 1})).is_err());
+    assert!(Value::from_typed_json(&json!(1)).is_err());
+}
+
+#[test]
+fn diff_reports_paths() {
+    let old = entity();
+    let new = Value::from_snbt(
+        "{id:\"minecraft:zombie\",Health:15.0f,Age:-3s,Pos:[1.5d,64.0d],\
+         UUID:[I;1,-2,3,5],Seed:9000000000L,Flags:[B;1b,-1b],Tags:[\"boss\"],\
+         Equipment:{head:{id:\"minecraft:carved_pumpkin\",count:1b,tag:{}}},Fire:0s}",
+    )
+    .unwrap();
+
+    let changes = nbt::diff(&old, &new);
+    let summary: Vec<String> = changes
+        .iter()
+        .map(|change| match change {
+            Change::Added { path, .. } => format!("+{}", path),
+            Change::Removed { path, .. } => format!("-{}", path),
+            Change::Changed { path, .. } => format!("~{}", path),
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            "~Health",
+            "-Pos[2]",
+            "~UUID",
+            "+Tags[0]",
+            "+Equipment.head.tag",
+            "+Fire",
+        ]
+    );
+    assert_eq!(
+        changes[0],
+        Change::Changed {
+            path: "Health".parse().unwrap(),
+            old: &Value::Float(20.0),
+            new: &Value::Float(15.0),
+        }
+    );
+    assert!(nbt::diff(&old, &old).is_empty());
+
+    let retyped = nbt::diff(&Value::Int(1), &Value::Long(1));
+    assert_eq!(retyped.len(), 1);
+    assert_eq!(retyped[0].path().to_string(), "");
+}
+
+#[test]
+fn merge_follows_data_merge() {
+    let mut target = entity();
+    let patch = Value::from_snbt(
+        "{Health:5.0f,Pos:[0.0d],Equipment:{head:{count:2b},feet:{id:\"minecraft:stone\"}},\
+         Tags:\"not a list\"}",
+    )
+    .unwrap();
+    target.merge(&patch);
+
+    assert_eq!(target.query("Health"), Some(&Value::Float(5.0)));
+    assert_eq!(target.query("Pos"), Some(&list![0.0]));
+    assert_eq!(target.query("Equipment.head.count"), Some(&Value::Byte(2)));
+    assert_eq!(
+        target.query("Equipment.head.id"),
+        Some(&Value::String("minecraft:carved_pumpkin".to_string()))
+    );
+    assert!(target.query("Equipment.feet.id").is_some());
+    assert_eq!(
+        target.query("Tags"),
+        Some(&Value::String("not a list".to_string()))
+    );
+    assert_eq!(target.query("id"), entity().query("id"));
+
+    let mut scalar = Value::Int(1);
+    scalar.merge(&patch);
+    assert_eq!(scalar, patch);
+}
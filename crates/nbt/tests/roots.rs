@@ -0,0 +1,87 @@
+use std::io::Cursor;
+
+use nbt::{Compound, Compression, NbtError, TagId, Value};
+
+fn sample() -> Compound {
+    let mut root = Compound::default();
+    root.insert("name".to_string(), Value::String("Bananrama".to_string()));
+    root.insert("count".to_string(), Value::Int(3));
+    root.insert(
+        "list".to_string(),
+        Value::List(vec![Value::Short(1), Value::Short(2)]),
+    );
+    root
+}
+
+#[test]
+fn named_root_round_trips() {
+    let mut bytes = Vec::new();
+    Value::Compound(sample())
+        .write_named(&mut bytes, "root")
+        .unwrap();
+    assert_eq!(&bytes[..7], [10, 0, 4, b'r', b'o', b'o', b't']);
+
+    let (name, root) = Value::read_named(&mut Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(name, "root");
+    assert_eq!(root, sample());
+}
+
+#[test]
+fn named_root_must_be_a_compound() {
+    let mut bytes = Vec::new();
+    Value::Int(1).write_named(&mut bytes, "").unwrap_err();
+
+    let not_compound = [3, 0, 0, 0, 0, 0, 1];
+    assert!(matches!(
+        Value::read_named(&mut Cursor::new(&not_compound[..])),
+        Err(NbtError::InvalidRoot(TagId::Int))
+    ));
+}
+
+#[test]
+fn network_root_has_no_name() {
+    let value = Value::String("hi".to_string());
+
+    let mut bytes = Vec::new();
+    value.write_network(&mut bytes).unwrap();
+    assert_eq!(bytes, [8, 0, 2, b'h', b'i']);
+    assert_eq!(
+        Value::read_network(&mut Cursor::new(&bytes[..])).unwrap(),
+        Some(value)
+    );
+}
+
+#[test]
+fn network_end_tag_is_no_nbt() {
+    let mut cursor = Cursor::new(&[0u8][..]);
+    assert_eq!(Value::read_network(&mut cursor).unwrap(), None);
+    assert_eq!(cursor.position(), 1);
+}
+
+#[test]
+fn round_trips_through_every_compression() {
+    for compression in [Compression::None, Compression::Gzip, Compression::Zlib] {
+        let mut bytes = Vec::new();
+        nbt::write_compressed(&mut bytes, "data", &sample(), compression).unwrap();
+        assert_eq!(Compression::detect(&bytes), compression);
+
+        let (name, root) = nbt::read_compressed(&mut Cursor::new(&bytes[..])).unwrap();
+        assert_eq!(name, "data");
+        assert_eq!(root, sample());
+    }
+}
+
+#[test]
+fn files_round_trip() {
+    let path = std::env::temp_dir().join(format!("nbt-roots-{}.dat", std::process::id()));
+
+    for compression in [Compression::None, Compression::Gzip, Compression::Zlib] {
+        nbt::write_file(&path, "level", &sample(), compression).unwrap();
+        let (name, root) = nbt::read_file(&path).unwrap();
+        assert_eq!(name, "level");
+        assert_eq!(root, sample());
+    }
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(nbt::read_file(&path), Err(NbtError::Io(_))));
+}
@@ -0,0 +1,157 @@
+use std::io::Cursor;
+
+use nbt::{Compound, IntArray, LongArray, NbtError, Value};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+enum GameMode {
+    Survival,
+    Creative,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+struct Item {
+    id: String,
+    count: i8,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+struct PlayerData {
+    #[serde(rename = "Pos")]
+    pos: Vec<f64>,
+    #[serde(rename = "UUID", with = "nbt::int_array")]
+    uuid: Vec<i32>,
+    seeds: LongArray,
+    #[serde(with = "nbt::byte_array")]
+    flags: Vec<u8>,
+    on_ground: bool,
+    health: f32,
+    xp: u32,
+    mode: GameMode,
+    inventory: Vec<Item>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    nickname: Option<String>,
+    extra: Value,
+}
+
+fn player() -> PlayerData {
+    PlayerData {
+        pos: vec![1.5, 64.0, -3.0],
+        uuid: vec![1, 2, 3, 4],
+        seeds: LongArray(vec![i64::MAX]),
+        flags: vec![0, 200],
+        on_ground: true,
+        health: 20.0,
+        xp: u32::MAX,
+        mode: GameMode::Creative,
+        inventory: vec![Item {
+            id: "minecraft:stone".to_string(),
+            count: 64,
+        }],
+        nickname: None,
+        extra: Value::from_snbt("{ids:[I;7],tag:{a:1b}}").unwrap(),
+    }
+}
+
+fn get<'a>(map: &'a Compound, key: &str) -> &'a Value {
+    map.get(key).unwrap_or_else(|| panic!("missing {}", key))
+}
+
+#[test]
+fn struct_fields_map_to_tags() {
+    let Value::Compound(map) = nbt::to_value(&player()).unwrap() else {
+        panic!("expected compound");
+    };
+
+    assert_eq!(
+        get(&map, "Pos"),
+        &Value::List(vec![
+            Value::Double(1.5),
+            Value::Double(64.0),
+            Value::Double(-3.0)
+        ])
+    );
+    assert_eq!(get(&map, "UUID"), &Value::IntArray(vec![1, 2, 3, 4]));
+    assert_eq!(get(&map, "seeds"), &Value::LongArray(vec![i64::MAX]));
+    assert_eq!(get(&map, "flags"), &Value::ByteArray(vec![0, 200]));
+    assert_eq!(get(&map, "on_ground"), &Value::Byte(1));
+    assert_eq!(get(&map, "xp"), &Value::Int(-1));
+    assert_eq!(get(&map, "mode"), &Value::String("Creative".to_string()));
+    assert!(!map.contains_key("nickname"));
+    assert_eq!(
+        get(&map, "extra"),
+        &Value::from_snbt("{ids:[I;7],tag:{a:1b}}").unwrap()
+    );
+}
+
+#[test]
+fn struct_round_trips() {
+    let value = nbt::to_value(&player()).unwrap();
+    let decoded: PlayerData = nbt::from_value(value).unwrap();
+
+    assert_eq!(decoded, player());
+}
+
+#[test]
+fn streams_through_writer_and_reader() {
+    let mut bytes = Vec::new();
+    nbt::to_writer(&mut bytes, &player()).unwrap();
+
+    let decoded: PlayerData = nbt::from_reader(&mut Cursor::new(&bytes[..])).unwrap();
+    assert_eq!(decoded, player());
+}
+
+#[test]
+fn wrapper_types_and_enums() {
+    #[derive(Debug, Serialize, Deserialize, PartialEq)]
+    enum Shape {
+        Point,
+        Circle(f32),
+        Rect { w: i32, h: i32 },
+        Line(i16, i16),
+    }
+
+    for shape in [
+        Shape::Point,
+        Shape::Circle(1.5),
+        Shape::Rect { w: 2, h: 3 },
+        Shape::Line(4, 5),
+    ] {
+        let value = nbt::to_value(&shape).unwrap();
+        assert_eq!(nbt::from_value::<Shape>(value).unwrap(), shape);
+    }
+
+    let ints = nbt::to_value(&IntArray(vec![1, 2])).unwrap();
+    assert_eq!(ints, Value::IntArray(vec![1, 2]));
+    assert_eq!(
+        nbt::from_value::<IntArray>(ints).unwrap(),
+        IntArray(vec![1, 2])
+    );
+}
+
+#[test]
+fn rejects_unrepresentable_values() {
+    assert!(matches!(
+        nbt::to_value(&vec![Some(1), None]),
+        Err(NbtError::Message(_))
+    ));
+    assert!(nbt::to_value(&Option::<i32>::None).is_err());
+
+    #[derive(Serialize)]
+    enum Mixed {
+        A(i32),
+        B(String),
+    }
+    assert!(matches!(
+        nbt::to_value(&vec![Mixed::A(1), Mixed::B("b".to_string())]),
+        Ok(Value::List(_))
+    ));
+    assert!(matches!(
+        nbt::to_value(&(1i32, "two")),
+        Err(NbtError::MixedList { .. })
+    ));
+
+    let wrong: Result<Item, _> = nbt::from_value(Value::Int(1));
+    assert!(wrong.is_err());
+}
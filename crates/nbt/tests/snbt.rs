@@ -0,0 +1,128 @@
+use nbt::{NbtError, Value};
+
+fn parse(input: &str) -> Value {
+    Value::from_snbt(input).unwrap()
+}
+
+fn error_column(input: &str) -> usize {
+    match Value::from_snbt(input) {
+        Err(NbtError::Snbt { column, .. }) => column,
+        other => panic!("expected snbt error, got {:?}", other),
+    }
+}
+
+#[test]
+fn parses_typed_numbers() {
+    assert_eq!(parse("1b"), Value::Byte(1));
+    assert_eq!(parse("-2s"), Value::Short(-2));
+    assert_eq!(parse("3"), Value::Int(3));
+    assert_eq!(parse("3L"), Value::Long(3));
+    assert_eq!(parse("1.5f"), Value::Float(1.5));
+    assert_eq!(parse("1.5"), Value::Double(1.5));
+    assert_eq!(parse("2d"), Value::Double(2.0));
+    assert_eq!(parse("1e3f"), Value::Float(1000.0));
+    assert_eq!(parse("true"), Value::Byte(1));
+    assert_eq!(parse("3000000000"), Value::String("3000000000".into()));
+}
+
+#[test]
+fn parses_strings_and_keys() {
+    assert_eq!(parse("hello"), Value::String("hello".into()));
+    assert_eq!(
+        parse(r#""a \"b\" \\ \n""#),
+        Value::String("a \"b\" \\ \n".into())
+    );
+    assert_eq!(
+        parse(r#"'it\'s "ok"'"#),
+        Value::String("it's \"ok\"".into())
+    );
+    assert_eq!(parse(r#""é""#), Value::String("é".into()));
+
+    let Value::Compound(map) = parse(r#"{ plain: 1, "quoted key": 2b, 'single': "x" }"#) else {
+        panic!("expected compound");
+    };
+    assert_eq!(map.get("plain"), Some(&Value::Int(1)));
+    assert_eq!(map.get("quoted key"), Some(&Value::Byte(2)));
+    assert_eq!(map.get("single"), Some(&Value::String("x".into())));
+}
+
+#[test]
+fn parses_lists_and_arrays() {
+    assert_eq!(
+        parse("[1s, 2s, 3s]"),
+        Value::List(vec![Value::Short(1), Value::Short(2), Value::Short(3)])
+    );
+    assert_eq!(parse("[]"), Value::List(vec![]));
+    assert_eq!(
+        parse("[B; 1b, -1b, 255]"),
+        Value::ByteArray(vec![1, 255, 255])
+    );
+    assert_eq!(parse("[I;1,2]"), Value::IntArray(vec![1, 2]));
+    assert_eq!(parse("[L; 1L, -5L]"), Value::LongArray(vec![1, -5]));
+    assert_eq!(parse("[I;]"), Value::IntArray(vec![]));
+}
+
+#[test]
+fn reports_offending_column() {
+    assert_eq!(error_column("{a:1b b:2b}"), 7);
+    assert_eq!(error_column("[1, 2b]"), 5);
+    assert_eq!(error_column("[B; 1b, 300]"), 9);
+    assert_eq!(error_column("\"unterminated"), 14);
+    assert_eq!(error_column("{a:1} extra"), 7);
+    assert_eq!(error_column("2000b"), 1);
+
+    let message = Value::from_snbt("{a:1b b:2b}").unwrap_err().to_string();
+    assert_eq!(message, "Expected ',' or '}' at column 7: {a:1b <--[HERE]");
+}
+
+#[test]
+fn printer_round_trips() {
+    let input = r#"{name:"Steve",Pos:[1.5d,64.0d,-3.25d],Health:20.0f,flags:[B;1b,0b],ids:[I;1,2],
+        seeds:[L;5L],items:[{id:"minecraft:stone",count:1b},{id:"it's \"quoted\"",count:2b}],
+        "odd key":{},empty:[],byte:1b,short:2s,long:3L}"#;
+    let value = parse(input);
+
+    assert_eq!(parse(&value.to_snbt()), value);
+    assert_eq!(parse(&value.to_snbt_pretty()), value);
+}
+
+#[test]
+fn printer_formats() {
+    assert_eq!(parse("[B;1b,2b]").to_snbt(), "[B;1b,2b]");
+    assert_eq!(parse("[B;1b,2b]").to_snbt_pretty(), "[B; 1b, 2b]");
+    assert_eq!(parse("1.0f").to_snbt(), "1.0f");
+    assert_eq!(parse("{\"a b\":[1,2]}").to_snbt(), "{\"a b\":[1,2]}");
+    assert_eq!(
+        parse("{list:[{a:1b}]}").to_snbt_pretty(),
+        "{\n    list: [\n        {\n            a: 1b\n        }\n    ]\n}"
+    );
+}
+
+#[test]
+fn deep_nesting_is_rejected() {
+    let result = Value::from_snbt(&"[".repeat(200_000));
+    assert!(matches!(result, Err(NbtError::DepthLimit(512))));
+
+    let result = Value::from_snbt(&"{a:".repeat(1000));
+    assert!(matches!(result, Err(NbtError::DepthLimit(512))));
+
+    let nested = format!("{}{}", "[".repeat(512), "]".repeat(512));
+    assert!(Value::from_snbt(&nested).is_ok());
+}
+
+#[test]
+fn non_finite_numbers_round_trip() {
+    for value in [
+        Value::Float(f32::INFINITY),
+        Value::Float(f32::NEG_INFINITY),
+        Value::Double(f64::INFINITY),
+        Value::Double(f64::NEG_INFINITY),
+    ] {
+        assert_eq!(parse(&value.to_snbt()), value);
+    }
+
+    assert_eq!(Value::Float(f32::NAN).to_snbt(), "NaNf");
+    assert!(matches!(parse("NaNf"), Value::Float(v) if v.is_nan()));
+    assert!(matches!(parse("NaNd"), Value::Double(v) if v.is_nan()));
+    assert_eq!(parse("Infinity"), Value::String("Infinity".to_string()));
+}
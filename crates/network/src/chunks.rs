@@ -0,0 +1,191 @@
+use std::{io, sync::Arc};
+
+use protocol::{
+    serial::{PacketError, PacketWrite},
+    types::var_int::VarInt,
+};
+use world::{
+    Dimension,
+    loader::LoaderError,
+    ticket::{Ticket, TicketId},
+    update::SectionChanges,
+    view::{PlayerView, ViewUpdate},
+};
+
+use crate::packets::client::play::{
+    BlockEntityDataPacket, BlockUpdatePacket, ChunkBatchFinishedPacket, ChunkBatchStartPacket,
+    ChunkDataAndUpdateLightPacket, SetCenterChunkPacket, SetChunkCacheRadiusPacket,
+    UnloadChunkPacket, UpdateSectionBlocksPacket,
+};
+
+pub type Packets = Vec<Box<dyn PacketWrite>>;
+
+// Streams the chunks of a dimension to one player. A player ticket follows
+// the view so the chunks it shows stay loaded.
+pub struct ChunkStream {
+    dimension: Arc<Dimension>,
+    view: PlayerView,
+    ticket: Option<TicketId>,
+}
+
+impl ChunkStream {
+    pub fn new(dimension: Arc<Dimension>, server_view_distance: u32) -> Self {
+        ChunkStream {
+            dimension,
+            view: PlayerView::new(server_view_distance),
+            ticket: None,
+        }
+    }
+
+    pub fn dimension(&self) -> &Arc<Dimension> {
+        &self.dimension
+    }
+
+    pub fn view(&self) -> &PlayerView {
+        &self.view
+    }
+
+    pub fn move_to(&mut self, chunk_x: i32, chunk_z: i32) -> Result<Packets, PacketError> {
+        let update = self.view.move_to(chunk_x, chunk_z);
+        self.apply(update)
+    }
+
+    pub fn set_client_view_distance(&mut self, distance: u32) -> Result<Packets, PacketError> {
+        let update = self.view.set_client_view_distance(distance);
+        self.apply(update)
+    }
+
+    pub fn set_server_view_distance(&mut self, distance: u32) -> Result<Packets, PacketError> {
+        let update = self.view.set_server_view_distance(distance);
+        self.apply(update)
+    }
+
+    pub fn batch_received(&mut self, chunks_per_tick: f32) {
+        self.view.batch_received(chunks_per_tick);
+    }
+
+    // The packets for this tick's batch, if the client is ready for one.
+    // Chunks are only loaded now, nearest first, and since reading and
+    // generating them blocks that happens off the async runtime.
+    pub async fn next_batch(&mut self) -> Result<Packets, PacketError> {
+        let batch = self.view.next_batch();
+        if batch.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let dimension = self.dimension.clone();
+        let chunks = batch.clone();
+        tokio::task::spawn_blocking(move || {
+            chunks
+                .into_iter()
+                .try_for_each(|(chunk_x, chunk_z)| dimension.load_chunk(chunk_x, chunk_z))
+        })
+        .await
+        .map_err(|error| PacketError::Io(io::Error::other(error)))?
+        .map_err(loader_error)?;
+
+        let mut packets: Packets = Vec::with_capacity(batch.len() + 2);
+        packets.push(Box::new(ChunkBatchStartPacket {}));
+        for &(chunk_x, chunk_z) in &batch {
+            let chunk = self
+                .dimension
+                .chunks
+                .get(&(chunk_x, chunk_z))
+                .ok_or(PacketError::InvalidData)?;
+            packets.push(Box::new(ChunkDataAndUpdateLightPacket::new(&chunk)?));
+        }
+        packets.push(Box::new(ChunkBatchFinishedPacket {
+            batch_size: VarInt(batch.len() as i32),
+        }));
+        Ok(packets)
+    }
+
+    // The changes in chunks the client has. Chunks still waiting to be sent
+    // will carry them already. Block entities follow their blocks.
+    pub fn block_changes(&self, changes: &[SectionChanges]) -> Result<Packets, PacketError> {
+        let mut packets: Packets = Vec::new();
+        for section in changes {
+            if !self.view.is_sent(section.chunk_x, section.chunk_z) {
+                continue;
+            }
+            match section.blocks.as_slice() {
+                [] => {}
+                [(pos, state)] => packets.push(Box::new(BlockUpdatePacket::new(*pos, *state))),
+                _ => packets.push(Box::new(UpdateSectionBlocksPacket::new(section))),
+            }
+            for (pos, entity) in &section.block_entities {
+                if let Some(packet) = BlockEntityDataPacket::new(*pos, entity)? {
+                    packets.push(Box::new(packet));
+                }
+            }
+        }
+        Ok(packets)
+    }
+
+    // Releases the ticket; the client is going away or leaving the dimension.
+    pub fn close(&mut self) {
+        if let Some(ticket) = self.ticket.take() {
+            self.dimension.remove_ticket(ticket);
+        }
+        self.view.reset();
+    }
+
+    fn apply(&mut self, update: ViewUpdate) -> Result<Packets, PacketError> {
+        if update.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.track();
+
+        let mut packets: Packets = Vec::new();
+        if let Some(distance) = update.view_distance {
+            packets.push(Box::new(SetChunkCacheRadiusPacket {
+                view_distance: VarInt(distance as i32),
+            }));
+        }
+        if let Some((chunk_x, chunk_z)) = update.center {
+            packets.push(Box::new(SetCenterChunkPacket {
+                chunk_x: VarInt(chunk_x),
+                chunk_z: VarInt(chunk_z),
+            }));
+        }
+        for (chunk_x, chunk_z) in update.unload {
+            packets.push(Box::new(UnloadChunkPacket { chunk_z, chunk_x }));
+        }
+        Ok(packets)
+    }
+
+    // Keeps the ticket on the view's center and radius. Its chunks are
+    // loaded as their batches go out.
+    fn track(&mut self) {
+        let Some((chunk_x, chunk_z)) = self.view.center() else {
+            return;
+        };
+        let distance = self.view.view_distance();
+        if let Some(id) = self.ticket
+            && self
+                .dimension
+                .ticket(id)
+                .is_some_and(|ticket| ticket.radius == distance)
+        {
+            self.dimension.move_ticket(id, chunk_x, chunk_z);
+            return;
+        }
+
+        let ticket = self
+            .dimension
+            .add_ticket(Ticket::player(chunk_x, chunk_z, distance));
+        if let Some(old) = self.ticket.replace(ticket) {
+            self.dimension.remove_ticket(old);
+        }
+    }
+}
+
+impl Drop for ChunkStream {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+pub(crate) fn loader_error(error: LoaderError) -> PacketError {
+    PacketError::Io(io::Error::other(error))
+}
@@ -2,11 +2,13 @@ use std::sync::Arc;
 
 use events::EventBus;
 use tokio::{net::TcpStream, sync::broadcast};
+use world::{World, update::DimensionTick};
 
 use crate::player_connection::PlayerConnection;
 
 pub mod player_connection;
 
+pub mod chunks;
 pub mod event;
 pub mod packets;
 pub mod states;
@@ -15,7 +17,9 @@ pub async fn handle_connection(
     socket: TcpStream,
     rx: broadcast::Receiver<()>,
     event_bus: Arc<EventBus>,
+    world: Arc<World>,
+    ticks: broadcast::Receiver<Arc<DimensionTick>>,
 ) {
-    let mut connection = PlayerConnection::new(socket, rx, event_bus);
+    let mut connection = PlayerConnection::new(socket, rx, event_bus, world, ticks);
     connection.run().await;
 }
@@ -0,0 +1,35 @@
+use macros::Packet;
+use protocol::{serial::PacketError, types::raw::RawBytes};
+use world::dimension::DimensionTypes;
+
+// Entries without data refer to what the client has in its known packs.
+#[derive(Packet)]
+pub struct RegistryEntry {
+    pub id: String,
+    pub data: Option<RawBytes>,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x07)]
+pub struct RegistryDataPacket {
+    pub registry: String,
+    pub entries: Vec<RegistryEntry>,
+}
+
+impl RegistryDataPacket {
+    pub fn dimension_types(types: &DimensionTypes) -> Result<Self, PacketError> {
+        let entries = types
+            .iter()
+            .map(|(id, kind)| {
+                Ok(RegistryEntry {
+                    id: id.to_string(),
+                    data: Some(RawBytes(kind.encoded()?)),
+                })
+            })
+            .collect::<Result<_, PacketError>>()?;
+        Ok(RegistryDataPacket {
+            registry: "minecraft:dimension_type".to_string(),
+            entries,
+        })
+    }
+}
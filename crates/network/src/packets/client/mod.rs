@@ -1,2 +1,4 @@
+pub mod configuration;
 pub mod login;
+pub mod play;
 pub mod status;
@@ -0,0 +1,314 @@
+use macros::Packet;
+use protocol::{
+    serial::{PacketError, PacketWrite},
+    types::{raw::RawBytes, var_int::VarInt, var_long::VarLong},
+};
+use world::{
+    Dimension,
+    block::BlockState,
+    block_entity::{self, BlockEntity},
+    border::{BorderChange, WorldBorder},
+    chunk::Chunk,
+    dimension::DimensionTypes,
+    pos::BlockPos,
+    time::WorldTime,
+    update::SectionChanges,
+    weather::WeatherChange,
+};
+
+#[derive(Packet)]
+#[packet(id = 0x2C)]
+pub struct ChunkDataAndUpdateLightPacket {
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub data: RawBytes,
+}
+
+impl ChunkDataAndUpdateLightPacket {
+    pub fn new(chunk: &Chunk) -> Result<Self, PacketError> {
+        Ok(ChunkDataAndUpdateLightPacket {
+            chunk_x: chunk.x,
+            chunk_z: chunk.z,
+            data: RawBytes(chunk.encoded()?),
+        })
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x0C)]
+pub struct ChunkBatchStartPacket {}
+
+#[derive(Packet)]
+#[packet(id = 0x0B)]
+pub struct ChunkBatchFinishedPacket {
+    pub batch_size: VarInt,
+}
+
+// The position goes out as one long, z in the high half.
+#[derive(Packet)]
+#[packet(id = 0x25)]
+pub struct UnloadChunkPacket {
+    pub chunk_z: i32,
+    pub chunk_x: i32,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x5C)]
+pub struct SetCenterChunkPacket {
+    pub chunk_x: VarInt,
+    pub chunk_z: VarInt,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x5D)]
+pub struct SetChunkCacheRadiusPacket {
+    pub view_distance: VarInt,
+}
+
+// Tells the client its block changes up to the sequence were handled, so it
+// takes the server's blocks over its own predictions.
+#[derive(Packet)]
+#[packet(id = 0x04)]
+pub struct AcknowledgeBlockChangePacket {
+    pub sequence: VarInt,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x08)]
+pub struct BlockUpdatePacket {
+    pub position: i64,
+    pub block: VarInt,
+}
+
+impl BlockUpdatePacket {
+    pub fn new(pos: BlockPos, state: BlockState) -> Self {
+        BlockUpdatePacket {
+            position: pack_position(pos),
+            block: VarInt(state.id() as i32),
+        }
+    }
+}
+
+// The data is network NBT with the block entity's fields.
+#[derive(Packet)]
+#[packet(id = 0x06)]
+pub struct BlockEntityDataPacket {
+    pub position: i64,
+    pub kind: VarInt,
+    pub data: RawBytes,
+}
+
+impl BlockEntityDataPacket {
+    // Nothing for block entities clients don't know.
+    pub fn new(pos: BlockPos, entity: &BlockEntity) -> Result<Option<Self>, PacketError> {
+        let Some(kind) = block_entity::network_id(&entity.id) else {
+            return Ok(None);
+        };
+        Ok(Some(BlockEntityDataPacket {
+            position: pack_position(pos),
+            kind: VarInt(kind),
+            data: RawBytes(entity.encoded()?),
+        }))
+    }
+}
+
+// Several changes within one section. Each entry packs the state id above
+// the position within the section.
+#[derive(Packet)]
+#[packet(id = 0x52)]
+pub struct UpdateSectionBlocksPacket {
+    pub section: i64,
+    pub blocks: Vec<VarLong>,
+}
+
+impl UpdateSectionBlocksPacket {
+    pub fn new(changes: &SectionChanges) -> Self {
+        UpdateSectionBlocksPacket {
+            section: ((changes.chunk_x as i64 & 0x3FFFFF) << 42)
+                | ((changes.chunk_z as i64 & 0x3FFFFF) << 20)
+                | (changes.section_y as i64 & 0xFFFFF),
+            blocks: changes
+                .blocks
+                .iter()
+                .map(|(pos, state)| {
+                    let local = ((pos.x & 15) << 8) | ((pos.z & 15) << 4) | (pos.y & 15);
+                    VarLong(((state.id() as i64) << 12) | local as i64)
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Packet)]
+pub struct DeathLocation {
+    pub dimension: String,
+    pub position: i64,
+}
+
+// Sent on death and to move the player to another dimension. The client
+// drops the chunks it has and waits for the new dimension's.
+#[derive(Packet)]
+#[packet(id = 0x50)]
+pub struct RespawnPacket {
+    pub dimension_type: VarInt,
+    pub dimension_name: String,
+    pub hashed_seed: i64,
+    pub game_mode: u8,
+    pub previous_game_mode: i8,
+    pub is_debug: bool,
+    pub is_flat: bool,
+    pub death_location: Option<DeathLocation>,
+    pub portal_cooldown: VarInt,
+    pub sea_level: VarInt,
+    pub data_kept: u8,
+}
+
+impl RespawnPacket {
+    // Keeps attributes and metadata, as vanilla does when changing
+    // dimension. Nothing if the dimension's type isn't in the registry sent
+    // to clients.
+    pub fn new(types: &DimensionTypes, dimension: &Dimension) -> Option<Self> {
+        Some(RespawnPacket {
+            dimension_type: VarInt(types.network_id(&dimension.type_id)?),
+            dimension_name: dimension.id.to_string(),
+            hashed_seed: 0,
+            game_mode: 0,
+            previous_game_mode: -1,
+            is_debug: false,
+            is_flat: false,
+            death_location: None,
+            portal_cooldown: VarInt(0),
+            sea_level: VarInt(63),
+            data_kept: 0x03,
+        })
+    }
+}
+
+// Everything about the border, sent when a player enters the dimension.
+// The speed is how many milliseconds the move to the new size takes.
+#[derive(Packet)]
+#[packet(id = 0x2A)]
+pub struct InitializeWorldBorderPacket {
+    pub x: f64,
+    pub z: f64,
+    pub old_size: f64,
+    pub new_size: f64,
+    pub speed: VarLong,
+    pub portal_teleport_boundary: VarInt,
+    pub warning_blocks: VarInt,
+    pub warning_time: VarInt,
+}
+
+impl InitializeWorldBorderPacket {
+    pub fn new(border: &WorldBorder) -> Self {
+        InitializeWorldBorderPacket {
+            x: border.center_x,
+            z: border.center_z,
+            old_size: border.size(),
+            new_size: border.target_size(),
+            speed: VarLong(border.remaining_millis()),
+            portal_teleport_boundary: VarInt(border.portal_teleport_boundary),
+            warning_blocks: VarInt(border.warning_blocks),
+            warning_time: VarInt(border.warning_time),
+        }
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x56)]
+pub struct SetBorderCenterPacket {
+    pub x: f64,
+    pub z: f64,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x57)]
+pub struct SetBorderLerpSizePacket {
+    pub old_size: f64,
+    pub new_size: f64,
+    pub speed: VarLong,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x58)]
+pub struct SetBorderSizePacket {
+    pub size: f64,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x59)]
+pub struct SetBorderWarningDelayPacket {
+    pub warning_time: VarInt,
+}
+
+#[derive(Packet)]
+#[packet(id = 0x5A)]
+pub struct SetBorderWarningDistancePacket {
+    pub warning_blocks: VarInt,
+}
+
+pub fn border_change_packet(change: &BorderChange) -> Box<dyn PacketWrite> {
+    match *change {
+        BorderChange::Center { x, z } => Box::new(SetBorderCenterPacket { x, z }),
+        BorderChange::Size(size) => Box::new(SetBorderSizePacket { size }),
+        BorderChange::Lerp { from, to, millis } => Box::new(SetBorderLerpSizePacket {
+            old_size: from,
+            new_size: to,
+            speed: VarLong(millis),
+        }),
+        BorderChange::WarningTime(seconds) => Box::new(SetBorderWarningDelayPacket {
+            warning_time: VarInt(seconds),
+        }),
+        BorderChange::WarningBlocks(blocks) => Box::new(SetBorderWarningDistancePacket {
+            warning_blocks: VarInt(blocks),
+        }),
+    }
+}
+
+// The time of day keeps increasing on the client unless told otherwise.
+#[derive(Packet)]
+#[packet(id = 0x6F)]
+pub struct UpdateTimePacket {
+    pub world_age: i64,
+    pub time_of_day: i64,
+    pub time_of_day_increasing: bool,
+}
+
+impl UpdateTimePacket {
+    pub fn new(time: &WorldTime) -> Self {
+        UpdateTimePacket {
+            world_age: time.age,
+            time_of_day: time.time_of_day,
+            time_of_day_increasing: !time.frozen,
+        }
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x26)]
+pub struct GameEventPacket {
+    pub event: u8,
+    pub value: f32,
+}
+
+impl GameEventPacket {
+    pub const BEGIN_RAINING: u8 = 1;
+    pub const END_RAINING: u8 = 2;
+    pub const RAIN_LEVEL_CHANGE: u8 = 7;
+    pub const THUNDER_LEVEL_CHANGE: u8 = 8;
+
+    pub fn weather(change: &WeatherChange) -> Self {
+        let (event, value) = match *change {
+            WeatherChange::StartRaining => (Self::BEGIN_RAINING, 0.0),
+            WeatherChange::StopRaining => (Self::END_RAINING, 0.0),
+            WeatherChange::RainLevel(level) => (Self::RAIN_LEVEL_CHANGE, level),
+            WeatherChange::ThunderLevel(level) => (Self::THUNDER_LEVEL_CHANGE, level),
+        };
+        GameEventPacket { event, value }
+    }
+}
+
+// x in the top 26 bits, then z, then y in the low 12.
+fn pack_position(pos: BlockPos) -> i64 {
+    ((pos.x as i64 & 0x3FFFFFF) << 38) | ((pos.z as i64 & 0x3FFFFFF) << 12) | (pos.y as i64 & 0xFFF)
+}
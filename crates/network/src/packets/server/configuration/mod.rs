@@ -19,8 +19,9 @@ pub struct ClientInformationPacket {
 
 #[async_trait]
 impl PacketHandler for ClientInformationPacket {
-    async fn handle(&mut self, _ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
         warn!("{}", &self.locale);
-        Ok(())
+        ctx.set_view_distance(self.view_distance.max(2) as u32)
+            .await
     }
 }
@@ -12,6 +12,6 @@ pub struct LoginAcknowledgedPacket {}
 impl PacketHandler for LoginAcknowledgedPacket {
     async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
         ctx.set_state(protocol::ConnectionState::Configuration);
-        Ok(())
+        ctx.send_registries().await
     }
 }
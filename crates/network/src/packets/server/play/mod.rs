@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use macros::Packet;
+use protocol::{serial::PacketError, types::var_int::VarInt};
+use world::pos::{BlockPos, Direction};
+
+use crate::{packets::PacketHandler, player_connection::PlayerConnection};
+
+#[derive(Packet)]
+#[packet(id = 0x0A)]
+pub struct ChunkBatchReceivedPacket {
+    pub chunks_per_tick: f32,
+}
+
+#[async_trait]
+impl PacketHandler for ChunkBatchReceivedPacket {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+        ctx.chunk_batch_received(self.chunks_per_tick);
+        Ok(())
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x1D)]
+pub struct SetPlayerPositionPacket {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub flags: u8,
+}
+
+#[async_trait]
+impl PacketHandler for SetPlayerPositionPacket {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+        ctx.move_player(self.x, self.y, self.z).await
+    }
+}
+
+#[derive(Packet)]
+#[packet(id = 0x1E)]
+pub struct SetPlayerPositionAndRotationPacket {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub flags: u8,
+}
+
+#[async_trait]
+impl PacketHandler for SetPlayerPositionAndRotationPacket {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+        ctx.move_player(self.x, self.y, self.z).await
+    }
+}
+
+// Only digging is handled; the status is 0 when it starts, 1 when it is
+// cancelled and 2 when the block breaks.
+#[derive(Packet)]
+#[packet(id = 0x28)]
+pub struct PlayerActionPacket {
+    pub status: VarInt,
+    pub position: i64,
+    pub face: i8,
+    pub sequence: VarInt,
+}
+
+#[async_trait]
+impl PacketHandler for PlayerActionPacket {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+        match self.status.0 {
+            0 | 1 => ctx.acknowledge_block_change(self.sequence.0).await,
+            2 => {
+                let pos = unpack_position(self.position);
+                ctx.break_block(pos, self.sequence.0).await
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// The position is the block clicked, the face the side of it clicked on.
+#[derive(Packet)]
+#[packet(id = 0x3F)]
+pub struct UseItemOnPacket {
+    pub hand: VarInt,
+    pub position: i64,
+    pub face: VarInt,
+    pub cursor_x: f32,
+    pub cursor_y: f32,
+    pub cursor_z: f32,
+    pub inside_block: bool,
+    pub world_border_hit: bool,
+    pub sequence: VarInt,
+}
+
+#[async_trait]
+impl PacketHandler for UseItemOnPacket {
+    async fn handle(&mut self, ctx: &mut PlayerConnection) -> Result<(), PacketError> {
+        let face = usize::try_from(self.face.0)
+            .ok()
+            .and_then(|face| Direction::ALL.get(face))
+            .ok_or(PacketError::InvalidData)?;
+        let pos = unpack_position(self.position).offset(*face);
+        ctx.place_block(pos, self.sequence.0).await
+    }
+}
+
+fn unpack_position(packed: i64) -> BlockPos {
+    BlockPos {
+        x: (packed >> 38) as i32,
+        y: (packed << 52 >> 52) as i32,
+        z: (packed << 26 >> 38) as i32,
+    }
+}
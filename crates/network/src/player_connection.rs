@@ -2,7 +2,7 @@ use std::{io::Cursor, sync::Arc};
 
 use bytes::{Buf, BytesMut};
 use events::EventBus;
-use log::error;
+use log::{error, warn};
 use protocol::{
     ConnectionState,
     serial::{PacketError, PacketRead, PacketWrite},
@@ -14,10 +14,31 @@ use tokio::{
     net::TcpStream,
     sync::broadcast,
 };
+use world::{
+    World,
+    block::BlockState,
+    border::BorderChange,
+    dimension::DimensionTypes,
+    pos::BlockPos,
+    time::WorldTime,
+    update::{BlockFlags, DimensionTick, SectionChanges},
+    weather::WeatherChange,
+};
 
-use crate::states::{
-    PacketDispatcher, configuration::ConfigurationPacketHandler,
-    handshaking::HandshakePacketHandler, login::LoginPacketHandler, status::StatusPacketHandler,
+use crate::{
+    chunks::{ChunkStream, loader_error},
+    packets::client::{
+        configuration::RegistryDataPacket,
+        play::{
+            AcknowledgeBlockChangePacket, BlockUpdatePacket, GameEventPacket,
+            InitializeWorldBorderPacket, RespawnPacket, UpdateTimePacket, border_change_packet,
+        },
+    },
+    states::{
+        PacketDispatcher, configuration::ConfigurationPacketHandler,
+        handshaking::HandshakePacketHandler, login::LoginPacketHandler, play::PlayPacketHandler,
+        status::StatusPacketHandler,
+    },
 };
 
 const MAX_PACKET_SIZE: usize = 2 * 1024 * 1024;
@@ -31,8 +52,14 @@ pub struct PlayerConnection {
     shutdown_tx: broadcast::Receiver<()>,
     protocol: i32,
     event_bus: Arc<EventBus>,
+    world: Arc<World>,
+    ticks: broadcast::Receiver<Arc<DimensionTick>>,
+    dimension_types: DimensionTypes,
     username: Option<String>,
     uuid: Option<uuid::Uuid>,
+    view_distance: Option<u32>,
+    chunks: Option<ChunkStream>,
+    position: Option<(f64, f64, f64)>,
 }
 
 impl PlayerConnection {
@@ -40,6 +67,8 @@ impl PlayerConnection {
         socket: TcpStream,
         shutdown_tx: broadcast::Receiver<()>,
         event_bus: Arc<EventBus>,
+        world: Arc<World>,
+        ticks: broadcast::Receiver<Arc<DimensionTick>>,
     ) -> Self {
         PlayerConnection {
             socket,
@@ -50,8 +79,14 @@ impl PlayerConnection {
             shutdown_tx,
             protocol: 0,
             event_bus,
+            dimension_types: world.types(),
+            world,
+            ticks,
             username: None,
             uuid: None,
+            view_distance: None,
+            chunks: None,
+            position: None,
         }
     }
 
@@ -62,6 +97,20 @@ impl PlayerConnection {
                         // encerrar conexão
                         break;
                     }
+                    tick = self.ticks.recv() => {
+                        match tick {
+                            Ok(tick) => {
+                                if let Err(e) = self.send_tick(&tick).await {
+                                    error!("Error sending tick: {}", e);
+                                    return;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                                warn!("connection fell {} ticks behind", missed);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => break,
+                        }
+                    }
                     read = self.socket.read_buf(&mut self.buffer) => {
                         match read {
                             Err(e) => {
@@ -134,7 +183,8 @@ impl PlayerConnection {
                 handler.dispatch_packet(self).await?;
             }
             ConnectionState::Play => {
-                return Err(PacketError::NotImplemented("play".to_string()));
+                let mut handler = PlayPacketHandler::from_id(packet_id, &mut cursor)?;
+                handler.dispatch_packet(self).await?;
             }
         }
 
@@ -162,6 +212,24 @@ impl PlayerConnection {
     pub fn event_bus(&self) -> &EventBus {
         &self.event_bus
     }
+
+    pub fn world(&self) -> &Arc<World> {
+        &self.world
+    }
+
+    // The dimension_type registry this client was sent during configuration,
+    // which the Login and Respawn packets refer to.
+    pub fn dimension_types(&self) -> &DimensionTypes {
+        &self.dimension_types
+    }
+
+    // Sends the world's registries as they are now. Types registered later
+    // stay unknown to this client.
+    pub async fn send_registries(&mut self) -> Result<(), PacketError> {
+        self.dimension_types = self.world.types();
+        let packet = RegistryDataPacket::dimension_types(&self.dimension_types)?;
+        self.send_packet(&packet).await
+    }
 }
 
 impl PlayerConnection {
@@ -183,6 +251,234 @@ impl PlayerConnection {
 }
 
 impl PlayerConnection {
+    // The view distance the client asked for in Client Information.
+    pub fn view_distance(&self) -> Option<u32> {
+        self.view_distance
+    }
+
+    pub async fn set_view_distance(&mut self, distance: u32) -> Result<(), PacketError> {
+        self.view_distance = Some(distance);
+        let packets = match &mut self.chunks {
+            Some(chunks) => chunks.set_client_view_distance(distance)?,
+            None => return Ok(()),
+        };
+        self.send_packets(&packets).await
+    }
+
+    pub fn chunk_stream(&self) -> Option<&ChunkStream> {
+        self.chunks.as_ref()
+    }
+
+    // Starts streaming the dimension around the given chunk, replacing the
+    // stream of the previous dimension. The dimension's world border, time
+    // and weather come first.
+    pub async fn start_chunk_stream(
+        &mut self,
+        mut chunks: ChunkStream,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<(), PacketError> {
+        let dimension = chunks.dimension();
+        let mut packets: Vec<Box<dyn PacketWrite>> = vec![
+            Box::new(InitializeWorldBorderPacket::new(&dimension.border())),
+            Box::new(UpdateTimePacket::new(&dimension.time())),
+        ];
+        for change in dimension.weather().client_state() {
+            packets.push(Box::new(GameEventPacket::weather(&change)));
+        }
+        if let Some(distance) = self.view_distance {
+            packets.extend(chunks.set_client_view_distance(distance)?);
+        }
+        packets.extend(chunks.move_to(chunk_x, chunk_z)?);
+        self.chunks = Some(chunks);
+        self.send_packets(&packets).await
+    }
+
+    pub fn stop_chunk_stream(&mut self) {
+        self.chunks = None;
+        self.position = None;
+    }
+
+    // Moves the player to the stream's dimension. Respawn goes first so the
+    // client drops the old dimension's chunks before the new ones arrive.
+    pub async fn change_dimension(
+        &mut self,
+        chunks: ChunkStream,
+        chunk_x: i32,
+        chunk_z: i32,
+    ) -> Result<(), PacketError> {
+        let respawn = RespawnPacket::new(&self.dimension_types, chunks.dimension())
+            .ok_or(PacketError::InvalidData)?;
+        self.stop_chunk_stream();
+        self.send_packet(&respawn).await?;
+        self.start_chunk_stream(chunks, chunk_x, chunk_z).await
+    }
+
+    // Call with the player's chunk whenever they move.
+    pub async fn move_chunk_view(&mut self, chunk_x: i32, chunk_z: i32) -> Result<(), PacketError> {
+        let packets = match &mut self.chunks {
+            Some(chunks) => chunks.move_to(chunk_x, chunk_z)?,
+            None => return Ok(()),
+        };
+        self.send_packets(&packets).await
+    }
+
+    // Everything the server's tick changed in the streamed dimension, then
+    // the next batch of chunks. Ticks of other dimensions are ignored.
+    pub async fn send_tick(&mut self, tick: &DimensionTick) -> Result<(), PacketError> {
+        match &self.chunks {
+            Some(chunks) if chunks.dimension().id == tick.dimension => {}
+            _ => return Ok(()),
+        }
+        self.send_block_changes(&tick.blocks).await?;
+        self.send_border_changes(&tick.border).await?;
+        if let Some(time) = &tick.time {
+            self.send_time(time).await?;
+        }
+        self.send_weather_changes(&tick.weather).await?;
+        self.send_chunk_batch().await
+    }
+
+    // Call once per tick to send the next batch of chunks.
+    pub async fn send_chunk_batch(&mut self) -> Result<(), PacketError> {
+        let packets = match &mut self.chunks {
+            Some(chunks) => chunks.next_batch().await?,
+            None => return Ok(()),
+        };
+        self.send_packets(&packets).await
+    }
+
+    // Call once per tick with the dimension's `take_block_changes`.
+    pub async fn send_block_changes(
+        &mut self,
+        changes: &[SectionChanges],
+    ) -> Result<(), PacketError> {
+        let packets = match &self.chunks {
+            Some(chunks) => chunks.block_changes(changes)?,
+            None => return Ok(()),
+        };
+        self.send_packets(&packets).await
+    }
+
+    // Call once per tick with the dimension's `take_border_changes`.
+    pub async fn send_border_changes(
+        &mut self,
+        changes: &[BorderChange],
+    ) -> Result<(), PacketError> {
+        if self.chunks.is_none() {
+            return Ok(());
+        }
+        let packets = changes.iter().map(border_change_packet).collect::<Vec<_>>();
+        self.send_packets(&packets).await
+    }
+
+    // Call with the dimension's `take_time_update` when it has one.
+    pub async fn send_time(&mut self, time: &WorldTime) -> Result<(), PacketError> {
+        if self.chunks.is_none() {
+            return Ok(());
+        }
+        self.send_packet(&UpdateTimePacket::new(time)).await
+    }
+
+    // Call once per tick with the dimension's `take_weather_changes`.
+    pub async fn send_weather_changes(
+        &mut self,
+        changes: &[WeatherChange],
+    ) -> Result<(), PacketError> {
+        if self.chunks.is_none() {
+            return Ok(());
+        }
+        for change in changes {
+            self.send_packet(&GameEventPacket::weather(change)).await?;
+        }
+        Ok(())
+    }
+
+    // Where the player is in the streamed dimension, as last moved.
+    pub fn position(&self) -> Option<(f64, f64, f64)> {
+        self.position
+    }
+
+    // A move the client reports, kept inside the world border unless the
+    // player was already outside it. The chunk view follows.
+    pub async fn move_player(&mut self, x: f64, y: f64, z: f64) -> Result<(), PacketError> {
+        let Some(chunks) = &self.chunks else {
+            return Ok(());
+        };
+        let (x, z) = match self.position {
+            Some((from_x, _, from_z)) => chunks
+                .dimension()
+                .border()
+                .clamp_move((from_x, from_z), (x, z)),
+            None => (x, z),
+        };
+        self.position = Some((x, y, z));
+        let chunk = BlockPos {
+            x: x.floor() as i32,
+            y: y.floor() as i32,
+            z: z.floor() as i32,
+        }
+        .chunk();
+        self.move_chunk_view(chunk.0, chunk.1).await
+    }
+
+    // Breaks the block unless it is outside the border or the dimension's
+    // height, in which case the client is sent the block back.
+    pub async fn break_block(&mut self, pos: BlockPos, sequence: i32) -> Result<(), PacketError> {
+        let Some(chunks) = &self.chunks else {
+            return Ok(());
+        };
+        let dimension = chunks.dimension().clone();
+        if dimension.can_build(pos) {
+            dimension
+                .set_block(pos, BlockState::AIR, BlockFlags::DEFAULT)
+                .map_err(loader_error)?;
+        } else {
+            self.send_packet(&BlockUpdatePacket::new(pos, dimension.get_block(pos)))
+                .await?;
+        }
+        self.acknowledge_block_change(sequence).await
+    }
+
+    // Placing needs the held item, which isn't tracked yet, so this only
+    // takes back placements outside the border or the dimension's height.
+    pub async fn place_block(&mut self, pos: BlockPos, sequence: i32) -> Result<(), PacketError> {
+        let Some(chunks) = &self.chunks else {
+            return Ok(());
+        };
+        let dimension = chunks.dimension().clone();
+        if !dimension.can_build(pos) {
+            self.send_packet(&BlockUpdatePacket::new(pos, dimension.get_block(pos)))
+                .await?;
+        }
+        self.acknowledge_block_change(sequence).await
+    }
+
+    pub async fn acknowledge_block_change(&mut self, sequence: i32) -> Result<(), PacketError> {
+        self.send_packet(&AcknowledgeBlockChangePacket {
+            sequence: VarInt(sequence),
+        })
+        .await
+    }
+
+    pub fn chunk_batch_received(&mut self, chunks_per_tick: f32) {
+        if let Some(chunks) = &mut self.chunks {
+            chunks.batch_received(chunks_per_tick);
+        }
+    }
+}
+
+impl PlayerConnection {
+    pub async fn send_packets(
+        &mut self,
+        packets: &[Box<dyn protocol::serial::PacketWrite>],
+    ) -> Result<(), PacketError> {
+        for packet in packets {
+            self.send_packet(packet.as_ref()).await?;
+        }
+        Ok(())
+    }
+
     pub async fn send_packet(
         &mut self,
         packet: &dyn protocol::serial::PacketWrite,
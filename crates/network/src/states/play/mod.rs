@@ -0,0 +1,98 @@
+use std::io::Cursor;
+
+use async_trait::async_trait;
+use protocol::serial::{PacketError, PacketRead};
+
+use crate::{
+    PlayerConnection,
+    packets::{
+        PacketHandler,
+        server::{
+            configuration::ClientInformationPacket,
+            play::{
+                ChunkBatchReceivedPacket, PlayerActionPacket, SetPlayerPositionAndRotationPacket,
+                SetPlayerPositionPacket, UseItemOnPacket,
+            },
+        },
+    },
+    states::PacketDispatcher,
+};
+
+// Client Information is the same packet as in configuration, sent again when
+// the player changes their settings.
+pub enum PlayPacketHandler {
+    ChunkBatchReceived(ChunkBatchReceivedPacket),
+    ClientInformation(ClientInformationPacket),
+    SetPlayerPosition(SetPlayerPositionPacket),
+    SetPlayerPositionAndRotation(SetPlayerPositionAndRotationPacket),
+    PlayerAction(PlayerActionPacket),
+    UseItemOn(UseItemOnPacket),
+}
+
+impl PlayPacketHandler {
+    pub fn from_id(id: i32, data: &mut Cursor<&[u8]>) -> Result<Self, PacketError> {
+        match id {
+            0x0A => {
+                let packet = ChunkBatchReceivedPacket::read(data)?;
+
+                Ok(PlayPacketHandler::ChunkBatchReceived(packet))
+            }
+            0x0D => {
+                let packet = ClientInformationPacket::read(data)?;
+
+                Ok(PlayPacketHandler::ClientInformation(packet))
+            }
+            0x1D => {
+                let packet = SetPlayerPositionPacket::read(data)?;
+
+                Ok(PlayPacketHandler::SetPlayerPosition(packet))
+            }
+            0x1E => {
+                let packet = SetPlayerPositionAndRotationPacket::read(data)?;
+
+                Ok(PlayPacketHandler::SetPlayerPositionAndRotation(packet))
+            }
+            0x28 => {
+                let packet = PlayerActionPacket::read(data)?;
+
+                Ok(PlayPacketHandler::PlayerAction(packet))
+            }
+            0x3F => {
+                let packet = UseItemOnPacket::read(data)?;
+
+                Ok(PlayPacketHandler::UseItemOn(packet))
+            }
+            _ => Err(PacketError::UnknownPacket),
+        }
+    }
+}
+
+#[async_trait]
+impl PacketDispatcher for PlayPacketHandler {
+    async fn dispatch_packet(
+        &mut self,
+        player_connection: &mut PlayerConnection,
+    ) -> Result<(), PacketError> {
+        match self {
+            PlayPacketHandler::ChunkBatchReceived(packet) => {
+                packet.handle(player_connection).await?;
+            }
+            PlayPacketHandler::ClientInformation(packet) => {
+                packet.handle(player_connection).await?;
+            }
+            PlayPacketHandler::SetPlayerPosition(packet) => {
+                packet.handle(player_connection).await?;
+            }
+            PlayPacketHandler::SetPlayerPositionAndRotation(packet) => {
+                packet.handle(player_connection).await?;
+            }
+            PlayPacketHandler::PlayerAction(packet) => {
+                packet.handle(player_connection).await?;
+            }
+            PlayPacketHandler::UseItemOn(packet) => {
+                packet.handle(player_connection).await?;
+            }
+        }
+        Ok(())
+    }
+}
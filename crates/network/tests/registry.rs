@@ -0,0 +1,45 @@
+use network::packets::client::{configuration::RegistryDataPacket, play::RespawnPacket};
+use world::{
+    Dimension, DimensionId, World, dimension::DimensionType, generator::flat::FlatGenerator,
+};
+
+fn id(input: &str) -> DimensionId {
+    input.parse().unwrap()
+}
+
+#[test]
+fn respawn_into_a_registered_type() {
+    let world = World::new();
+    let arena = DimensionType {
+        fixed_time: Some(18000),
+        ..DimensionType::overworld()
+    };
+    let lobby = Dimension::new(id("myteam:lobby"), Box::new(FlatGenerator::new()))
+        .with_type(id("myteam:arena"), arena);
+    let lobby = world.add_dimension(lobby).unwrap();
+
+    // What a client joining now is sent, and so what it can respawn into.
+    let types = world.types();
+    let registry = RegistryDataPacket::dimension_types(&types).unwrap();
+    let position = registry
+        .entries
+        .iter()
+        .position(|entry| entry.id == "myteam:arena")
+        .unwrap();
+
+    let respawn = RespawnPacket::new(&types, &lobby).unwrap();
+    assert_eq!(respawn.dimension_type.0, position as i32);
+    assert_eq!(respawn.dimension_name, "myteam:lobby");
+}
+
+#[test]
+fn no_respawn_into_a_type_sent_too_late() {
+    let world = World::new();
+    let types = world.types();
+    let lobby = Dimension::new(id("myteam:lobby"), Box::new(FlatGenerator::new()))
+        .with_type(id("myteam:arena"), DimensionType::overworld());
+    let lobby = world.add_dimension(lobby).unwrap();
+
+    assert!(RespawnPacket::new(&types, &lobby).is_none());
+    assert!(RespawnPacket::new(&world.types(), &lobby).is_some());
+}
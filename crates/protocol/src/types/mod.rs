@@ -1,4 +1,6 @@
 pub mod property;
+pub mod raw;
 pub mod types;
 pub mod var_int;
+pub mod var_long;
 pub mod vec;
@@ -0,0 +1,21 @@
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::serial::{PacketError, PacketRead, PacketWrite};
+
+// Pre-encoded bytes written as-is with no length prefix. Reading takes the
+// rest of the packet, so it can only be the last field.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RawBytes(pub Bytes);
+
+impl PacketWrite for RawBytes {
+    fn write(&self, buffer: &mut BytesMut) -> Result<(), PacketError> {
+        buffer.put_slice(&self.0);
+        Ok(())
+    }
+}
+
+impl PacketRead for RawBytes {
+    fn read<Buffer: bytes::Buf>(buffer: &mut Buffer) -> Result<Self, PacketError> {
+        Ok(RawBytes(buffer.copy_to_bytes(buffer.remaining())))
+    }
+}
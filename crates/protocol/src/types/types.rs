@@ -37,6 +37,8 @@ impl_primitive!(i8, 1, get_i8, put_i8);
 impl_primitive!(i16, 2, get_i16, put_i16);
 impl_primitive!(i32, 4, get_i32, put_i32);
 impl_primitive!(i64, 8, get_i64, put_i64);
+impl_primitive!(f32, 4, get_f32, put_f32);
+impl_primitive!(f64, 8, get_f64, put_f64);
 
 impl PacketRead for String {
     fn read<Buffer: bytes::Buf>(buffer: &mut Buffer) -> Result<Self, crate::serial::PacketError> {
@@ -0,0 +1,54 @@
+use bytes::{BufMut, BytesMut};
+use serde::{Deserialize, Serialize};
+
+use crate::serial::{PacketError, PacketRead, PacketWrite};
+
+pub type VarLongType = i64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct VarLong(pub VarLongType);
+
+impl PacketWrite for VarLong {
+    fn write(&self, buffer: &mut BytesMut) -> Result<(), crate::serial::PacketError> {
+        let mut x = self.0 as u64;
+
+        loop {
+            let mut temp = (x & 0x7F) as u8;
+            x >>= 7;
+            if x != 0 {
+                temp |= 0x80;
+            }
+            buffer.put_u8(temp);
+            if x == 0 {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl PacketRead for VarLong {
+    fn read<Buffer: bytes::Buf>(buffer: &mut Buffer) -> Result<Self, crate::serial::PacketError> {
+        let mut num = 0;
+        let mut shift = 0;
+
+        loop {
+            if !buffer.has_remaining() {
+                return Err(crate::serial::PacketError::Incomplete);
+            }
+            let byte = buffer.get_u8();
+            num |= ((byte & 0x7F) as i64) << shift;
+            if (byte & 0x80) == 0 {
+                return Ok(VarLong(num));
+            }
+            shift += 7;
+            if shift >= 64 {
+                return Err(PacketError::Io(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "VarLong too big",
+                )));
+            }
+        }
+    }
+}
@@ -1,12 +1,20 @@
+use std::{sync::Arc, time::Duration};
+
 pub use async_trait::async_trait;
 use log::{error, info, warn};
 
 use network::handle_connection;
+use world::{
+    Dimension, DimensionId, World, command,
+    generator::flat::{FlatGenerator, FlatSettings},
+    update::DimensionTick,
+};
 
 use tokio::{
     io::{AsyncBufReadExt, BufReader},
     net::TcpListener,
     sync::broadcast,
+    time::{MissedTickBehavior, interval},
 };
 
 use crate::{ServerConfig, ServerContext};
@@ -25,6 +33,19 @@ pub async fn bootstrap<Server: IridiumServer + Send + Sync + 'static>(mut server
         warn!("could not load server.yml: {}", e);
     }
 
+    match ctx.config.dimension_types() {
+        Ok(types) => match World::new().with_types(types) {
+            Ok(world) => ctx.world = Arc::new(world),
+            Err(e) => warn!("invalid dimension_types in server.yml: {}", e),
+        },
+        Err(e) => warn!("invalid dimension_types in server.yml: {}", e),
+    }
+
+    if let Err(e) = add_overworld(&ctx.config, &ctx.world) {
+        error!("invalid world.flat in server.yml: {}", e);
+        return;
+    }
+
     let (add, port) = assert_config(&ctx.config).unwrap_or_else(|| {
         return ("0.0.0.0", 25565);
     });
@@ -43,6 +64,9 @@ pub async fn bootstrap<Server: IridiumServer + Send + Sync + 'static>(mut server
     };
 
     let (shutdown_tex, _) = broadcast::channel::<()>(16);
+    let (tick_tx, _) = broadcast::channel::<Arc<DimensionTick>>(TICK_BACKLOG);
+    let mut ticks = interval(Duration::from_millis(50));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
     log::info!("╭──────────────────────────────────────────╮");
     log::info!("│ 💎 Iridium Server                        │");
@@ -64,13 +88,16 @@ pub async fn bootstrap<Server: IridiumServer + Send + Sync + 'static>(mut server
                     Ok((socket, _address)) => {
                         let rx = shutdown_tex.subscribe();
                         let event_bus = ctx.event_bus.clone();
+                        let world = ctx.world.clone();
+                        let tick_rx = tick_tx.subscribe();
                         tokio::spawn(async move {
-                            handle_connection(socket, rx, event_bus).await;
+                            handle_connection(socket, rx, event_bus, world, tick_rx).await;
                         });
                     },
                     Err(error) => error!("failed to accept connection: {}", error),
                 }
             },
+            _ = ticks.tick() => tick_world(&ctx.world, &tick_tx),
             line = reader.next_line() => match line {
                 Ok(Some(line)) => {
                     if line == "stop" {
@@ -80,6 +107,8 @@ pub async fn bootstrap<Server: IridiumServer + Send + Sync + 'static>(mut server
                         warn!("Reloading Iridium Server...");
                         server.on_reload(&mut ctx).await;
                         info!("reloaded Iridium server");
+                    } else if !line.trim().is_empty() {
+                        run_command(&ctx.world, &line);
                     }
                 }
                 Ok(None) => break,
@@ -99,6 +128,48 @@ pub async fn bootstrap<Server: IridiumServer + Send + Sync + 'static>(mut server
     server.on_disable(&mut ctx).await;
 }
 
+// How many ticks a connection may fall behind before it misses some.
+const TICK_BACKLOG: usize = 64;
+
+fn tick_world(world: &World, tick_tx: &broadcast::Sender<Arc<DimensionTick>>) {
+    for dimension in world.dimensions.iter() {
+        match dimension.tick() {
+            Ok(tick) => {
+                // Only fails when nobody is connected.
+                let _ = tick_tx.send(Arc::new(tick));
+            }
+            Err(e) => error!("failed to tick {}: {}", dimension.id, e),
+        }
+    }
+}
+
+// Console commands run in the overworld.
+fn run_command(world: &World, line: &str) {
+    let Some(overworld) = world.get_dimension(&DimensionId::overworld()) else {
+        warn!("no overworld to run {} in", line);
+        return;
+    };
+    match command::run(&overworld, line) {
+        Ok(feedback) => info!("{}", feedback),
+        Err(e) => warn!("{}", e),
+    }
+}
+
+// A flat overworld laid out by `world.flat`, fitted to the overworld's type
+// as configured under `dimension_types`.
+fn add_overworld(config: &ServerConfig, world: &World) -> Result<(), Box<dyn std::error::Error>> {
+    let settings = match config.get_as::<FlatSettings>("world.flat") {
+        Some(settings) => settings?,
+        None => FlatSettings::classic(),
+    };
+    let id = DimensionId::overworld();
+    let kind = world.types().get(&id).cloned().ok_or("no overworld type")?;
+    let generator = FlatGenerator::with_settings(&settings, kind.min_y, kind.height)?;
+    let overworld = Dimension::new(id.clone(), Box::new(generator)).with_type(id, kind);
+    world.add_dimension(overworld)?;
+    Ok(())
+}
+
 pub fn assert_config(config: &ServerConfig) -> Option<(&str, i64)> {
     let address = config.get_str("server.host").unwrap_or_else(|| "0.0.0.0");
     let port = config.get_int("server.port").unwrap_or_else(|| 25565);
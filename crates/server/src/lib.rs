@@ -1,11 +1,12 @@
 pub mod iridium_server;
-use std::{env, fs, path::Path, sync::Arc};
+use std::{collections::BTreeMap, env, fs, path::Path, sync::Arc};
 
 use ahash::AHashMap;
 use events::EventBus;
 pub use log;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
 pub use tokio;
+use world::{DimensionId, World, dimension::DimensionType};
 
 pub mod game;
 use time::macros::format_description;
@@ -17,6 +18,9 @@ pub struct ServerContext {
 
     #[serde(skip)]
     pub event_bus: Arc<events::EventBus>,
+
+    #[serde(skip)]
+    pub world: Arc<World>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -120,6 +124,51 @@ impl ServerConfig {
     pub fn get_int(&self, key: &str) -> Option<i64> {
         self.config.get(key).and_then(|v| v.as_i64())
     }
+
+    // Deserializes a key, or the section of keys nested under it, such as a
+    // generator's settings under `world.generator`.
+    pub fn get_as<T: DeserializeOwned>(&self, key: &str) -> Option<Result<T, serde_yaml::Error>> {
+        if let Some(value) = self.config.get(key) {
+            return Some(serde_yaml::from_value(value.clone()));
+        }
+
+        let prefix = format!("{}.", key);
+        let mut section = serde_yaml::Mapping::new();
+        for (path, value) in &self.config {
+            let Some(rest) = path.strip_prefix(&prefix) else {
+                continue;
+            };
+            let mut parts = rest.split('.').peekable();
+            let mut current = &mut section;
+            while let Some(part) = parts.next() {
+                let part = serde_yaml::Value::String(part.to_string());
+                if parts.peek().is_none() {
+                    current.insert(part, value.clone());
+                    break;
+                }
+                current = current
+                    .entry(part)
+                    .or_insert_with(|| serde_yaml::Value::Mapping(serde_yaml::Mapping::new()))
+                    .as_mapping_mut()?;
+            }
+        }
+
+        if section.is_empty() {
+            return None;
+        }
+        Some(serde_yaml::from_value(serde_yaml::Value::Mapping(section)))
+    }
+
+    // The types under `dimension_types`, keyed by id, to register on top of
+    // vanilla's. Fields a type leaves out take the overworld's.
+    pub fn dimension_types(
+        &self,
+    ) -> Result<BTreeMap<DimensionId, DimensionType>, Box<dyn std::error::Error>> {
+        match self.get_as("dimension_types") {
+            Some(types) => Ok(types?),
+            None => Ok(BTreeMap::new()),
+        }
+    }
 }
 
 impl ServerContext {
@@ -140,6 +189,7 @@ impl ServerContext {
             path,
             config,
             event_bus: event_bus.clone(),
+            world: Arc::new(World::new()),
         }
     }
 }
@@ -0,0 +1,442 @@
+// Generates the block registry from `data/blocks.json`, in the format of
+// vanilla's block report (`java -DbundlerMainClass=net.minecraft.data.Main
+// -jar server.jar --reports` writes it to `generated/reports/blocks.json`).
+//
+// The bundled copy is not that report. Its blocks up to `birch_log` are the
+// report's first ones with the report's ids; after that it holds only the
+// blocks the server uses so far, numbered on without gaps, so their state ids
+// are not the ones vanilla clients expect. Replacing it with the full report
+// for the protocol version the server speaks gives every block its client id.
+//
+// Light emission, opacity, collision and fluids are not part of the report and
+// come from `data/block_properties.json`, which is written by hand. Blocks
+// missing from it emit no light, are opaque, block motion and hold no fluid.
+// Leaves are recognized by name, which covers everything in vanilla's
+// `#leaves` tag.
+
+use std::{collections::BTreeMap, env, fmt::Write, fs, path::PathBuf};
+
+use serde_json::{Map, Value};
+
+struct Block {
+    name: String,
+    properties: Vec<(String, Vec<String>)>,
+    first_state: u32,
+    default_state: u32,
+    state_count: u32,
+    light_emission: u8,
+    opacity: u8,
+    solid: bool,
+    fluid: bool,
+    leaves: bool,
+    block_entity: Option<String>,
+}
+
+#[derive(PartialEq)]
+enum Kind {
+    Bool,
+    Int,
+    Enum,
+}
+
+fn main() {
+    let json = read_json("data/blocks.json");
+    let extra = read_json("data/block_properties.json");
+
+    let mut blocks = json
+        .iter()
+        .map(|(name, entry)| parse_block(name, entry, extra.get(name)))
+        .collect::<Vec<_>>();
+    blocks.sort_by_key(|block| block.first_state);
+
+    // State ids index `STATE_BLOCKS`, so they have to cover 0 to the last
+    // one exactly once.
+    let mut next_state = 0;
+    for block in &blocks {
+        assert_eq!(
+            block.first_state, next_state,
+            "{} does not follow the previous block's states",
+            block.name
+        );
+        next_state += block.state_count;
+    }
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap());
+    fs::write(out.join("blocks.rs"), blocks_source(&blocks)).unwrap();
+    fs::write(out.join("properties.rs"), properties_source(&blocks)).unwrap();
+}
+
+fn read_json(path: &str) -> Map<String, Value> {
+    println!("cargo::rerun-if-changed={}", path);
+    let text = fs::read_to_string(path).unwrap_or_else(|_| panic!("failed to read {}", path));
+    serde_json::from_str(&text).unwrap_or_else(|e| panic!("invalid {}: {}", path, e))
+}
+
+fn parse_block(name: &str, entry: &Value, extra: Option<&Value>) -> Block {
+    let properties = entry
+        .get("properties")
+        .and_then(Value::as_object)
+        .map(|properties| {
+            properties
+                .iter()
+                .map(|(key, values)| {
+                    let values: Vec<String> = values
+                        .as_array()
+                        .expect("property values must be a list")
+                        .iter()
+                        .map(|value| value.as_str().unwrap().to_string())
+                        .collect();
+                    (key.clone(), values)
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let states = entry["states"].as_array().expect("block has no states");
+    let first_state = states
+        .iter()
+        .map(|state| state["id"].as_u64().unwrap() as u32)
+        .min()
+        .unwrap();
+    let state_count = properties
+        .iter()
+        .map(|(_, values)| values.len() as u32)
+        .product::<u32>();
+    assert_eq!(
+        states.len() as u32,
+        state_count,
+        "{} has missing states",
+        name
+    );
+
+    // The registry computes ids from property value indices, so the report
+    // has to list states in that order. Vanilla always does.
+    let mut default_state = None;
+    for state in states {
+        let id = state["id"].as_u64().unwrap() as u32;
+        let mut expected = first_state;
+        for (key, values) in &properties {
+            let value = state["properties"][key].as_str().unwrap();
+            let index = values.iter().position(|v| v == value).unwrap() as u32;
+            expected += index * stride(&properties, key);
+        }
+        assert_eq!(id, expected, "{} has out of order states", name);
+        if state.get("default").and_then(Value::as_bool) == Some(true) {
+            default_state = Some(id);
+        }
+    }
+
+    Block {
+        name: name.to_string(),
+        properties,
+        first_state,
+        default_state: default_state.unwrap_or(first_state),
+        state_count,
+        light_emission: light_value(extra, "emission", 0),
+        opacity: light_value(extra, "opacity", 15),
+        solid: flag(extra, "solid", true),
+        fluid: flag(extra, "fluid", false),
+        leaves: name.ends_with("_leaves"),
+        block_entity: block_entity(name.strip_prefix("minecraft:").unwrap_or(name)),
+    }
+}
+
+// The block entity type a block has, following the valid blocks vanilla
+// registers for each type. Families are matched by name so a full report
+// covers every wood, color and copper variant.
+fn block_entity(name: &str) -> Option<String> {
+    let kind = match name {
+        "furnace"
+        | "trapped_chest"
+        | "ender_chest"
+        | "jukebox"
+        | "dispenser"
+        | "dropper"
+        | "creaking_heart"
+        | "brewing_stand"
+        | "enchanting_table"
+        | "end_portal"
+        | "beacon"
+        | "daylight_detector"
+        | "hopper"
+        | "comparator"
+        | "structure_block"
+        | "end_gateway"
+        | "conduit"
+        | "barrel"
+        | "smoker"
+        | "blast_furnace"
+        | "lectern"
+        | "bell"
+        | "jigsaw"
+        | "sculk_sensor"
+        | "calibrated_sculk_sensor"
+        | "sculk_catalyst"
+        | "sculk_shrieker"
+        | "chiseled_bookshelf"
+        | "decorated_pot"
+        | "crafter"
+        | "trial_spawner"
+        | "vault"
+        | "test_block"
+        | "test_instance_block" => name,
+        "spawner" => "mob_spawner",
+        "moving_piston" => "piston",
+        "command_block" | "chain_command_block" | "repeating_command_block" => "command_block",
+        "campfire" | "soul_campfire" => "campfire",
+        "bee_nest" | "beehive" => "beehive",
+        "suspicious_sand" | "suspicious_gravel" => "brushable_block",
+        "piston_head" => return None,
+        _ if name == "chest" || name.ends_with("copper_chest") => "chest",
+        _ if name.ends_with("_hanging_sign") => "hanging_sign",
+        _ if name.ends_with("_sign") => "sign",
+        _ if name.ends_with("_skull") || name.ends_with("_head") => "skull",
+        _ if name.ends_with("_banner") => "banner",
+        _ if name.ends_with("shulker_box") => "shulker_box",
+        _ if name.ends_with("_bed") => "bed",
+        _ if name.ends_with("_shelf") => "shelf",
+        _ if name.ends_with("copper_golem_statue") => "copper_golem_statue",
+        _ => return None,
+    };
+    Some(format!("minecraft:{}", kind))
+}
+
+fn light_value(extra: Option<&Value>, key: &str, default: u8) -> u8 {
+    extra
+        .and_then(|extra| extra.get(key))
+        .and_then(Value::as_u64)
+        .map_or(default, |value| value.min(15) as u8)
+}
+
+fn flag(extra: Option<&Value>, key: &str, default: bool) -> bool {
+    extra
+        .and_then(|extra| extra.get(key))
+        .and_then(Value::as_bool)
+        .unwrap_or(default)
+}
+
+fn stride(properties: &[(String, Vec<String>)], key: &str) -> u32 {
+    properties
+        .iter()
+        .skip_while(|(name, _)| name != key)
+        .skip(1)
+        .map(|(_, values)| values.len() as u32)
+        .product()
+}
+
+fn blocks_source(blocks: &[Block]) -> String {
+    let mut source = String::new();
+    let state_count = blocks
+        .iter()
+        .map(|block| block.first_state + block.state_count)
+        .max()
+        .unwrap_or(0);
+
+    writeln!(
+        source,
+        "pub(crate) static BLOCKS: [BlockInfo; {}] = [",
+        blocks.len()
+    )
+    .unwrap();
+    for block in blocks {
+        let properties = block
+            .properties
+            .iter()
+            .map(|(key, values)| {
+                format!("PropertyInfo {{ name: {:?}, values: &{:?} }}", key, values)
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(
+            source,
+            "    BlockInfo {{ name: {:?}, properties: &[{}], first_state: {}, default_state: {}, state_count: {}, light_emission: {}, opacity: {}, solid: {}, fluid: {}, leaves: {}, block_entity: {:?} }},",
+            block.name,
+            properties,
+            block.first_state,
+            block.default_state,
+            block.state_count,
+            block.light_emission,
+            block.opacity,
+            block.solid,
+            block.fluid,
+            block.leaves,
+            block.block_entity
+        )
+        .unwrap();
+    }
+    writeln!(source, "];\n").unwrap();
+
+    let mut state_blocks = vec![0u16; state_count as usize];
+    for (index, block) in blocks.iter().enumerate() {
+        let range = block.first_state..block.first_state + block.state_count;
+        state_blocks[range.start as usize..range.end as usize].fill(index as u16);
+    }
+    writeln!(
+        source,
+        "pub(crate) static STATE_BLOCKS: [u16; {}] = {:?};\n",
+        state_blocks.len(),
+        state_blocks
+    )
+    .unwrap();
+
+    let mut by_name = blocks
+        .iter()
+        .enumerate()
+        .map(|(index, block)| (block.name.as_str(), index))
+        .collect::<Vec<_>>();
+    by_name.sort();
+    writeln!(
+        source,
+        "pub(crate) static BY_NAME: [(&str, u16); {}] = {:?};\n",
+        by_name.len(),
+        by_name
+    )
+    .unwrap();
+
+    writeln!(source, "impl Block {{").unwrap();
+    for (index, block) in blocks.iter().enumerate() {
+        let name = block.name.strip_prefix("minecraft:").unwrap_or(&block.name);
+        writeln!(
+            source,
+            "    pub const {}: Block = Block({});",
+            name.to_uppercase(),
+            index
+        )
+        .unwrap();
+    }
+    writeln!(source, "}}").unwrap();
+    source
+}
+
+// One constant per property name. Properties whose values are all booleans or
+// all integers get `bool` and `u8`; anything else becomes an enum holding the
+// union of the values used by every block, so `facing` covers both the four
+// horizontal and the six full directions.
+fn properties_source(blocks: &[Block]) -> String {
+    let mut properties: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+    for block in blocks {
+        for (key, values) in &block.properties {
+            let union = properties.entry(key).or_default();
+            for value in values {
+                if !union.contains(&value.as_str()) {
+                    union.push(value);
+                }
+            }
+        }
+    }
+
+    let mut source = String::new();
+    for (key, values) in &properties {
+        let constant = key.to_uppercase();
+        match kind(values) {
+            Kind::Bool => writeln!(
+                source,
+                "pub const {}: Property<bool> = Property::new({:?});",
+                constant, key
+            )
+            .unwrap(),
+            Kind::Int => writeln!(
+                source,
+                "pub const {}: Property<u8> = Property::new({:?});",
+                constant, key
+            )
+            .unwrap(),
+            Kind::Enum => {
+                let name = camel_case(key);
+                writeln!(
+                    source,
+                    "pub const {}: Property<{}> = Property::new({:?});\n",
+                    constant, name, key
+                )
+                .unwrap();
+                enum_source(&mut source, &name, values);
+            }
+        }
+    }
+    source
+}
+
+fn enum_source(source: &mut String, name: &str, values: &[&str]) {
+    let variants = values
+        .iter()
+        .map(|value| {
+            let variant = camel_case(value);
+            if variant.starts_with(|c: char| c.is_ascii_digit()) {
+                format!("V{}", variant)
+            } else {
+                variant
+            }
+        })
+        .collect::<Vec<_>>();
+
+    writeln!(
+        source,
+        "#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\npub enum {} {{",
+        name
+    )
+    .unwrap();
+    for variant in &variants {
+        writeln!(source, "    {},", variant).unwrap();
+    }
+    writeln!(source, "}}\n").unwrap();
+
+    writeln!(source, "impl {} {{", name).unwrap();
+    writeln!(
+        source,
+        "    pub fn as_str(self) -> &'static str {{\n        match self {{"
+    )
+    .unwrap();
+    for (variant, value) in variants.iter().zip(values) {
+        writeln!(source, "            {}::{} => {:?},", name, variant, value).unwrap();
+    }
+    writeln!(source, "        }}\n    }}\n}}\n").unwrap();
+
+    writeln!(source, "impl PropertyValue for {} {{", name).unwrap();
+    writeln!(
+        source,
+        "    fn parse(value: &str) -> Option<Self> {{\n        match value {{"
+    )
+    .unwrap();
+    for (variant, value) in variants.iter().zip(values) {
+        writeln!(
+            source,
+            "            {:?} => Some({}::{}),",
+            value, name, variant
+        )
+        .unwrap();
+    }
+    writeln!(source, "            _ => None,\n        }}\n    }}\n}}\n").unwrap();
+
+    writeln!(source, "impl std::fmt::Display for {} {{", name).unwrap();
+    writeln!(
+        source,
+        "    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{\n        f.write_str(self.as_str())\n    }}\n}}\n"
+    )
+    .unwrap();
+}
+
+fn kind(values: &[&str]) -> Kind {
+    if values
+        .iter()
+        .all(|value| *value == "true" || *value == "false")
+    {
+        Kind::Bool
+    } else if values.iter().all(|value| value.parse::<u8>().is_ok()) {
+        Kind::Int
+    } else {
+        Kind::Enum
+    }
+}
+
+fn camel_case(value: &str) -> String {
+    value
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,80 @@
+// Vanilla biomes in registry order, which is what the client indexes biome
+// palettes with. Data packs that add biomes would extend this list.
+pub const BIOMES: [&str; 65] = [
+    "badlands",
+    "bamboo_jungle",
+    "basalt_deltas",
+    "beach",
+    "birch_forest",
+    "cherry_grove",
+    "cold_ocean",
+    "crimson_forest",
+    "dark_forest",
+    "deep_cold_ocean",
+    "deep_dark",
+    "deep_frozen_ocean",
+    "deep_lukewarm_ocean",
+    "deep_ocean",
+    "desert",
+    "dripstone_caves",
+    "end_barrens",
+    "end_highlands",
+    "end_midlands",
+    "eroded_badlands",
+    "flower_forest",
+    "forest",
+    "frozen_ocean",
+    "frozen_peaks",
+    "frozen_river",
+    "grove",
+    "ice_spikes",
+    "jagged_peaks",
+    "jungle",
+    "lukewarm_ocean",
+    "lush_caves",
+    "mangrove_swamp",
+    "meadow",
+    "mushroom_fields",
+    "nether_wastes",
+    "ocean",
+    "old_growth_birch_forest",
+    "old_growth_pine_taiga",
+    "old_growth_spruce_taiga",
+    "pale_garden",
+    "plains",
+    "river",
+    "savanna",
+    "savanna_plateau",
+    "small_end_islands",
+    "snowy_beach",
+    "snowy_plains",
+    "snowy_slopes",
+    "snowy_taiga",
+    "soul_sand_valley",
+    "sparse_jungle",
+    "stony_peaks",
+    "stony_shore",
+    "sunflower_plains",
+    "swamp",
+    "taiga",
+    "the_end",
+    "the_void",
+    "warm_ocean",
+    "warped_forest",
+    "windswept_forest",
+    "windswept_gravelly_hills",
+    "windswept_hills",
+    "windswept_savanna",
+    "wooded_badlands",
+];
+
+pub const PLAINS: u8 = 40;
+
+pub fn biome_id(name: &str) -> Option<u8> {
+    let name = name.strip_prefix("minecraft:").unwrap_or(name);
+    BIOMES.iter().position(|&b| b == name).map(|id| id as u8)
+}
+
+pub fn biome_name(id: u8) -> Option<&'static str> {
+    BIOMES.get(id as usize).copied()
+}
@@ -0,0 +1,346 @@
+use std::{fmt, str::FromStr};
+
+use nbt::{Compound, Value, compound};
+
+use crate::loader::StateMapper;
+
+pub mod property;
+pub mod shape;
+
+pub use property::{Property, PropertyValue};
+
+include!(concat!(env!("OUT_DIR"), "/blocks.rs"));
+
+pub(crate) struct BlockInfo {
+    name: &'static str,
+    properties: &'static [PropertyInfo],
+    first_state: u16,
+    default_state: u16,
+    state_count: u16,
+    light_emission: u8,
+    opacity: u8,
+    solid: bool,
+    fluid: bool,
+    leaves: bool,
+    block_entity: Option<&'static str>,
+}
+
+#[derive(Debug)]
+pub struct PropertyInfo {
+    pub name: &'static str,
+    pub values: &'static [&'static str],
+}
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BlockStateError {
+    #[error("Unknown block {0}")]
+    UnknownBlock(String),
+
+    #[error("Block {block} has no property {property}")]
+    UnknownProperty { block: String, property: String },
+
+    #[error("Invalid value {value} for property {property}")]
+    InvalidValue { property: String, value: String },
+
+    #[error("Malformed block state {0}")]
+    Malformed(String),
+}
+
+// A block type, indexing the generated registry. Its constants are named
+// after the identifier, `Block::OAK_STAIRS` for `minecraft:oak_stairs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Block(u16);
+
+// One combination of a block's property values. The id is the global
+// palette id sent to clients; states of a block are numbered from its first
+// state with the last property varying fastest, as vanilla does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockState(pub(crate) u16);
+
+impl Block {
+    pub fn from_name(name: &str) -> Option<Block> {
+        let name = name.strip_prefix("minecraft:").unwrap_or(name);
+        BY_NAME
+            .binary_search_by(|(key, _)| key["minecraft:".len()..].cmp(name))
+            .ok()
+            .map(|index| Block(BY_NAME[index].1))
+    }
+
+    pub fn all() -> impl Iterator<Item = Block> {
+        (0..BLOCKS.len() as u16).map(Block)
+    }
+
+    pub fn id(self) -> u16 {
+        self.0
+    }
+
+    pub fn name(self) -> &'static str {
+        self.info().name
+    }
+
+    pub fn properties(self) -> &'static [PropertyInfo] {
+        self.info().properties
+    }
+
+    pub fn default_state(self) -> BlockState {
+        BlockState(self.info().default_state)
+    }
+
+    pub fn states(self) -> impl Iterator<Item = BlockState> {
+        let info = self.info();
+        (info.first_state..info.first_state + info.state_count).map(BlockState)
+    }
+
+    pub fn with<V: PropertyValue>(self, property: Property<V>, value: V) -> BlockState {
+        self.default_state().with(property, value)
+    }
+
+    // The type of block entity the block has, such as `minecraft:sign`.
+    pub fn block_entity(self) -> Option<&'static str> {
+        self.info().block_entity
+    }
+
+    fn info(self) -> &'static BlockInfo {
+        &BLOCKS[self.0 as usize]
+    }
+}
+
+impl fmt::Display for Block {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl BlockState {
+    pub const AIR: BlockState = BlockState(0);
+
+    pub fn from_id(id: u16) -> Option<BlockState> {
+        STATE_BLOCKS.get(id as usize).map(|_| BlockState(id))
+    }
+
+    pub fn id(self) -> u16 {
+        self.0
+    }
+
+    // Ids past the registry, as a container filled from elsewhere may hold,
+    // count as air.
+    pub fn block(self) -> Block {
+        STATE_BLOCKS
+            .get(self.0 as usize)
+            .map_or(Block::AIR, |&block| Block(block))
+    }
+
+    pub fn is_air(self) -> bool {
+        matches!(self.block(), Block::AIR | Block::CAVE_AIR | Block::VOID_AIR)
+    }
+
+    pub fn light_emission(self) -> u8 {
+        self.block().info().light_emission
+    }
+
+    // How much light is lost passing through, 15 for full blocks.
+    pub fn opacity(self) -> u8 {
+        self.block().info().opacity
+    }
+
+    // Whether entities collide with it, vanilla's `blocksMotion`.
+    pub fn is_solid(self) -> bool {
+        self.block().info().solid
+    }
+
+    // Water or lava, either the fluid itself or a waterlogged block.
+    pub fn has_fluid(self) -> bool {
+        self.block().info().fluid || self.get(property::WATERLOGGED) == Some(true)
+    }
+
+    pub fn is_leaves(self) -> bool {
+        self.block().info().leaves
+    }
+
+    pub fn is_default(self) -> bool {
+        self.block().default_state() == self
+    }
+
+    pub fn get<V: PropertyValue>(self, property: Property<V>) -> Option<V> {
+        V::parse(self.value(property.name())?)
+    }
+
+    // Panics when the block has no such property or does not allow the value,
+    // like vanilla's `setValue`.
+    pub fn with<V: PropertyValue>(self, property: Property<V>, value: V) -> BlockState {
+        self.try_with(property, value).unwrap_or_else(|| {
+            panic!(
+                "{} cannot have property {} set to {:?}",
+                self.block(),
+                property.name(),
+                value
+            )
+        })
+    }
+
+    pub fn try_with<V: PropertyValue>(self, property: Property<V>, value: V) -> Option<BlockState> {
+        let (index, info) = self.property(property.name())?;
+        let value = info
+            .values
+            .iter()
+            .position(|candidate| V::parse(candidate) == Some(value))?;
+        Some(self.with_index(index, value))
+    }
+
+    pub fn value(self, name: &str) -> Option<&'static str> {
+        let (index, info) = self.property(name)?;
+        Some(info.values[self.value_index(index)])
+    }
+
+    pub fn with_value(self, name: &str, value: &str) -> Option<BlockState> {
+        let (index, info) = self.property(name)?;
+        let value = info.values.iter().position(|&v| v == value)?;
+        Some(self.with_index(index, value))
+    }
+
+    pub fn properties(self) -> impl Iterator<Item = (&'static str, &'static str)> {
+        self.block()
+            .properties()
+            .iter()
+            .enumerate()
+            .map(move |(index, info)| (info.name, info.values[self.value_index(index)]))
+    }
+
+    fn property(self, name: &str) -> Option<(usize, &'static PropertyInfo)> {
+        self.block()
+            .properties()
+            .iter()
+            .enumerate()
+            .find(|(_, info)| info.name == name)
+    }
+
+    fn stride(self, index: usize) -> u16 {
+        self.block().properties()[index + 1..]
+            .iter()
+            .map(|info| info.values.len() as u16)
+            .product()
+    }
+
+    fn value_index(self, index: usize) -> usize {
+        let offset = self.0 - self.block().info().first_state;
+        let len = self.block().properties()[index].values.len() as u16;
+        ((offset / self.stride(index)) % len) as usize
+    }
+
+    fn with_index(self, index: usize, value: usize) -> BlockState {
+        let stride = self.stride(index);
+        let current = self.value_index(index) as u16;
+        BlockState(self.0 - current * stride + value as u16 * stride)
+    }
+}
+
+impl Default for BlockState {
+    fn default() -> Self {
+        BlockState::AIR
+    }
+}
+
+impl From<Block> for BlockState {
+    fn from(block: Block) -> Self {
+        block.default_state()
+    }
+}
+
+// `minecraft:oak_stairs[facing=east,half=bottom,...]`, listing every property.
+impl fmt::Display for BlockState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.block().name())?;
+        let mut properties = self.properties().peekable();
+        if properties.peek().is_none() {
+            return Ok(());
+        }
+        f.write_str("[")?;
+        for (index, (name, value)) in properties.enumerate() {
+            if index > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}={}", name, value)?;
+        }
+        f.write_str("]")
+    }
+}
+
+// Parses `stone`, `minecraft:stone` or `oak_stairs[facing=east,half=top]`.
+// Properties that are not given keep their default value.
+impl FromStr for BlockState {
+    type Err = BlockStateError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (name, properties) = match input.split_once('[') {
+            Some((name, rest)) => {
+                let properties = rest
+                    .strip_suffix(']')
+                    .ok_or_else(|| BlockStateError::Malformed(input.to_string()))?;
+                (name, Some(properties))
+            }
+            None => (input, None),
+        };
+
+        let name = name.trim();
+        let block = Block::from_name(name)
+            .ok_or_else(|| BlockStateError::UnknownBlock(name.to_string()))?;
+        let mut state = block.default_state();
+
+        for property in properties.into_iter().flat_map(|p| p.split(',')) {
+            if property.trim().is_empty() {
+                continue;
+            }
+            let (key, value) = property
+                .split_once('=')
+                .ok_or_else(|| BlockStateError::Malformed(input.to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            if state.property(key).is_none() {
+                return Err(BlockStateError::UnknownProperty {
+                    block: block.name().to_string(),
+                    property: key.to_string(),
+                });
+            }
+            state = state
+                .with_value(key, value)
+                .ok_or_else(|| BlockStateError::InvalidValue {
+                    property: key.to_string(),
+                    value: value.to_string(),
+                })?;
+        }
+
+        Ok(state)
+    }
+}
+
+// Maps Anvil's `{Name, Properties}` palette entries onto the registry.
+// Unknown properties or values keep the default, as vanilla does.
+pub struct BlockRegistry;
+
+impl StateMapper for BlockRegistry {
+    fn state_id(&self, state: &Compound) -> Option<u16> {
+        let mut result = Block::from_name(state.get_str("Name")?)?.default_state();
+        for (key, value) in state.get_compound("Properties").into_iter().flatten() {
+            if let Some(next) = value
+                .as_str()
+                .and_then(|value| result.with_value(key, value))
+            {
+                result = next;
+            }
+        }
+        Some(result.id())
+    }
+
+    fn state_nbt(&self, id: u16) -> Option<Compound> {
+        let state = BlockState::from_id(id)?;
+        let mut nbt = compound! { "Name" => state.block().name() };
+        let properties = state
+            .properties()
+            .map(|(name, value)| (name, Value::from(value)))
+            .collect::<Compound>();
+        if !properties.is_empty() {
+            nbt.insert("Properties", properties);
+        }
+        Some(nbt)
+    }
+}
@@ -0,0 +1,59 @@
+use std::{fmt, marker::PhantomData};
+
+// A value a block state property can take, parsed from the strings the data
+// report and Anvil use.
+pub trait PropertyValue: Copy + PartialEq + fmt::Debug {
+    fn parse(value: &str) -> Option<Self>;
+}
+
+impl PropertyValue for bool {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "true" => Some(true),
+            "false" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+impl PropertyValue for u8 {
+    fn parse(value: &str) -> Option<Self> {
+        value.parse().ok()
+    }
+}
+
+// A property name typed by its values. The same property is shared by every
+// block that has it, each with its own set of allowed values.
+pub struct Property<V> {
+    name: &'static str,
+    value: PhantomData<fn() -> V>,
+}
+
+impl<V> Property<V> {
+    pub const fn new(name: &'static str) -> Self {
+        Property {
+            name,
+            value: PhantomData,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+impl<V> Clone for Property<V> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<V> Copy for Property<V> {}
+
+impl<V> fmt::Debug for Property<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Property({})", self.name)
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/properties.rs"));
@@ -0,0 +1,238 @@
+use crate::{
+    block::{Block, BlockState, property},
+    pos::{BlockPos, Direction},
+};
+
+// Recomputes the properties that depend on the blocks around, as vanilla's
+// `updateShape` does after a neighbor changed: fences and walls connect,
+// stairs form corners and grass under snow turns snowy. Blocks without such
+// properties come back unchanged.
+//
+// Blocks are recognized by name, which stands in for vanilla's tags.
+pub fn update_shape(
+    state: BlockState,
+    pos: BlockPos,
+    world: impl Fn(BlockPos) -> BlockState,
+) -> BlockState {
+    let name = state.block().name();
+    if is_fence(name) {
+        fence_shape(state, pos, &world)
+    } else if is_wall(name) {
+        wall_shape(state, pos, &world)
+    } else if name.ends_with("_stairs") {
+        stairs_shape(state, pos, &world)
+    } else if state.get(property::SNOWY).is_some() {
+        let above = world(pos.offset(Direction::Up)).block();
+        state.with(
+            property::SNOWY,
+            matches!(above, Block::SNOW | Block::SNOW_BLOCK),
+        )
+    } else {
+        state
+    }
+}
+
+fn fence_shape(
+    state: BlockState,
+    pos: BlockPos,
+    world: &impl Fn(BlockPos) -> BlockState,
+) -> BlockState {
+    let nether = Some(state.block()) == nether_brick_fence();
+    let mut result = state;
+    for direction in Direction::HORIZONTAL {
+        let neighbor = world(pos.offset(direction));
+        let face = direction.opposite();
+        let connected = if is_fence(neighbor.block().name()) {
+            (Some(neighbor.block()) == nether_brick_fence()) == nether
+        } else {
+            gate_connects(neighbor, direction) || is_sturdy_connection(neighbor, face)
+        };
+        result = result
+            .with_value(direction.name(), if connected { "true" } else { "false" })
+            .unwrap_or(result);
+    }
+    result
+}
+
+fn wall_shape(
+    state: BlockState,
+    pos: BlockPos,
+    world: &impl Fn(BlockPos) -> BlockState,
+) -> BlockState {
+    let above = world(pos.offset(Direction::Up));
+    let above_is_wall = is_wall(above.block().name());
+
+    let mut sides = [""; 4];
+    for (side, direction) in sides.iter_mut().zip(Direction::HORIZONTAL) {
+        let neighbor = world(pos.offset(direction));
+        let name = neighbor.block().name();
+        let connected = is_wall(name)
+            || is_bars(name)
+            || gate_connects(neighbor, direction)
+            || is_sturdy_connection(neighbor, direction.opposite());
+        let covered = is_sturdy(above, Direction::Down)
+            || (above_is_wall && above.value(direction.name()).is_some_and(|v| v != "none"));
+        *side = match (connected, covered) {
+            (false, _) => "none",
+            (true, false) => "low",
+            (true, true) => "tall",
+        };
+    }
+
+    // Posts show at corners, ends and under other posts; straight runs only
+    // keep theirs when something sits on top.
+    let [north, south, west, east] = sides;
+    let up = if (above_is_wall && above.value("up") == Some("true"))
+        || sides.iter().all(|side| *side == "none")
+        || (north == "none") != (south == "none")
+        || (west == "none") != (east == "none")
+    {
+        true
+    } else if (north == "tall" && south == "tall") || (west == "tall" && east == "tall") {
+        false
+    } else {
+        is_post_override(above.block().name()) || is_sturdy(above, Direction::Down)
+    };
+
+    let mut result = state;
+    for (side, direction) in sides.into_iter().zip(Direction::HORIZONTAL) {
+        result = result.with_value(direction.name(), side).unwrap_or(result);
+    }
+    result
+        .with_value("up", if up { "true" } else { "false" })
+        .unwrap_or(result)
+}
+
+// Stairs turn into an outer corner when the stairs in front of them face
+// sideways, and into an inner corner when the ones behind do.
+fn stairs_shape(
+    state: BlockState,
+    pos: BlockPos,
+    world: &impl Fn(BlockPos) -> BlockState,
+) -> BlockState {
+    let Some(facing) = facing_of(state) else {
+        return state;
+    };
+    let half = state.get(property::HALF);
+    let matching = |neighbor: BlockState| {
+        facing_of(neighbor).filter(|_| {
+            neighbor.block().name().ends_with("_stairs") && neighbor.get(property::HALF) == half
+        })
+    };
+    // A corner needs the side it turns to free of stairs lined up with these.
+    let can_take_shape = |direction: Direction| {
+        let neighbor = world(pos.offset(direction));
+        matching(neighbor) != Some(facing)
+    };
+
+    let mut shape = "straight";
+    if let Some(front) = matching(world(pos.offset(facing)))
+        && !front.is_parallel(facing)
+        && can_take_shape(front.opposite())
+    {
+        shape = if front == facing.counter_clockwise() {
+            "outer_left"
+        } else {
+            "outer_right"
+        };
+    } else if let Some(back) = matching(world(pos.offset(facing.opposite())))
+        && !back.is_parallel(facing)
+        && can_take_shape(back)
+    {
+        shape = if back == facing.counter_clockwise() {
+            "inner_left"
+        } else {
+            "inner_right"
+        };
+    }
+    state.with_value("shape", shape).unwrap_or(state)
+}
+
+fn facing_of(state: BlockState) -> Option<Direction> {
+    state.value("facing").and_then(Direction::from_name)
+}
+
+// `_fence` leaves out fence gates, `_wall` leaves out wall signs, torches,
+// banners and heads, which all end in something else.
+fn is_fence(name: &str) -> bool {
+    name.ends_with("_fence")
+}
+
+fn is_wall(name: &str) -> bool {
+    name.ends_with("_wall")
+}
+
+// Iron and copper bars and glass panes, vanilla's `IronBarsBlock`.
+fn is_bars(name: &str) -> bool {
+    name.ends_with("_bars") || name.ends_with("_pane")
+}
+
+fn nether_brick_fence() -> Option<Block> {
+    Block::from_name("minecraft:nether_brick_fence")
+}
+
+// Vanilla's `#wall_post_override`: standing torches, tripwire, signs,
+// banners and pressure plates. Wall torches and hanging signs are not in it.
+fn is_post_override(name: &str) -> bool {
+    matches!(
+        name,
+        "minecraft:torch"
+            | "minecraft:soul_torch"
+            | "minecraft:redstone_torch"
+            | "minecraft:copper_torch"
+            | "minecraft:tripwire"
+    ) || (name.ends_with("_sign") && !name.ends_with("_hanging_sign"))
+        || name.ends_with("_banner")
+        || name.ends_with("_pressure_plate")
+}
+
+// Fence gates connect along the axis they close.
+fn gate_connects(state: BlockState, direction: Direction) -> bool {
+    state.block().name().ends_with("_fence_gate")
+        && facing_of(state).is_some_and(|facing| facing.is_parallel(direction.clockwise()))
+}
+
+// Full blocks vanilla makes an exception for never take connections.
+fn is_sturdy_connection(state: BlockState, face: Direction) -> bool {
+    !is_connection_exception(state) && is_sturdy(state, face)
+}
+
+fn is_connection_exception(state: BlockState) -> bool {
+    let name = state.block().name();
+    state.is_leaves()
+        || name.ends_with("shulker_box")
+        || matches!(
+            name,
+            "minecraft:barrier"
+                | "minecraft:carved_pumpkin"
+                | "minecraft:jack_o_lantern"
+                | "minecraft:melon"
+                | "minecraft:pumpkin"
+        )
+}
+
+// Whether the given face of the block is a full square. Only full cubes,
+// the back of stairs and the faces of slabs against their half count.
+fn is_sturdy(state: BlockState, face: Direction) -> bool {
+    if !state.is_solid() {
+        return false;
+    }
+    let name = state.block().name();
+    if name.ends_with("_stairs") {
+        let half = state.value("half");
+        return match face {
+            Direction::Up => half == Some("top"),
+            Direction::Down => half == Some("bottom"),
+            _ => facing_of(state) == Some(face),
+        };
+    }
+    if name.ends_with("_slab") {
+        return match state.value("type") {
+            Some("double") => true,
+            Some("top") => face == Direction::Up,
+            Some("bottom") => face == Direction::Down,
+            _ => false,
+        };
+    }
+    state.opacity() == 15 || state.block() == Block::GLASS || state.is_leaves()
+}
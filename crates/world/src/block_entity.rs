@@ -0,0 +1,354 @@
+use std::collections::BTreeMap;
+
+use components::Component;
+use nbt::{Compound, Value, compound};
+use serde_json::Value as Json;
+
+use crate::block::Block;
+
+// Vanilla's block entity types in registry order, which gives their network
+// ids.
+pub const TYPES: [&str; 49] = [
+    "minecraft:furnace",
+    "minecraft:chest",
+    "minecraft:trapped_chest",
+    "minecraft:ender_chest",
+    "minecraft:jukebox",
+    "minecraft:dispenser",
+    "minecraft:dropper",
+    "minecraft:sign",
+    "minecraft:hanging_sign",
+    "minecraft:mob_spawner",
+    "minecraft:creaking_heart",
+    "minecraft:piston",
+    "minecraft:brewing_stand",
+    "minecraft:enchanting_table",
+    "minecraft:end_portal",
+    "minecraft:beacon",
+    "minecraft:skull",
+    "minecraft:daylight_detector",
+    "minecraft:hopper",
+    "minecraft:comparator",
+    "minecraft:banner",
+    "minecraft:structure_block",
+    "minecraft:end_gateway",
+    "minecraft:command_block",
+    "minecraft:shulker_box",
+    "minecraft:bed",
+    "minecraft:conduit",
+    "minecraft:barrel",
+    "minecraft:smoker",
+    "minecraft:blast_furnace",
+    "minecraft:lectern",
+    "minecraft:bell",
+    "minecraft:jigsaw",
+    "minecraft:campfire",
+    "minecraft:beehive",
+    "minecraft:sculk_sensor",
+    "minecraft:calibrated_sculk_sensor",
+    "minecraft:sculk_catalyst",
+    "minecraft:sculk_shrieker",
+    "minecraft:chiseled_bookshelf",
+    "minecraft:shelf",
+    "minecraft:brushable_block",
+    "minecraft:decorated_pot",
+    "minecraft:crafter",
+    "minecraft:trial_spawner",
+    "minecraft:vault",
+    "minecraft:test_block",
+    "minecraft:test_instance_block",
+    "minecraft:copper_golem_statue",
+];
+
+// Fields clients are not sent, so players can't see into containers they
+// haven't opened.
+const SERVER_ONLY: [&str; 3] = ["Items", "LootTable", "LootTableSeed"];
+
+// A block entity as Anvil stores it: its type and the rest of its fields,
+// without the position, which is where it is kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockEntity {
+    pub id: String,
+    pub data: Compound,
+}
+
+// Typed access to the fields of common block entities. Fields the type
+// doesn't know are kept as they are.
+pub trait BlockEntityData: Sized {
+    fn read(data: &Compound) -> Self;
+    fn write(&self, data: &mut Compound);
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignText {
+    pub messages: [Component; 4],
+    pub color: String,
+    pub glowing: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Sign {
+    pub front: SignText,
+    pub back: SignText,
+    pub waxed: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemStack {
+    pub id: String,
+    pub count: i32,
+    pub components: Compound,
+}
+
+// The items of a chest or other container, by slot.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Inventory {
+    pub items: BTreeMap<u8, ItemStack>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BannerPattern {
+    pub pattern: String,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Banner {
+    pub patterns: Vec<BannerPattern>,
+}
+
+pub fn network_id(id: &str) -> Option<i32> {
+    let id = id.strip_prefix("minecraft:").unwrap_or(id);
+    TYPES
+        .iter()
+        .position(|kind| kind.strip_prefix("minecraft:") == Some(id))
+        .map(|index| index as i32)
+}
+
+impl BlockEntity {
+    pub fn new(id: impl Into<String>) -> Self {
+        BlockEntity {
+            id: id.into(),
+            data: Compound::new(),
+        }
+    }
+
+    // An empty block entity of the block's type.
+    pub fn for_block(block: Block) -> Option<BlockEntity> {
+        block.block_entity().map(BlockEntity::new)
+    }
+
+    // Whether this may sit on the block. Ids are compared without the
+    // `minecraft:` namespace.
+    pub fn fits(&self, block: Block) -> bool {
+        block.block_entity().is_some_and(|kind| {
+            kind.strip_prefix("minecraft:")
+                == Some(self.id.strip_prefix("minecraft:").unwrap_or(&self.id))
+        })
+    }
+
+    pub fn get<T: BlockEntityData>(&self) -> T {
+        T::read(&self.data)
+    }
+
+    pub fn set<T: BlockEntityData>(&mut self, value: &T) {
+        value.write(&mut self.data);
+    }
+
+    pub fn with<T: BlockEntityData>(mut self, value: &T) -> Self {
+        self.set(value);
+        self
+    }
+
+    // What the Chunk Data and Block Entity Data packets carry.
+    pub fn client_data(&self) -> Compound {
+        let mut data = self.data.clone();
+        for key in SERVER_ONLY {
+            data.remove(key);
+        }
+        data
+    }
+}
+
+impl Default for SignText {
+    fn default() -> Self {
+        SignText {
+            messages: std::array::from_fn(|_| Component::text("")),
+            color: "black".to_string(),
+            glowing: false,
+        }
+    }
+}
+
+impl SignText {
+    fn read(nbt: Option<&Compound>) -> Self {
+        let mut text = SignText::default();
+        let Some(nbt) = nbt else {
+            return text;
+        };
+        for (line, message) in nbt
+            .get_list("messages")
+            .unwrap_or_default()
+            .iter()
+            .take(4)
+            .enumerate()
+        {
+            text.messages[line] = read_component(message);
+        }
+        if let Some(color) = nbt.get_str("color") {
+            text.color = color.to_string();
+        }
+        text.glowing = nbt.get_bool("has_glowing_text").unwrap_or(false);
+        text
+    }
+
+    fn write(&self) -> Compound {
+        let messages = self
+            .messages
+            .iter()
+            .map(write_component)
+            .collect::<Vec<_>>();
+        compound! {
+            "messages" => messages,
+            "color" => self.color.as_str(),
+            "has_glowing_text" => self.glowing,
+        }
+    }
+}
+
+impl BlockEntityData for Sign {
+    fn read(data: &Compound) -> Self {
+        Sign {
+            front: SignText::read(data.get_compound("front_text")),
+            back: SignText::read(data.get_compound("back_text")),
+            waxed: data.get_bool("is_waxed").unwrap_or(false),
+        }
+    }
+
+    fn write(&self, data: &mut Compound) {
+        data.insert("front_text", self.front.write());
+        data.insert("back_text", self.back.write());
+        data.insert("is_waxed", self.waxed);
+    }
+}
+
+// Stacks without an id or a count are dropped, as vanilla does.
+impl BlockEntityData for Inventory {
+    fn read(data: &Compound) -> Self {
+        let items = data
+            .get_list_of::<&Compound>("Items")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|item| {
+                let slot = item.get_i8("Slot")? as u8;
+                let stack = ItemStack {
+                    id: item.get_str("id")?.to_string(),
+                    count: item.get_i32("count").unwrap_or(1),
+                    components: item.get_compound("components").cloned().unwrap_or_default(),
+                };
+                (stack.count > 0).then_some((slot, stack))
+            })
+            .collect();
+        Inventory { items }
+    }
+
+    fn write(&self, data: &mut Compound) {
+        let items = self
+            .items
+            .iter()
+            .map(|(&slot, stack)| {
+                let mut item = compound! {
+                    "Slot" => slot as i8,
+                    "id" => stack.id.as_str(),
+                    "count" => stack.count,
+                };
+                if !stack.components.is_empty() {
+                    item.insert("components", stack.components.clone());
+                }
+                Value::Compound(item)
+            })
+            .collect::<Vec<_>>();
+        data.insert("Items", items);
+    }
+}
+
+impl BlockEntityData for Banner {
+    fn read(data: &Compound) -> Self {
+        let patterns = data
+            .get_list_of::<&Compound>("patterns")
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|layer| {
+                Some(BannerPattern {
+                    pattern: layer.get_str("pattern")?.to_string(),
+                    color: layer.get_str("color").unwrap_or("white").to_string(),
+                })
+            })
+            .collect();
+        Banner { patterns }
+    }
+
+    fn write(&self, data: &mut Compound) {
+        let patterns = self
+            .patterns
+            .iter()
+            .map(|layer| {
+                Value::Compound(compound! {
+                    "pattern" => layer.pattern.as_str(),
+                    "color" => layer.color.as_str(),
+                })
+            })
+            .collect::<Vec<_>>();
+        data.insert("patterns", patterns);
+    }
+}
+
+// Text components are stored as NBT since 1.21.5: plain text as a string,
+// anything else as a compound. Booleans come back as bytes.
+fn read_component(value: &Value) -> Component {
+    match value {
+        Value::String(text) => Component::text(text.as_str()),
+        value => serde_json::from_value(component_json(value, Component::text("").protocol))
+            .unwrap_or_else(|_| {
+                let text = value
+                    .as_compound()
+                    .and_then(|component| component.get_str("text"))
+                    .unwrap_or_default();
+                Component::text(text)
+            }),
+    }
+}
+
+// The protocol is never stored, so every component gets the one a built
+// component starts with.
+fn component_json(value: &Value, protocol: i32) -> Json {
+    match value {
+        Value::Byte(byte @ (0 | 1)) => Json::Bool(*byte == 1),
+        Value::List(list) => list
+            .iter()
+            .map(|value| component_json(value, protocol))
+            .collect(),
+        Value::Compound(compound) => {
+            let mut object: serde_json::Map<_, _> = compound
+                .iter()
+                .map(|(key, value)| (key.clone(), component_json(value, protocol)))
+                .collect();
+            object.insert("protocol".to_string(), protocol.into());
+            Json::Object(object)
+        }
+        value => value.to_json(),
+    }
+}
+
+fn write_component(component: &Component) -> Value {
+    if let components::Content::Text { text } = &component.content
+        && component.style == Default::default()
+        && component.extra.is_empty()
+    {
+        return Value::String(text.clone());
+    }
+    serde_json::to_value(component)
+        .ok()
+        .and_then(|json| Value::from_json(&json).ok())
+        .unwrap_or_else(|| Value::String(String::new()))
+}
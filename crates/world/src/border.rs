@@ -0,0 +1,307 @@
+use nbt::{Compound, compound};
+
+use crate::{Dimension, loader::LoaderError, pos::BlockPos};
+
+// The largest border vanilla allows, leaving the outermost blocks of the
+// 30 million block world outside.
+pub const MAX_SIZE: f64 = 59_999_968.0;
+
+const MILLIS_PER_TICK: i64 = 50;
+
+// Where the border's saved data lives in the dimension's `data` directory.
+const DATA_NAME: &str = "minecraft/world_border";
+
+// A square border around a center on x and z. Its size may be moving to a
+// target over a number of ticks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBorder {
+    pub center_x: f64,
+    pub center_z: f64,
+    from: f64,
+    to: f64,
+    elapsed: u64,
+    duration: u64,
+    // Damage per block beyond the buffer, per second outside.
+    pub damage_per_block: f64,
+    pub damage_buffer: f64,
+    pub warning_blocks: i32,
+    pub warning_time: i32,
+    pub portal_teleport_boundary: i32,
+}
+
+// What players in the dimension are sent about the border changing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BorderChange {
+    Center { x: f64, z: f64 },
+    Size(f64),
+    Lerp { from: f64, to: f64, millis: i64 },
+    WarningTime(i32),
+    WarningBlocks(i32),
+}
+
+impl Default for WorldBorder {
+    fn default() -> Self {
+        WorldBorder {
+            center_x: 0.0,
+            center_z: 0.0,
+            from: MAX_SIZE,
+            to: MAX_SIZE,
+            elapsed: 0,
+            duration: 0,
+            damage_per_block: 0.2,
+            damage_buffer: 5.0,
+            warning_blocks: 5,
+            warning_time: 15,
+            portal_teleport_boundary: 29_999_984,
+        }
+    }
+}
+
+impl WorldBorder {
+    pub fn size(&self) -> f64 {
+        if self.elapsed >= self.duration {
+            return self.to;
+        }
+        let progress = self.elapsed as f64 / self.duration as f64;
+        self.from + (self.to - self.from) * progress
+    }
+
+    // The size it is moving to, or the size when it isn't.
+    pub fn target_size(&self) -> f64 {
+        self.to
+    }
+
+    pub fn remaining_ticks(&self) -> u64 {
+        self.duration - self.elapsed.min(self.duration)
+    }
+
+    pub fn remaining_millis(&self) -> i64 {
+        self.remaining_ticks() as i64 * MILLIS_PER_TICK
+    }
+
+    pub fn set_size(&mut self, size: f64) {
+        let size = size.clamp(1.0, MAX_SIZE);
+        self.from = size;
+        self.to = size;
+        self.elapsed = 0;
+        self.duration = 0;
+    }
+
+    // Moves from the current size to the target over the ticks. No ticks
+    // sets the size right away.
+    pub fn lerp_size(&mut self, size: f64, ticks: u64) {
+        let from = self.size();
+        self.set_size(size);
+        if ticks > 0 {
+            self.from = from;
+            self.duration = ticks;
+        }
+    }
+
+    pub fn tick(&mut self) {
+        if self.elapsed < self.duration {
+            self.elapsed += 1;
+        }
+    }
+
+    pub fn min_x(&self) -> f64 {
+        (self.center_x - self.size() / 2.0).max(-MAX_SIZE / 2.0)
+    }
+
+    pub fn max_x(&self) -> f64 {
+        (self.center_x + self.size() / 2.0).min(MAX_SIZE / 2.0)
+    }
+
+    pub fn min_z(&self) -> f64 {
+        (self.center_z - self.size() / 2.0).max(-MAX_SIZE / 2.0)
+    }
+
+    pub fn max_z(&self) -> f64 {
+        (self.center_z + self.size() / 2.0).min(MAX_SIZE / 2.0)
+    }
+
+    pub fn contains(&self, x: f64, z: f64) -> bool {
+        x >= self.min_x() && x < self.max_x() && z >= self.min_z() && z < self.max_z()
+    }
+
+    // Whether the whole block is inside.
+    pub fn contains_block(&self, pos: BlockPos) -> bool {
+        let (x, z) = (pos.x as f64, pos.z as f64);
+        x >= self.min_x() && x + 1.0 <= self.max_x() && z >= self.min_z() && z + 1.0 <= self.max_z()
+    }
+
+    // How far the point is from the nearest edge, negative outside.
+    pub fn distance_inside(&self, x: f64, z: f64) -> f64 {
+        (x - self.min_x())
+            .min(self.max_x() - x)
+            .min(z - self.min_z())
+            .min(self.max_z() - z)
+    }
+
+    // The damage an entity at the point takes each second, as vanilla deals
+    // it: at least one point once past the buffer.
+    pub fn damage(&self, x: f64, z: f64) -> f64 {
+        let beyond = -(self.distance_inside(x, z) + self.damage_buffer);
+        if beyond <= 0.0 || self.damage_per_block <= 0.0 {
+            return 0.0;
+        }
+        (beyond * self.damage_per_block).floor().max(1.0)
+    }
+
+    // Where a move from one point to another ends. Moves that would leave
+    // the border stop just inside it; entities already outside, as when it
+    // shrinks past them, move freely.
+    pub fn clamp_move(&self, from: (f64, f64), to: (f64, f64)) -> (f64, f64) {
+        if self.contains(to.0, to.1) || !self.contains(from.0, from.1) {
+            return to;
+        }
+        let inside = |value: f64, min: f64, max: f64| value.clamp(min, max - 1e-7);
+        (
+            inside(to.0, self.min_x(), self.max_x()),
+            inside(to.1, self.min_z(), self.max_z()),
+        )
+    }
+
+    // As vanilla's world border saved data. A moving border saves where it
+    // is headed and how long is left.
+    pub fn to_nbt(&self) -> Compound {
+        compound! {
+            "center_x" => self.center_x,
+            "center_z" => self.center_z,
+            "size" => self.size(),
+            "lerp_time" => self.remaining_millis(),
+            "lerp_target" => self.to,
+            "safe_zone" => self.damage_buffer,
+            "damage_per_block" => self.damage_per_block,
+            "warning_blocks" => self.warning_blocks,
+            "warning_time" => self.warning_time,
+        }
+    }
+
+    // Fields that are missing keep their defaults.
+    pub fn from_nbt(nbt: &Compound) -> Self {
+        let defaults = WorldBorder::default();
+        let mut border = WorldBorder {
+            center_x: nbt.get_f64("center_x").unwrap_or(defaults.center_x),
+            center_z: nbt.get_f64("center_z").unwrap_or(defaults.center_z),
+            damage_per_block: nbt
+                .get_f64("damage_per_block")
+                .unwrap_or(defaults.damage_per_block),
+            damage_buffer: nbt.get_f64("safe_zone").unwrap_or(defaults.damage_buffer),
+            warning_blocks: nbt
+                .get_i32("warning_blocks")
+                .unwrap_or(defaults.warning_blocks),
+            warning_time: nbt.get_i32("warning_time").unwrap_or(defaults.warning_time),
+            ..defaults
+        };
+        border.set_size(nbt.get_f64("size").unwrap_or(MAX_SIZE));
+        let millis = nbt.get_i64("lerp_time").unwrap_or(0);
+        if millis > 0
+            && let Some(target) = nbt.get_f64("lerp_target")
+        {
+            border.lerp_size(target, (millis / MILLIS_PER_TICK) as u64);
+        }
+        border
+    }
+}
+
+// The border's setters send players in the dimension the change; see
+// `take_border_changes`.
+impl Dimension {
+    pub fn border(&self) -> WorldBorder {
+        *self.border.lock()
+    }
+
+    pub fn set_border_center(&self, x: f64, z: f64) {
+        let limit = MAX_SIZE / 2.0;
+        let (x, z) = (x.clamp(-limit, limit), z.clamp(-limit, limit));
+        let mut border = self.border.lock();
+        border.center_x = x;
+        border.center_z = z;
+        self.border_changes
+            .lock()
+            .push(BorderChange::Center { x, z });
+    }
+
+    pub fn set_border_size(&self, size: f64) {
+        let mut border = self.border.lock();
+        border.set_size(size);
+        self.border_changes
+            .lock()
+            .push(BorderChange::Size(border.size()));
+    }
+
+    pub fn lerp_border_size(&self, size: f64, ticks: u64) {
+        let mut border = self.border.lock();
+        border.lerp_size(size, ticks);
+        let change = match border.remaining_ticks() {
+            0 => BorderChange::Size(border.size()),
+            _ => BorderChange::Lerp {
+                from: border.size(),
+                to: border.target_size(),
+                millis: border.remaining_millis(),
+            },
+        };
+        self.border_changes.lock().push(change);
+    }
+
+    pub fn set_border_warning_time(&self, seconds: i32) {
+        self.border.lock().warning_time = seconds;
+        self.border_changes
+            .lock()
+            .push(BorderChange::WarningTime(seconds));
+    }
+
+    pub fn set_border_warning_blocks(&self, blocks: i32) {
+        self.border.lock().warning_blocks = blocks;
+        self.border_changes
+            .lock()
+            .push(BorderChange::WarningBlocks(blocks));
+    }
+
+    // Clients work damage out on their own, so nothing is sent.
+    pub fn set_border_damage(&self, per_block: f64, buffer: f64) {
+        let mut border = self.border.lock();
+        border.damage_per_block = per_block;
+        border.damage_buffer = buffer;
+    }
+
+    // Runs once per game tick. Clients move the border on their own.
+    pub fn tick_border(&self) {
+        self.border.lock().tick();
+    }
+
+    // The changes since the last call, in order, for the world border
+    // packets.
+    pub fn take_border_changes(&self) -> Vec<BorderChange> {
+        std::mem::take(&mut *self.border_changes.lock())
+    }
+
+    // Whether players may place or break the block: inside the border and
+    // the dimension's height.
+    pub fn can_build(&self, pos: BlockPos) -> bool {
+        pos.y >= self.min_y()
+            && pos.y < self.min_y() + self.height()
+            && self.border.lock().contains_block(pos)
+    }
+
+    // Reads the border saved with the dimension, if there is one. Called by
+    // `with_storage`.
+    pub fn load_border(&self) -> Result<bool, LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(false);
+        };
+        let Some(data) = storage.read_data(DATA_NAME)? else {
+            return Ok(false);
+        };
+        *self.border.lock() = WorldBorder::from_nbt(&data);
+        Ok(true)
+    }
+
+    pub fn save_border(&self) -> Result<(), LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        storage.write_data(DATA_NAME, self.border().to_nbt())
+    }
+}
@@ -1,82 +1,358 @@
+use std::{collections::BTreeMap, sync::OnceLock};
+
+use bytes::{BufMut, Bytes, BytesMut};
+use protocol::serial::{PacketError, PacketWrite};
+
+use crate::{
+    biome,
+    block::BlockState,
+    block_entity::BlockEntity,
+    heightmap::{HeightmapKind, Heightmaps},
+    light::{LightKind, NibbleArray},
+    palette::{PalettedContainer, Strategy},
+};
+
 #[derive(Debug, Clone)]
 pub struct Chunk {
     pub x: i32,
     pub z: i32,
+    pub min_y: i32,
+    // Mutate through `section_mut` or the chunk's setters so the cached
+    // packet is dropped.
     pub sections: Vec<ChunkSection>,
+    pub heightmaps: Heightmaps,
+    // Keyed like the chunk's setters take positions: `x` and `z` local,
+    // `y` absolute.
+    block_entities: BTreeMap<(usize, i32, usize), BlockEntity>,
+    pub(crate) encoded: OnceLock<Bytes>,
 }
 
 #[derive(Debug, Clone)]
 pub struct ChunkSection {
     pub blocks_count: u16,
-    pub blocks: Box<[u16; 4096]>,
-    pub biomes: Option<Box<[u8; 64]>>,
+    pub blocks: PalettedContainer,
+    pub biomes: PalettedContainer,
+    pub sky_light: NibbleArray,
+    pub block_light: NibbleArray,
+    // Indices of blocks whose light emission or opacity changed since the
+    // light engine last ran.
+    pub(crate) light_updates: Vec<u16>,
+}
+
+impl Default for ChunkSection {
+    fn default() -> Self {
+        ChunkSection::new()
+    }
 }
 
 impl ChunkSection {
     pub fn new() -> Self {
         ChunkSection {
             blocks_count: 0,
-            blocks: Box::new([0; 4096]),
-            biomes: None,
+            blocks: PalettedContainer::new(Strategy::BLOCKS, 0),
+            biomes: PalettedContainer::new(Strategy::BIOMES, biome::PLAINS as u16),
+            sky_light: NibbleArray::default(),
+            block_light: NibbleArray::default(),
+            light_updates: Vec::new(),
         }
     }
 
-    pub fn set_block(&mut self, x: usize, y: usize, z: usize, block_id: u16) {
+    pub fn set_block(&mut self, x: usize, y: usize, z: usize, state: BlockState) -> BlockState {
         let index = (y * 16 + z) * 16 + x;
-        let old_id = self.blocks[index];
+        let old = BlockState(self.blocks.set(index, state.id()));
+        if old.opacity() != state.opacity() || old.light_emission() != state.light_emission() {
+            self.light_updates.push(index as u16);
+        }
 
-        if old_id == 0 && block_id != 0 {
+        if old.is_air() && !state.is_air() {
             self.blocks_count += 1;
-        } else if old_id != 0 && block_id == 0 {
+        } else if !old.is_air() && state.is_air() {
             self.blocks_count -= 1;
         }
-        self.blocks[index] = block_id;
+        old
+    }
+
+    pub fn get_block(&self, x: usize, y: usize, z: usize) -> BlockState {
+        BlockState(self.blocks.get((y * 16 + z) * 16 + x))
+    }
+
+    // Biomes are stored per 4x4x4 cell, so coordinates here are 0..4.
+    pub fn set_biome(&mut self, x: usize, y: usize, z: usize, biome: u8) {
+        self.biomes.set((y * 4 + z) * 4 + x, biome as u16);
+    }
+
+    pub fn get_biome(&self, x: usize, y: usize, z: usize) -> u8 {
+        self.biomes.get((y * 4 + z) * 4 + x) as u8
+    }
+
+    pub fn fill(&mut self, state: BlockState) {
+        self.blocks.fill(state.id());
+        self.light_updates = (0..4096).collect();
+        self.blocks_count = if state.is_air() { 0 } else { 4096 };
+    }
+
+    // Swaps in all the blocks at once and returns the indices of those that
+    // changed.
+    pub fn set_blocks(&mut self, blocks: PalettedContainer) -> Vec<u16> {
+        let mut changed = Vec::new();
+        for index in 0..4096 {
+            let old = BlockState(self.blocks.get(index));
+            let state = BlockState(blocks.get(index));
+            if old == state {
+                continue;
+            }
+            changed.push(index as u16);
+            if old.opacity() != state.opacity() || old.light_emission() != state.light_emission() {
+                self.light_updates.push(index as u16);
+            }
+        }
+        if !changed.is_empty() {
+            self.blocks = blocks;
+            self.recount();
+        }
+        changed
     }
 
-    pub fn get_block(&self, x: usize, y: usize, z: usize) -> u16 {
-        self.blocks[(y * 16 + z) * 16 + x]
+    pub fn light(&self, kind: LightKind, x: usize, y: usize, z: usize) -> u8 {
+        let index = (y * 16 + z) * 16 + x;
+        match kind {
+            LightKind::Sky => self.sky_light.get(index),
+            LightKind::Block => self.block_light.get(index),
+        }
+    }
+
+    pub fn set_light(&mut self, kind: LightKind, x: usize, y: usize, z: usize, level: u8) {
+        let index = (y * 16 + z) * 16 + x;
+        match kind {
+            LightKind::Sky => self.sky_light.set(index, level),
+            LightKind::Block => self.block_light.set(index, level),
+        }
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        size_of::<ChunkSection>()
+            + self.blocks.memory_usage()
+            + self.biomes.memory_usage()
+            + self.sky_light.memory_usage()
+            + self.block_light.memory_usage()
+            + self.light_updates.capacity() * size_of::<u16>()
+    }
+
+    pub fn recount(&mut self) {
+        self.blocks_count = self.blocks.count(|id| !BlockState(id).is_air()) as u16;
+    }
+}
+
+impl PacketWrite for ChunkSection {
+    fn write(&self, buffer: &mut BytesMut) -> Result<(), PacketError> {
+        buffer.put_i16(self.blocks_count as i16);
+        self.blocks.write(buffer)?;
+        self.biomes.write(buffer)
     }
 }
 
 impl Chunk {
     pub fn new(x: i32, z: i32) -> Self {
+        Chunk::with_height(x, z, -64, 384)
+    }
+
+    pub fn with_height(x: i32, z: i32, min_y: i32, height: i32) -> Self {
         Chunk {
             x,
             z,
-            sections: vec![ChunkSection::new(); 24],
+            min_y,
+            sections: vec![ChunkSection::new(); (height / 16) as usize],
+            heightmaps: Heightmaps::default(),
+            block_entities: BTreeMap::new(),
+            encoded: OnceLock::new(),
         }
     }
 
-    pub fn fill_layer(&mut self, y: i32, block_id: u16) {
-        if y < -64 || y > 320 {
-            return;
+    pub fn height(&self) -> i32 {
+        self.sections.len() as i32 * 16
+    }
+
+    pub fn section_mut(&mut self, index: usize) -> Option<&mut ChunkSection> {
+        self.mark_dirty();
+        self.sections.get_mut(index)
+    }
+
+    // An estimate of the memory the chunk holds, including its cached packet.
+    pub fn memory_usage(&self) -> usize {
+        size_of::<Chunk>()
+            + self
+                .sections
+                .iter()
+                .map(ChunkSection::memory_usage)
+                .sum::<usize>()
+            + self.encoded.get().map_or(0, Bytes::len)
+    }
+
+    pub fn mark_dirty(&mut self) {
+        self.encoded.take();
+    }
+
+    // `x` and `z` are local to the chunk, `y` is absolute.
+    // Returns the state that was replaced, air outside the chunk's height.
+    pub fn set_block(&mut self, x: usize, y: i32, z: usize, state: BlockState) -> BlockState {
+        let (index, local_y) = self.section_of(y);
+        let Some(section) = index.and_then(|index| self.section_mut(index)) else {
+            return BlockState::AIR;
+        };
+        let old = section.set_block(x, local_y, z, state);
+        if old != state {
+            self.refresh_heightmaps(x, z, y - self.min_y);
         }
+        if old.block() != state.block() {
+            self.block_entities.remove(&(x, y, z));
+        }
+        old
+    }
 
-        let idx = ((y + 64) / 16) as usize;
-        let local_y = ((y + 64) % 16) as usize;
+    pub fn get_block(&self, x: usize, y: i32, z: usize) -> BlockState {
+        let (index, local_y) = self.section_of(y);
+        index
+            .and_then(|index| self.sections.get(index))
+            .map_or(BlockState::AIR, |section| section.get_block(x, local_y, z))
+    }
 
-        let section = &mut self.sections[idx];
+    pub fn block_entity(&self, x: usize, y: i32, z: usize) -> Option<&BlockEntity> {
+        self.block_entities.get(&(x, y, z))
+    }
 
-        let start_index = local_y * 256;
-        let end_idnex = start_index + 256;
-        let slice = &mut section.blocks[start_index..end_idnex];
+    // Only where the block has that type of block entity. Returns whether it
+    // was set.
+    pub fn set_block_entity(&mut self, x: usize, y: i32, z: usize, entity: BlockEntity) -> bool {
+        if !entity.fits(self.get_block(x, y, z).block()) {
+            return false;
+        }
+        self.mark_dirty();
+        self.block_entities.insert((x, y, z), entity);
+        true
+    }
 
-        let existing_blocks = slice.iter().filter(|&&id| id != 0).count() as u16;
-        section.blocks_count -= existing_blocks;
-        slice.fill(block_id);
-        if block_id != 0 {
-            section.blocks_count += 256;
+    pub fn remove_block_entity(&mut self, x: usize, y: i32, z: usize) -> Option<BlockEntity> {
+        let removed = self.block_entities.remove(&(x, y, z));
+        if removed.is_some() {
+            self.mark_dirty();
         }
+        removed
+    }
+
+    pub fn block_entities(&self) -> impl Iterator<Item = ((usize, i32, usize), &BlockEntity)> {
+        self.block_entities
+            .iter()
+            .map(|(&pos, entity)| (pos, entity))
     }
 
-    pub fn fill_section(&mut self, section_index: usize, block_id: u16) {
-        if let Some(section) = self.sections.get_mut(section_index) {
-            section.blocks.fill(block_id);
-            if block_id == 0 {
-                section.blocks_count = 0;
-            } else {
-                section.blocks_count = 4096;
+    // For chunks whose sections were changed directly: drops the block
+    // entities whose block was replaced.
+    pub fn retain_block_entities(&mut self) {
+        let before = self.block_entities.len();
+        let sections = &self.sections;
+        let min_y = self.min_y;
+        self.block_entities.retain(|&(x, y, z), entity| {
+            let relative = y - min_y;
+            usize::try_from(relative >> 4)
+                .ok()
+                .and_then(|index| sections.get(index))
+                .is_some_and(|section| {
+                    entity.fits(section.get_block(x, (relative & 15) as usize, z).block())
+                })
+        });
+        if self.block_entities.len() != before {
+            self.mark_dirty();
+        }
+    }
+
+    // Above the chunk is open sky, below it is dark.
+    pub fn light(&self, kind: LightKind, x: usize, y: i32, z: usize) -> u8 {
+        let (index, local_y) = self.section_of(y);
+        match index.and_then(|index| self.sections.get(index)) {
+            Some(section) => section.light(kind, x, local_y, z),
+            None if kind == LightKind::Sky && y >= self.min_y + self.height() => 15,
+            None => 0,
+        }
+    }
+
+    pub fn set_light(&mut self, kind: LightKind, x: usize, y: i32, z: usize, level: u8) {
+        let (index, local_y) = self.section_of(y);
+        if let Some(section) = index.and_then(|index| self.section_mut(index)) {
+            section.set_light(kind, x, local_y, z, level);
+        }
+    }
+
+    pub fn fill_layer(&mut self, y: i32, state: BlockState) {
+        let (index, local_y) = self.section_of(y);
+        let Some(section) = index.and_then(|index| self.section_mut(index)) else {
+            return;
+        };
+        for z in 0..16 {
+            for x in 0..16 {
+                section.set_block(x, local_y, z, state);
             }
         }
+        for z in 0..16 {
+            for x in 0..16 {
+                self.refresh_heightmaps(x, z, y - self.min_y);
+            }
+        }
+        self.retain_block_entities();
+    }
+
+    pub fn fill_section(&mut self, section_index: usize, state: BlockState) {
+        let Some(section) = self.section_mut(section_index) else {
+            return;
+        };
+        section.fill(state);
+        for z in 0..16 {
+            for x in 0..16 {
+                self.refresh_heightmaps(x, z, section_index as i32 * 16 + 15);
+            }
+        }
+        self.retain_block_entities();
+    }
+
+    // The y just above the highest block the heightmap counts, or the bottom
+    // of the chunk when the column has none.
+    pub fn height_at(&self, kind: HeightmapKind, x: usize, z: usize) -> i32 {
+        self.min_y + self.heightmaps.get(kind).get(x, z) as i32
+    }
+
+    // For chunks whose sections were changed directly.
+    pub fn recompute_heightmaps(&mut self) {
+        let top = self.height() - 1;
+        for z in 0..16 {
+            for x in 0..16 {
+                for kind in HeightmapKind::ALL {
+                    self.heightmaps.get_mut(kind).set(x, z, 0);
+                }
+                self.refresh_heightmaps(x, z, top);
+            }
+        }
+    }
+
+    // Blocks at or below the relative height `changed` may differ. Columns
+    // whose top is above it are unaffected; the rest are scanned down.
+    fn refresh_heightmaps(&mut self, x: usize, z: usize, changed: i32) {
+        for kind in HeightmapKind::ALL {
+            let current = self.heightmaps.get(kind).get(x, z) as i32;
+            if current - 1 > changed {
+                continue;
+            }
+            let top = (0..=changed)
+                .rev()
+                .find(|&y| kind.matches(self.get_block(x, self.min_y + y, z)))
+                .map_or(0, |y| y + 1);
+            self.heightmaps.get_mut(kind).set(x, z, top as u16);
+        }
+    }
+
+    fn section_of(&self, y: i32) -> (Option<usize>, usize) {
+        let relative = y - self.min_y;
+        if relative < 0 || relative >= self.height() {
+            return (None, 0);
+        }
+        (Some((relative / 16) as usize), (relative % 16) as usize)
     }
 }
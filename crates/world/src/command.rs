@@ -0,0 +1,89 @@
+use crate::{Dimension, time::TICKS_PER_DAY, weather::WeatherKind};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    #[error("Unknown command {0}")]
+    Unknown(String),
+    #[error("Usage: {0}")]
+    Usage(&'static str),
+    #[error("Invalid time {0}")]
+    InvalidTime(String),
+}
+
+const TIME_USAGE: &str = "time set|add <time> | time query daytime|gametime|day";
+const WEATHER_USAGE: &str = "weather clear|rain|thunder [duration]";
+
+// Vanilla's `/time` and `/weather` for one dimension, such as the one the
+// sender is in. Returns the feedback to show.
+pub fn run(dimension: &Dimension, line: &str) -> Result<String, CommandError> {
+    let mut args = line.trim().trim_start_matches('/').split_whitespace();
+    match args.next() {
+        Some("time") => time(dimension, args.collect()),
+        Some("weather") => weather(dimension, args.collect()),
+        _ => Err(CommandError::Unknown(line.trim().to_string())),
+    }
+}
+
+fn time(dimension: &Dimension, args: Vec<&str>) -> Result<String, CommandError> {
+    match args[..] {
+        ["set", value] => {
+            let ticks = match value {
+                "day" => 1000,
+                "noon" => 6000,
+                "night" => 13000,
+                "midnight" => 18000,
+                value => parse_time(value)?,
+            };
+            dimension.set_time_of_day(ticks);
+            Ok(format!("Set the time to {}", ticks))
+        }
+        ["add", value] => {
+            dimension.add_time(parse_time(value)?);
+            Ok(format!("Set the time to {}", dimension.time().day_time()))
+        }
+        ["query", what] => {
+            let time = dimension.time();
+            let value = match what {
+                "daytime" => time.day_time(),
+                "gametime" => time.age,
+                "day" => time.day(),
+                _ => return Err(CommandError::Usage(TIME_USAGE)),
+            };
+            Ok(format!("The time is {}", value))
+        }
+        _ => Err(CommandError::Usage(TIME_USAGE)),
+    }
+}
+
+fn weather(dimension: &Dimension, args: Vec<&str>) -> Result<String, CommandError> {
+    let (kind, duration) = match args[..] {
+        [kind] => (kind, None),
+        [kind, duration] => (kind, Some(parse_time(duration)?)),
+        _ => return Err(CommandError::Usage(WEATHER_USAGE)),
+    };
+    let (kind, name) = match kind {
+        "clear" => (WeatherKind::Clear, "clear"),
+        "rain" => (WeatherKind::Rain, "rain"),
+        "thunder" => (WeatherKind::Thunder, "rain & thunder"),
+        _ => return Err(CommandError::Usage(WEATHER_USAGE)),
+    };
+    let duration = duration.map(|ticks| ticks.clamp(0, u32::MAX as i64) as u32);
+    dimension.set_weather(kind, duration);
+    Ok(format!("Set the weather to {}", name))
+}
+
+// Ticks, or a number with `t`, `s` or `d` for ticks, seconds or days.
+fn parse_time(input: &str) -> Result<i64, CommandError> {
+    let invalid = || CommandError::InvalidTime(input.to_string());
+    let (number, scale) = match input.char_indices().last() {
+        Some((at, 't')) => (&input[..at], 1.0),
+        Some((at, 's')) => (&input[..at], 20.0),
+        Some((at, 'd')) => (&input[..at], TICKS_PER_DAY as f64),
+        _ => (input, 1.0),
+    };
+    let ticks = number.parse::<f64>().map_err(|_| invalid())? * scale;
+    if !ticks.is_finite() || ticks < 0.0 {
+        return Err(invalid());
+    }
+    Ok(ticks.round() as i64)
+}
@@ -0,0 +1,278 @@
+use std::{fmt, str::FromStr};
+
+use nbt::Compound;
+use serde::{Deserialize, Serialize};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq)]
+pub enum DimensionError {
+    #[error("Invalid identifier {0}")]
+    InvalidId(String),
+    #[error("Dimension {0} already exists")]
+    Duplicate(DimensionId),
+    #[error("Dimension type {0} is already registered with other properties")]
+    TypeMismatch(DimensionId),
+    #[error("Invalid height: {0}")]
+    InvalidHeight(String),
+}
+
+// A namespaced identifier like `minecraft:overworld` or `myteam:lobby`,
+// naming both dimensions and dimension types. Ids without a namespace get
+// `minecraft:`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DimensionId(String);
+
+impl DimensionId {
+    pub fn overworld() -> Self {
+        DimensionId("minecraft:overworld".to_string())
+    }
+
+    pub fn nether() -> Self {
+        DimensionId("minecraft:the_nether".to_string())
+    }
+
+    pub fn end() -> Self {
+        DimensionId("minecraft:the_end".to_string())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for DimensionId {
+    type Err = DimensionError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (namespace, path) = input.split_once(':').unwrap_or(("minecraft", input));
+        let valid = |text: &str, extra: &[char]| {
+            !text.is_empty()
+                && text.chars().all(|c| {
+                    c.is_ascii_lowercase()
+                        || c.is_ascii_digit()
+                        || matches!(c, '_' | '-' | '.')
+                        || extra.contains(&c)
+                })
+        };
+        if !valid(namespace, &[]) || !valid(path, &['/']) {
+            return Err(DimensionError::InvalidId(input.to_string()));
+        }
+        Ok(DimensionId(format!("{}:{}", namespace, path)))
+    }
+}
+
+impl TryFrom<String> for DimensionId {
+    type Error = DimensionError;
+
+    fn try_from(id: String) -> Result<Self, Self::Error> {
+        id.parse()
+    }
+}
+
+impl From<DimensionId> for String {
+    fn from(id: DimensionId) -> Self {
+        id.0
+    }
+}
+
+impl fmt::Display for DimensionId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// The properties clients and the server share for every dimension of a
+// type, named as in vanilla's dimension_type registry. Fields left out of
+// a config take the overworld's values.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DimensionType {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixed_time: Option<i64>,
+    pub has_skylight: bool,
+    pub has_ceiling: bool,
+    pub ultrawarm: bool,
+    pub natural: bool,
+    pub coordinate_scale: f64,
+    pub bed_works: bool,
+    pub respawn_anchor_works: bool,
+    pub min_y: i32,
+    pub height: i32,
+    pub logical_height: i32,
+    pub infiniburn: String,
+    pub effects: String,
+    pub ambient_light: f32,
+    pub piglin_safe: bool,
+    pub has_raids: bool,
+    pub monster_spawn_light_level: i32,
+    pub monster_spawn_block_light_limit: i32,
+}
+
+impl Default for DimensionType {
+    fn default() -> Self {
+        DimensionType::overworld()
+    }
+}
+
+impl DimensionType {
+    pub fn overworld() -> Self {
+        DimensionType {
+            fixed_time: None,
+            has_skylight: true,
+            has_ceiling: false,
+            ultrawarm: false,
+            natural: true,
+            coordinate_scale: 1.0,
+            bed_works: true,
+            respawn_anchor_works: false,
+            min_y: -64,
+            height: 384,
+            logical_height: 384,
+            infiniburn: "#minecraft:infiniburn_overworld".to_string(),
+            effects: "minecraft:overworld".to_string(),
+            ambient_light: 0.0,
+            piglin_safe: false,
+            has_raids: true,
+            monster_spawn_light_level: 0,
+            monster_spawn_block_light_limit: 0,
+        }
+    }
+
+    pub fn overworld_caves() -> Self {
+        DimensionType {
+            has_ceiling: true,
+            ..DimensionType::overworld()
+        }
+    }
+
+    pub fn nether() -> Self {
+        DimensionType {
+            fixed_time: Some(18000),
+            has_skylight: false,
+            has_ceiling: true,
+            ultrawarm: true,
+            natural: false,
+            coordinate_scale: 8.0,
+            bed_works: false,
+            respawn_anchor_works: true,
+            min_y: 0,
+            height: 256,
+            logical_height: 128,
+            infiniburn: "#minecraft:infiniburn_nether".to_string(),
+            effects: "minecraft:the_nether".to_string(),
+            ambient_light: 0.1,
+            piglin_safe: true,
+            has_raids: false,
+            monster_spawn_light_level: 7,
+            monster_spawn_block_light_limit: 15,
+        }
+    }
+
+    pub fn end() -> Self {
+        DimensionType {
+            fixed_time: Some(6000),
+            has_skylight: false,
+            natural: false,
+            bed_works: false,
+            min_y: 0,
+            height: 256,
+            logical_height: 256,
+            infiniburn: "#minecraft:infiniburn_end".to_string(),
+            effects: "minecraft:the_end".to_string(),
+            ..DimensionType::overworld()
+        }
+    }
+
+    // The limits vanilla puts on heights: whole sections, within y -2032
+    // to 2031.
+    pub fn validate(&self) -> Result<(), DimensionError> {
+        let invalid = |reason: &str| Err(DimensionError::InvalidHeight(reason.to_string()));
+        if self.min_y % 16 != 0 || self.height % 16 != 0 {
+            return invalid("min_y and height must be multiples of 16");
+        }
+        if !(16..=4064).contains(&self.height) {
+            return invalid("height must be between 16 and 4064");
+        }
+        if self.min_y < -2032 || self.min_y + self.height > 2032 {
+            return invalid("the dimension must fit between y -2032 and 2031");
+        }
+        if self.logical_height < 0 || self.logical_height > self.height {
+            return invalid("logical_height must be between 0 and height");
+        }
+        Ok(())
+    }
+
+    pub fn to_nbt(&self) -> Compound {
+        match nbt::to_value(self) {
+            Ok(nbt::Value::Compound(compound)) => compound,
+            _ => Compound::new(),
+        }
+    }
+}
+
+// The dimension_type registry sent to clients. An entry's index is the id
+// the Login and Respawn packets refer to it by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionTypes {
+    entries: Vec<(DimensionId, DimensionType)>,
+}
+
+impl Default for DimensionTypes {
+    fn default() -> Self {
+        DimensionTypes::vanilla()
+    }
+}
+
+impl DimensionTypes {
+    pub fn vanilla() -> Self {
+        let entries = [
+            ("overworld", DimensionType::overworld()),
+            ("overworld_caves", DimensionType::overworld_caves()),
+            ("the_end", DimensionType::end()),
+            ("the_nether", DimensionType::nether()),
+        ];
+        DimensionTypes {
+            entries: entries
+                .into_iter()
+                .map(|(id, kind)| (DimensionId(format!("minecraft:{}", id)), kind))
+                .collect(),
+        }
+    }
+
+    // Adds a type, or replaces the one with the same id in place so ids
+    // clients already know stay the same.
+    pub fn register(&mut self, id: DimensionId, kind: DimensionType) -> Result<(), DimensionError> {
+        kind.validate()?;
+        match self.entries.iter_mut().find(|(entry, _)| *entry == id) {
+            Some((_, entry)) => *entry = kind,
+            None => self.entries.push((id, kind)),
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, id: &DimensionId) -> Option<&DimensionType> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry == id)
+            .map(|(_, kind)| kind)
+    }
+
+    pub fn network_id(&self, id: &DimensionId) -> Option<i32> {
+        self.entries
+            .iter()
+            .position(|(entry, _)| entry == id)
+            .map(|index| index as i32)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&DimensionId, &DimensionType)> {
+        self.entries.iter().map(|(id, kind)| (id, kind))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
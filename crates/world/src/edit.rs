@@ -0,0 +1,377 @@
+use ahash::AHashMap;
+
+use crate::{
+    Dimension,
+    block::BlockState,
+    block_entity::BlockEntity,
+    chunk::{Chunk, ChunkSection},
+    loader::LoaderError,
+    palette::{PalettedContainer, Strategy},
+    pos::BlockPos,
+    section_index,
+};
+
+// A box of blocks, both corners included.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Cuboid {
+    pub min: BlockPos,
+    pub max: BlockPos,
+}
+
+// Blocks copied out of a dimension, kept as the containers of the sections
+// they came from so a paste to the same section grid copies whole sections.
+#[derive(Debug, Clone)]
+pub struct Clipboard {
+    region: Cuboid,
+    sections: AHashMap<(i32, i32, i32), PalettedContainer>,
+    block_entities: Vec<(BlockPos, BlockEntity)>,
+}
+
+// The blocks and block entities of whole chunks, to put back with
+// `Dimension::restore`, as when an arena is reset between rounds.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    chunks: AHashMap<(i32, i32), SavedChunk>,
+}
+
+#[derive(Debug, Clone)]
+struct SavedChunk {
+    sections: Vec<PalettedContainer>,
+    block_entities: Vec<(BlockPos, BlockEntity)>,
+}
+
+impl Cuboid {
+    // Any two opposite corners.
+    pub fn new(from: BlockPos, to: BlockPos) -> Self {
+        Cuboid {
+            min: BlockPos::new(from.x.min(to.x), from.y.min(to.y), from.z.min(to.z)),
+            max: BlockPos::new(from.x.max(to.x), from.y.max(to.y), from.z.max(to.z)),
+        }
+    }
+
+    pub fn size(self) -> [i32; 3] {
+        [
+            self.max.x - self.min.x + 1,
+            self.max.y - self.min.y + 1,
+            self.max.z - self.min.z + 1,
+        ]
+    }
+
+    pub fn volume(self) -> u64 {
+        self.size().iter().map(|&side| side as u64).product()
+    }
+
+    pub fn contains(self, pos: BlockPos) -> bool {
+        (self.min.x..=self.max.x).contains(&pos.x)
+            && (self.min.y..=self.max.y).contains(&pos.y)
+            && (self.min.z..=self.max.z).contains(&pos.z)
+    }
+
+    // The same box with its minimum corner at `min`.
+    pub fn moved_to(self, min: BlockPos) -> Cuboid {
+        let [x, y, z] = self.size();
+        Cuboid {
+            min,
+            max: BlockPos::new(min.x + x - 1, min.y + y - 1, min.z + z - 1),
+        }
+    }
+
+    pub fn intersection(self, other: Cuboid) -> Option<Cuboid> {
+        let min = BlockPos::new(
+            self.min.x.max(other.min.x),
+            self.min.y.max(other.min.y),
+            self.min.z.max(other.min.z),
+        );
+        let max = BlockPos::new(
+            self.max.x.min(other.max.x),
+            self.max.y.min(other.max.y),
+            self.max.z.min(other.max.z),
+        );
+        (min.x <= max.x && min.y <= max.y && min.z <= max.z).then_some(Cuboid { min, max })
+    }
+
+    pub fn chunks(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min.z >> 4..=self.max.z >> 4).flat_map(move |chunk_z| {
+            (self.min.x >> 4..=self.max.x >> 4).map(move |chunk_x| (chunk_x, chunk_z))
+        })
+    }
+
+    // Layer by layer, in the order blocks are stored.
+    pub fn positions(self) -> impl Iterator<Item = BlockPos> {
+        (self.min.y..=self.max.y).flat_map(move |y| {
+            (self.min.z..=self.max.z)
+                .flat_map(move |z| (self.min.x..=self.max.x).map(move |x| BlockPos::new(x, y, z)))
+        })
+    }
+
+    fn section(chunk_x: i32, section_y: i32, chunk_z: i32) -> Cuboid {
+        let min = BlockPos::new(chunk_x * 16, section_y * 16, chunk_z * 16);
+        Cuboid {
+            min,
+            max: BlockPos::new(min.x + 15, min.y + 15, min.z + 15),
+        }
+    }
+}
+
+impl Clipboard {
+    // Where the blocks were copied from.
+    pub fn region(&self) -> Cuboid {
+        self.region
+    }
+
+    // `pos` is relative to the minimum corner. Air outside the clipboard.
+    pub fn get(&self, pos: BlockPos) -> BlockState {
+        let min = self.region.min;
+        let source = BlockPos::new(min.x + pos.x, min.y + pos.y, min.z + pos.z);
+        if !self.region.contains(source) {
+            return BlockState::AIR;
+        }
+        self.source(source)
+    }
+
+    // Relative to the minimum corner, like `get`.
+    pub fn block_entities(&self) -> impl Iterator<Item = (BlockPos, &BlockEntity)> {
+        let min = self.region.min;
+        self.block_entities.iter().map(move |(pos, entity)| {
+            (
+                BlockPos::new(pos.x - min.x, pos.y - min.y, pos.z - min.z),
+                entity,
+            )
+        })
+    }
+
+    fn source(&self, pos: BlockPos) -> BlockState {
+        let (chunk_x, chunk_z) = pos.chunk();
+        self.sections
+            .get(&(chunk_x, pos.section_y(), chunk_z))
+            .map_or(BlockState::AIR, |blocks| {
+                BlockState(blocks.get(section_index(pos)))
+            })
+    }
+}
+
+impl Snapshot {
+    pub fn chunks(&self) -> impl Iterator<Item = (i32, i32)> + '_ {
+        self.chunks.keys().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+// Region operations work on whole section containers where they can and
+// block by block elsewhere. Blocks go in as given, without shape updates or
+// ticks; players with the chunks are sent only the blocks that changed.
+// Block entities go with their blocks. Light is left for `update_light`.
+impl Dimension {
+    // Returns how many blocks changed.
+    pub fn fill(&self, region: Cuboid, state: BlockState) -> Result<usize, LoaderError> {
+        self.edit_region(region, |area, section| {
+            if area.volume() == 4096 {
+                return section.set_blocks(PalettedContainer::new(Strategy::BLOCKS, state.id()));
+            }
+            area.positions()
+                .filter_map(|pos| set_block(section, pos, state))
+                .collect()
+        })
+    }
+
+    // Loads the chunks as needed. Blocks outside the dimension's height copy
+    // as air.
+    pub fn copy(&self, region: Cuboid) -> Result<Clipboard, LoaderError> {
+        let mut sections = AHashMap::new();
+        let mut block_entities = Vec::new();
+        for (chunk_x, chunk_z) in region.chunks() {
+            self.load_chunk(chunk_x, chunk_z)?;
+            let Some(chunk) = self.chunks.get(&(chunk_x, chunk_z)) else {
+                continue;
+            };
+            block_entities
+                .extend(chunk_block_entities(&chunk).filter(|(pos, _)| region.contains(*pos)));
+            let bottom = chunk.min_y >> 4;
+            for section_y in region.min.y >> 4..=region.max.y >> 4 {
+                if let Some(section) = usize::try_from(section_y - bottom)
+                    .ok()
+                    .and_then(|index| chunk.sections.get(index))
+                {
+                    sections.insert((chunk_x, section_y, chunk_z), section.blocks.clone());
+                }
+            }
+        }
+        Ok(Clipboard {
+            region,
+            sections,
+            block_entities,
+        })
+    }
+
+    // Puts the clipboard back with its minimum corner at `at` and returns how
+    // many blocks changed. Moved by whole sections, as when pasting where it
+    // was copied, full sections are swapped in at once.
+    pub fn paste(&self, clipboard: &Clipboard, at: BlockPos) -> Result<usize, LoaderError> {
+        let source = clipboard.region.min;
+        let delta = BlockPos::new(at.x - source.x, at.y - source.y, at.z - source.z);
+        let aligned = delta.x % 16 == 0 && delta.y % 16 == 0 && delta.z % 16 == 0;
+        let changed = self.edit_region(clipboard.region.moved_to(at), |area, section| {
+            if aligned && area.volume() == 4096 {
+                let from = BlockPos::new(
+                    (area.min.x - delta.x) >> 4,
+                    (area.min.y - delta.y) >> 4,
+                    (area.min.z - delta.z) >> 4,
+                );
+                if let Some(blocks) = clipboard.sections.get(&(from.x, from.y, from.z)) {
+                    return section.set_blocks(blocks.clone());
+                }
+            }
+            area.positions()
+                .filter_map(|pos| {
+                    let from = BlockPos::new(pos.x - delta.x, pos.y - delta.y, pos.z - delta.z);
+                    set_block(section, pos, clipboard.source(from))
+                })
+                .collect()
+        })?;
+        for (pos, entity) in &clipboard.block_entities {
+            let pos = BlockPos::new(pos.x + delta.x, pos.y + delta.y, pos.z + delta.z);
+            self.set_block_entity(pos, entity.clone())?;
+        }
+        Ok(changed)
+    }
+
+    pub fn snapshot(
+        &self,
+        chunks: impl IntoIterator<Item = (i32, i32)>,
+    ) -> Result<Snapshot, LoaderError> {
+        let mut snapshot = Snapshot {
+            chunks: AHashMap::new(),
+        };
+        for (chunk_x, chunk_z) in chunks {
+            self.load_chunk(chunk_x, chunk_z)?;
+            if let Some(chunk) = self.chunks.get(&(chunk_x, chunk_z)) {
+                let saved = SavedChunk {
+                    sections: chunk
+                        .sections
+                        .iter()
+                        .map(|section| section.blocks.clone())
+                        .collect(),
+                    block_entities: chunk_block_entities(&chunk).collect(),
+                };
+                snapshot.chunks.insert((chunk_x, chunk_z), saved);
+            }
+        }
+        Ok(snapshot)
+    }
+
+    // Returns how many blocks changed since the snapshot was taken.
+    pub fn restore(&self, snapshot: &Snapshot) -> Result<usize, LoaderError> {
+        let mut changed = 0;
+        for (&(chunk_x, chunk_z), saved) in &snapshot.chunks {
+            changed += self.edit_chunk(chunk_x, chunk_z, |index, _, section| {
+                saved
+                    .sections
+                    .get(index)
+                    .map_or_else(Vec::new, |blocks| section.set_blocks(blocks.clone()))
+            })?;
+
+            let current = match self.chunks.get(&(chunk_x, chunk_z)) {
+                Some(chunk) => chunk_block_entities(&chunk).collect::<Vec<_>>(),
+                None => continue,
+            };
+            for (pos, _) in &current {
+                if !saved.block_entities.iter().any(|(saved, _)| saved == pos) {
+                    self.remove_block_entity(*pos);
+                }
+            }
+            for (pos, entity) in &saved.block_entities {
+                if !current
+                    .iter()
+                    .any(|current| (&current.0, &current.1) == (pos, entity))
+                {
+                    self.set_block_entity(*pos, entity.clone())?;
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    // Runs `edit` with the part of the region inside each section it touches.
+    fn edit_region(
+        &self,
+        region: Cuboid,
+        mut edit: impl FnMut(Cuboid, &mut ChunkSection) -> Vec<u16>,
+    ) -> Result<usize, LoaderError> {
+        let mut changed = 0;
+        for (chunk_x, chunk_z) in region.chunks() {
+            changed += self.edit_chunk(chunk_x, chunk_z, |_, section_y, section| {
+                match region.intersection(Cuboid::section(chunk_x, section_y, chunk_z)) {
+                    Some(area) => edit(area, section),
+                    None => Vec::new(),
+                }
+            })?;
+        }
+        Ok(changed)
+    }
+
+    // Runs `edit` on every section of the chunk, each returning the indices
+    // of the blocks it changed, then refreshes the chunk and records the
+    // changes for clients.
+    fn edit_chunk(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+        mut edit: impl FnMut(usize, i32, &mut ChunkSection) -> Vec<u16>,
+    ) -> Result<usize, LoaderError> {
+        self.load_chunk(chunk_x, chunk_z)?;
+        let changed = {
+            let Some(mut chunk) = self.chunks.get_mut(&(chunk_x, chunk_z)) else {
+                return Ok(0);
+            };
+            let bottom = chunk.min_y >> 4;
+            let changed = chunk
+                .sections
+                .iter_mut()
+                .enumerate()
+                .map(|(index, section)| {
+                    let section_y = bottom + index as i32;
+                    (section_y, edit(index, section_y, section))
+                })
+                .filter(|(_, indices)| !indices.is_empty())
+                .collect::<Vec<_>>();
+            if !changed.is_empty() {
+                chunk.mark_dirty();
+                chunk.recompute_heightmaps();
+                chunk.retain_block_entities();
+            }
+            changed
+        };
+
+        let mut changes = self.changes.lock();
+        let mut count = 0;
+        for (section_y, indices) in changed {
+            count += indices.len();
+            let section = changes.entry((chunk_x, section_y, chunk_z)).or_default();
+            for index in indices {
+                section.blocks.insert(index as usize);
+            }
+        }
+        Ok(count)
+    }
+}
+
+fn chunk_block_entities(chunk: &Chunk) -> impl Iterator<Item = (BlockPos, BlockEntity)> + '_ {
+    chunk.block_entities().map(|((x, y, z), entity)| {
+        let pos = BlockPos::new(chunk.x * 16 + x as i32, y, chunk.z * 16 + z as i32);
+        (pos, entity.clone())
+    })
+}
+
+// The block's index when the state changed.
+fn set_block(section: &mut ChunkSection, pos: BlockPos, state: BlockState) -> Option<u16> {
+    let (x, z) = pos.local();
+    let old = section.set_block(x, pos.y.rem_euclid(16) as usize, z, state);
+    (old != state).then_some(section_index(pos) as u16)
+}
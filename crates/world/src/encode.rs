@@ -0,0 +1,141 @@
+use bytes::{BufMut, Bytes, BytesMut};
+use nbt::{Compound, Value};
+use protocol::{
+    serial::{PacketError, PacketWrite},
+    types::var_int::VarInt,
+};
+
+use crate::{
+    block_entity::{self, BlockEntity},
+    chunk::Chunk,
+    dimension::DimensionType,
+    heightmap::HeightmapKind,
+    light::NibbleArray,
+};
+
+impl Chunk {
+    // The Chunk Data and Update Light payload after the chunk coordinates:
+    // heightmaps, section data, block entities and light. Encoded once and
+    // reused until the chunk is modified.
+    pub fn encoded(&self) -> Result<Bytes, PacketError> {
+        if let Some(bytes) = self.encoded.get() {
+            return Ok(bytes.clone());
+        }
+        let bytes = encode(self)?;
+        Ok(self.encoded.get_or_init(|| bytes).clone())
+    }
+}
+
+impl BlockEntity {
+    // The data clients get in Block Entity Data, as network NBT.
+    pub fn encoded(&self) -> Result<Bytes, PacketError> {
+        let mut buffer = BytesMut::new();
+        write_nbt(&mut buffer, self.client_data())?;
+        Ok(buffer.freeze())
+    }
+}
+
+impl DimensionType {
+    // The entry's data in Registry Data, as network NBT.
+    pub fn encoded(&self) -> Result<Bytes, PacketError> {
+        let mut buffer = BytesMut::new();
+        write_nbt(&mut buffer, self.to_nbt())?;
+        Ok(buffer.freeze())
+    }
+}
+
+fn encode(chunk: &Chunk) -> Result<Bytes, PacketError> {
+    let mut buffer = BytesMut::new();
+
+    VarInt(HeightmapKind::CLIENT.len() as i32).write(&mut buffer)?;
+    for kind in HeightmapKind::CLIENT {
+        VarInt(kind.network_id()).write(&mut buffer)?;
+        write_longs(
+            &mut buffer,
+            &chunk.heightmaps.get(kind).pack(chunk.height()),
+        )?;
+    }
+
+    let mut sections = BytesMut::new();
+    for section in &chunk.sections {
+        section.write(&mut sections)?;
+    }
+    VarInt(sections.len() as i32).write(&mut buffer)?;
+    buffer.put_slice(&sections);
+
+    // Block entities whose type clients don't know are left out.
+    let block_entities = chunk
+        .block_entities()
+        .filter_map(|(pos, entity)| Some((pos, block_entity::network_id(&entity.id)?, entity)))
+        .collect::<Vec<_>>();
+    VarInt(block_entities.len() as i32).write(&mut buffer)?;
+    for ((x, y, z), kind, entity) in block_entities {
+        buffer.put_u8(((x as u8) << 4) | z as u8);
+        buffer.put_i16(y as i16);
+        VarInt(kind).write(&mut buffer)?;
+        write_nbt(&mut buffer, entity.client_data())?;
+    }
+
+    write_light(chunk, &mut buffer)?;
+    Ok(buffer.freeze())
+}
+
+// Light covers one extra section below and above the chunk. The one above is
+// open sky and the one below is dark; sections without any light are only
+// flagged in the empty masks.
+fn write_light(chunk: &Chunk, buffer: &mut BytesMut) -> Result<(), PacketError> {
+    let count = chunk.sections.len() + 2;
+    let above = NibbleArray::Uniform(15);
+    let below = NibbleArray::default();
+
+    let sky = std::iter::once(&below)
+        .chain(chunk.sections.iter().map(|section| &section.sky_light))
+        .chain(std::iter::once(&above))
+        .collect::<Vec<_>>();
+    let block = std::iter::once(&below)
+        .chain(chunk.sections.iter().map(|section| &section.block_light))
+        .chain(std::iter::once(&below))
+        .collect::<Vec<_>>();
+
+    let mask = |arrays: &[&NibbleArray], empty: bool| {
+        let mut longs = vec![0i64; count.div_ceil(64)];
+        for (bit, array) in arrays.iter().enumerate() {
+            if array.is_empty() == empty {
+                longs[bit / 64] |= 1 << (bit % 64);
+            }
+        }
+        longs
+    };
+
+    write_longs(buffer, &mask(&sky, false))?;
+    write_longs(buffer, &mask(&block, false))?;
+    write_longs(buffer, &mask(&sky, true))?;
+    write_longs(buffer, &mask(&block, true))?;
+
+    for arrays in [&sky, &block] {
+        let arrays = arrays.iter().filter(|array| !array.is_empty());
+        VarInt(arrays.clone().count() as i32).write(buffer)?;
+        for array in arrays {
+            array.write(buffer)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_longs(buffer: &mut BytesMut, longs: &[i64]) -> Result<(), PacketError> {
+    VarInt(longs.len() as i32).write(buffer)?;
+    for &long in longs {
+        buffer.put_i64(long);
+    }
+    Ok(())
+}
+
+// Network NBT: a nameless root compound.
+fn write_nbt(buffer: &mut BytesMut, data: Compound) -> Result<(), PacketError> {
+    let mut bytes = Vec::new();
+    Value::Compound(data)
+        .write_network(&mut bytes)
+        .map_err(|error| PacketError::Io(std::io::Error::other(error)))?;
+    buffer.put_slice(&bytes);
+    Ok(())
+}
@@ -1,36 +1,274 @@
-use crate::{chunk::Chunk, generator::WorldGenerator};
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    biome,
+    block::{Block, BlockState, BlockStateError},
+    chunk::Chunk,
+    generator::WorldGenerator,
+    palette::{PalettedContainer, Strategy},
+};
+
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum FlatError {
+    #[error("Invalid layer block: {0}")]
+    Block(#[from] BlockStateError),
+    #[error("Unknown biome {0}")]
+    UnknownBiome(String),
+    #[error("Malformed layer {0}")]
+    MalformedLayer(String),
+    #[error("Layers are {layers} blocks tall but the dimension is only {height}")]
+    TooTall { layers: u32, height: i32 },
+}
+
+// One vanilla-style layer: a block repeated `height` times upwards.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FlatLayer {
+    pub block: String,
+    #[serde(default = "one")]
+    pub height: u32,
+}
+
+fn one() -> u32 {
+    1
+}
+
+impl FlatLayer {
+    pub fn new(block: impl Into<String>, height: u32) -> Self {
+        FlatLayer {
+            block: block.into(),
+            height,
+        }
+    }
+}
+
+// Layers listed bottom up from the dimension's `min_y`, and the biome of the
+// whole world. Deserializes from either a preset string or a map like
+// `{ layers: [{ block: "minecraft:bedrock", height: 1 }], biome: "plains" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "SettingsRepr")]
+pub struct FlatSettings {
+    pub layers: Vec<FlatLayer>,
+    pub biome: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SettingsRepr {
+    Preset(String),
+    Fields {
+        #[serde(default)]
+        layers: Vec<FlatLayer>,
+        #[serde(default = "default_biome")]
+        biome: String,
+    },
+}
+
+fn default_biome() -> String {
+    "minecraft:plains".to_string()
+}
+
+impl TryFrom<SettingsRepr> for FlatSettings {
+    type Error = FlatError;
+
+    fn try_from(repr: SettingsRepr) -> Result<Self, Self::Error> {
+        match repr {
+            SettingsRepr::Preset(preset) => preset.parse(),
+            SettingsRepr::Fields { layers, biome } => Ok(FlatSettings { layers, biome }),
+        }
+    }
+}
+
+impl Default for FlatSettings {
+    fn default() -> Self {
+        FlatSettings::classic()
+    }
+}
+
+impl FlatSettings {
+    pub fn classic() -> Self {
+        FlatSettings {
+            layers: vec![
+                FlatLayer::new("minecraft:bedrock", 1),
+                FlatLayer::new("minecraft:dirt", 2),
+                FlatLayer::new("minecraft:grass_block", 1),
+            ],
+            biome: default_biome(),
+        }
+    }
+
+    // Nothing but air, for worlds built entirely from schematics.
+    pub fn void() -> Self {
+        FlatSettings {
+            layers: Vec::new(),
+            biome: "minecraft:the_void".to_string(),
+        }
+    }
+
+    // Vanilla's preset ids, with or without the namespace.
+    pub fn preset(name: &str) -> Option<Self> {
+        match name.strip_prefix("minecraft:").unwrap_or(name) {
+            "classic_flat" => Some(FlatSettings::classic()),
+            "the_void" => Some(FlatSettings::void()),
+            _ => None,
+        }
+    }
+
+    pub fn total_height(&self) -> u32 {
+        self.layers
+            .iter()
+            .fold(0u32, |total, layer| total.saturating_add(layer.height))
+    }
+}
+
+// The superflat preset format: comma separated layers bottom up, each an
+// optional `count*` and a block, then `;` and the biome. Anything after a
+// second `;` configured structures in older versions and is ignored.
+impl FromStr for FlatSettings {
+    type Err = FlatError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let mut parts = input.trim().split(';');
+        let layers = parts.next().unwrap_or_default();
+        let biome = parts
+            .next()
+            .map(str::trim)
+            .filter(|biome| !biome.is_empty())
+            .map_or_else(default_biome, str::to_string);
+
+        let layers = layers
+            .split(',')
+            .map(str::trim)
+            .filter(|layer| !layer.is_empty())
+            .map(|layer| match layer.split_once('*') {
+                Some((count, block)) => count
+                    .trim()
+                    .parse()
+                    .map(|height| FlatLayer::new(block.trim(), height))
+                    .map_err(|_| FlatError::MalformedLayer(layer.to_string())),
+                None => Ok(FlatLayer::new(layer, 1)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(FlatSettings { layers, biome })
+    }
+}
+
+impl fmt::Display for FlatSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, layer) in self.layers.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            if layer.height != 1 {
+                write!(f, "{}*", layer.height)?;
+            }
+            f.write_str(&layer.block)?;
+        }
+        write!(f, ";{}", self.biome)
+    }
+}
 
 pub struct FlatGenerator {
-    layers: Vec<Layer>,
+    min_y: i32,
+    height: i32,
+    // The block at each y from `min_y` up, until the layers run out.
+    column: Vec<BlockState>,
+    biome: u8,
 }
 
-struct Layer {
-    y: i32,
-    block_id: u16,
+impl Default for FlatGenerator {
+    fn default() -> Self {
+        FlatGenerator::new()
+    }
 }
 
 impl FlatGenerator {
+    // Bedrock at y 0 under three dirt and a grass block, the layout the
+    // server has always used.
     pub fn new() -> Self {
+        let mut column = vec![BlockState::AIR; 64];
+        column.extend([
+            Block::BEDROCK.default_state(),
+            Block::DIRT.default_state(),
+            Block::DIRT.default_state(),
+            Block::DIRT.default_state(),
+            Block::GRASS_BLOCK.default_state(),
+        ]);
         FlatGenerator {
-            layers: vec![
-                Layer { y: 0, block_id: 33 },
-                Layer { y: 1, block_id: 10 },
-                Layer { y: 2, block_id: 10 },
-                Layer { y: 3, block_id: 10 },
-                Layer { y: 4, block_id: 9 },
-            ],
+            min_y: -64,
+            height: 384,
+            column,
+            biome: biome::PLAINS,
+        }
+    }
+
+    // Layers that don't fit in `height` blocks above `min_y` are rejected
+    // rather than cut off.
+    pub fn with_settings(
+        settings: &FlatSettings,
+        min_y: i32,
+        height: i32,
+    ) -> Result<Self, FlatError> {
+        let total = settings.total_height();
+        if total > height.max(0) as u32 {
+            return Err(FlatError::TooTall {
+                layers: total,
+                height,
+            });
+        }
+
+        let biome = biome::biome_id(&settings.biome)
+            .ok_or_else(|| FlatError::UnknownBiome(settings.biome.clone()))?;
+        let mut column = Vec::with_capacity(total as usize);
+        for layer in &settings.layers {
+            let state = layer.block.parse::<BlockState>()?;
+            column.extend(std::iter::repeat_n(state, layer.height as usize));
         }
+
+        Ok(FlatGenerator {
+            min_y,
+            height,
+            column,
+            biome,
+        })
+    }
+
+    // The y just above the top layer, where players spawn.
+    pub fn surface_y(&self) -> i32 {
+        let top = self.column.iter().rposition(|state| !state.is_air());
+        self.min_y + top.map_or(0, |top| top as i32 + 1)
     }
 }
 
 impl WorldGenerator for FlatGenerator {
     fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
-        let mut chunk = Chunk::new(chunk_x, chunk_z);
+        let mut chunk = Chunk::with_height(chunk_x, chunk_z, self.min_y, self.height);
 
-        for layer in &self.layers {
-            chunk.fill_layer(layer.y, layer.block_id);
+        for (index, section) in chunk.sections.iter_mut().enumerate() {
+            section.biomes = PalettedContainer::new(Strategy::BIOMES, self.biome as u16);
+            let layers = self.column.iter().skip(index * 16).take(16);
+            if layers.len() == 0 {
+                continue;
+            }
+            let mut values = vec![0u16; 4096];
+            for (y, state) in layers.enumerate() {
+                values[y * 256..(y + 1) * 256].fill(state.id());
+            }
+            section.blocks = PalettedContainer::from_values(Strategy::BLOCKS, &values);
+            section.recount();
         }
 
+        chunk.recompute_heightmaps();
         chunk
     }
+
+    fn min_y(&self) -> i32 {
+        self.min_y
+    }
+
+    fn height(&self) -> i32 {
+        self.height
+    }
 }
@@ -1,8 +1,14 @@
 use crate::chunk::Chunk;
 
 pub mod flat;
+pub mod noise;
 pub mod pregen;
+pub mod terrain;
 
+// Generated chunks span `height` blocks from `min_y`, which has to match the
+// dimension type they are used with.
 pub trait WorldGenerator: Send + Sync {
     fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk;
+    fn min_y(&self) -> i32;
+    fn height(&self) -> i32;
 }
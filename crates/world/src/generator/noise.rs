@@ -0,0 +1,197 @@
+// Seeded randomness and gradient noise for world generation. Everything here
+// is a pure function of the seed, so generators can sample from any thread.
+
+// xoroshiro128++, seeded through splitmix64 like vanilla's positional random.
+#[derive(Debug, Clone)]
+pub struct Random {
+    lo: u64,
+    hi: u64,
+}
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        let mut state = seed;
+        let lo = splitmix(&mut state);
+        let hi = splitmix(&mut state);
+        // An all zero state would only ever produce zeros.
+        if lo | hi == 0 {
+            return Random {
+                lo: 0x9e3779b97f4a7c15,
+                hi: 0x6a09e667f3bcc909,
+            };
+        }
+        Random { lo, hi }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let (lo, hi) = (self.lo, self.hi);
+        let result = lo.wrapping_add(hi).rotate_left(17).wrapping_add(lo);
+        let hi = hi ^ lo;
+        self.lo = lo.rotate_left(49) ^ hi ^ (hi << 21);
+        self.hi = hi.rotate_left(28);
+        result
+    }
+
+    // Uniform in `0.0..1.0`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    // Uniform in `0..bound`.
+    pub fn next_below(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    }
+
+    // An independent generator, for handing each noise its own stream.
+    pub fn fork(&mut self) -> Random {
+        Random {
+            lo: self.next_u64(),
+            hi: self.next_u64() | 1,
+        }
+    }
+}
+
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+// A well mixed hash of a seed and a block position, for per-block decisions
+// that must not depend on generation order.
+pub fn position_hash(seed: u64, x: i32, y: i32, z: i32) -> u64 {
+    let mut state = seed
+        ^ (x as u32 as u64).wrapping_mul(0x2f0f3c2b6b1f1a2d)
+        ^ (y as u32 as u64).wrapping_mul(0x51afd7ed558ccd)
+        ^ (z as u32 as u64).wrapping_mul(0x7fb5d329728ea185);
+    splitmix(&mut state)
+}
+
+// Ken Perlin's improved noise with a shuffled permutation and a random
+// origin. Samples are roughly within `-1.0..=1.0`.
+#[derive(Debug, Clone)]
+pub struct PerlinNoise {
+    permutation: [u8; 256],
+    origin: [f64; 3],
+}
+
+impl PerlinNoise {
+    pub fn new(random: &mut Random) -> Self {
+        let origin = [
+            random.next_f64() * 256.0,
+            random.next_f64() * 256.0,
+            random.next_f64() * 256.0,
+        ];
+        let mut permutation = [0u8; 256];
+        for (i, value) in permutation.iter_mut().enumerate() {
+            *value = i as u8;
+        }
+        for i in (1..256).rev() {
+            let j = random.next_below(i as u32 + 1) as usize;
+            permutation.swap(i, j);
+        }
+        PerlinNoise {
+            permutation,
+            origin,
+        }
+    }
+
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let (x, y, z) = (x + self.origin[0], y + self.origin[1], z + self.origin[2]);
+        let (fx, fy, fz) = (x.floor(), y.floor(), z.floor());
+        let (cx, cy, cz) = (fx as i32, fy as i32, fz as i32);
+        let (x, y, z) = (x - fx, y - fy, z - fz);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let hash = |i: i32| self.permutation[(i & 255) as usize] as i32;
+        let a = hash(cx) + cy;
+        let (aa, ab) = (hash(a) + cz, hash(a + 1) + cz);
+        let b = hash(cx + 1) + cy;
+        let (ba, bb) = (hash(b) + cz, hash(b + 1) + cz);
+
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(hash(aa), x, y, z), grad(hash(ba), x - 1.0, y, z)),
+                lerp(
+                    u,
+                    grad(hash(ab), x, y - 1.0, z),
+                    grad(hash(bb), x - 1.0, y - 1.0, z),
+                ),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(hash(aa + 1), x, y, z - 1.0),
+                    grad(hash(ba + 1), x - 1.0, y, z - 1.0),
+                ),
+                lerp(
+                    u,
+                    grad(hash(ab + 1), x, y - 1.0, z - 1.0),
+                    grad(hash(bb + 1), x - 1.0, y - 1.0, z - 1.0),
+                ),
+            ),
+        )
+    }
+}
+
+// Octaves of Perlin noise, each at twice the frequency and half the amplitude
+// of the previous one, normalized back to roughly `-1.0..=1.0`.
+#[derive(Debug, Clone)]
+pub struct OctaveNoise {
+    octaves: Vec<PerlinNoise>,
+    frequency: f64,
+}
+
+impl OctaveNoise {
+    // `scale` is the size in blocks of the largest features.
+    pub fn new(random: &mut Random, octaves: usize, scale: f64) -> Self {
+        OctaveNoise {
+            octaves: (0..octaves).map(|_| PerlinNoise::new(random)).collect(),
+            frequency: 1.0 / scale,
+        }
+    }
+
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> f64 {
+        let mut total = 0.0;
+        let mut weight = 0.0;
+        let mut frequency = self.frequency;
+        let mut amplitude = 1.0;
+        for octave in &self.octaves {
+            total += octave.sample(x * frequency, y * frequency, z * frequency) * amplitude;
+            weight += amplitude;
+            frequency *= 2.0;
+            amplitude *= 0.5;
+        }
+        total / weight
+    }
+
+    pub fn sample_2d(&self, x: f64, z: f64) -> f64 {
+        self.sample(x, 0.0, z)
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+fn grad(hash: i32, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -1,10 +1,31 @@
+use std::{
+    collections::BTreeSet,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use events::EventBus;
+use log::{info, warn};
+use macros::event;
+use parking_lot::{Condvar, Mutex};
+
+use crate::{Dimension, loader::LoaderError};
+
+// Chunk positions spiraling outwards from the center one ring at a time,
+// each position exactly once. Ring `r` holds the `8r` chunks at Chebyshev
+// distance `r` and starts at index `(2r - 1)²`.
 pub struct Pregenerator {
     pub center_x: i32,
     pub center_z: i32,
     pub radius: i32,
 
-    pub current_r: i32,
-    pub current_step: i32,
+    pub index: u64,
 
     pub is_finished: bool,
 }
@@ -15,47 +36,548 @@ impl Pregenerator {
             center_x: 0,
             center_z: 0,
             radius,
-            current_r: 0,
-            current_step: 0,
+            index: 0,
             is_finished: false,
         }
     }
 
+    pub fn with_center(mut self, center_x: i32, center_z: i32) -> Self {
+        self.center_x = center_x;
+        self.center_z = center_z;
+        self
+    }
+
+    pub fn total(&self) -> u64 {
+        let width = self.radius.max(0) as u64 * 2 + 1;
+        width * width
+    }
+
+    pub fn chunk_at(&self, index: u64) -> (i32, i32) {
+        let (x, z) = spiral_offset(index);
+        (self.center_x + x, self.center_z + z)
+    }
+
     pub fn next_chunk(&mut self) -> Option<(i32, i32)> {
-        if self.current_r > self.radius {
+        if self.index >= self.total() {
             self.is_finished = true;
             return None;
         }
+        let position = self.chunk_at(self.index);
+        self.index += 1;
+        Some(position)
+    }
+}
+
+impl Iterator for Pregenerator {
+    type Item = (i32, i32);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_chunk()
+    }
+}
+
+fn spiral_offset(index: u64) -> (i32, i32) {
+    if index == 0 {
+        return (0, 0);
+    }
+    // ⌊√index⌋ is `2r - 1` or `2r` inside ring `r`.
+    let ring = index.isqrt().div_ceil(2);
+    let position = index - (2 * ring - 1) * (2 * ring - 1);
+    let (r, side, offset) = (
+        ring as i32,
+        position / (2 * ring),
+        (position % (2 * ring)) as i32,
+    );
+
+    // Each side runs counterclockwise and ends on a corner.
+    match side {
+        0 => (r, -r + 1 + offset),
+        1 => (r - 1 - offset, r),
+        2 => (-r, r - 1 - offset),
+        _ => (-r + 1 + offset, -r),
+    }
+}
+
+#[event]
+pub struct PregenProgressEvent {
+    pub progress: PregenProgress,
+}
+
+impl PregenProgressEvent {
+    pub fn new(progress: PregenProgress) -> Self {
+        Self {
+            progress,
+            is_canceled: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PregenProgress {
+    // Chunks handled so far, including ones that already existed.
+    pub done: u64,
+    pub total: u64,
+    // Chunks this run generated, as opposed to found on disk or in memory.
+    pub generated: u64,
+    pub chunks_per_second: f64,
+    pub eta: Option<Duration>,
+    pub finished: bool,
+}
+
+impl PregenProgress {
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 {
+            100.0
+        } else {
+            self.done as f64 * 100.0 / self.total as f64
+        }
+    }
+}
+
+// Generates every chunk within `radius` of the center on a pool of worker
+// threads, nearest first. Dimensions with storage get each chunk saved as
+// soon as it is generated instead of kept in memory, and the job can be
+// resumed from its checkpoint file after a restart.
+pub struct PregenJob {
+    spiral: Pregenerator,
+    threads: usize,
+    checkpoint: Option<PathBuf>,
+    events: Option<Arc<EventBus>>,
+    report_interval: Duration,
+}
+
+impl PregenJob {
+    pub fn new(radius: i32) -> Self {
+        PregenJob {
+            spiral: Pregenerator::new(radius),
+            threads: thread::available_parallelism().map_or(2, |n| n.get()),
+            checkpoint: None,
+            events: None,
+            report_interval: Duration::from_secs(5),
+        }
+    }
+
+    pub fn center(mut self, chunk_x: i32, chunk_z: i32) -> Self {
+        self.spiral = self.spiral.with_center(chunk_x, chunk_z);
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
 
-        if self.current_r == 0 {
-            self.current_r += 1;
-            return Some((self.center_x, self.center_z));
+    pub fn checkpoint<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.checkpoint = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    pub fn events(mut self, events: Arc<EventBus>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    pub fn report_interval(mut self, interval: Duration) -> Self {
+        self.report_interval = interval;
+        self
+    }
+
+    pub fn start(self, dimension: Arc<Dimension>) -> PregenHandle {
+        let start = self.read_checkpoint().min(self.spiral.total());
+        if start > 0 {
+            info!(
+                "resuming pregeneration at {} of {} chunks",
+                start,
+                self.spiral.total()
+            );
         }
 
-        let perimeter = 8 * self.current_r;
+        let shared = Arc::new(Shared {
+            spiral: self.spiral,
+            dimension,
+            next: AtomicU64::new(start),
+            generated: AtomicU64::new(0),
+            completed: Mutex::new(Completed {
+                watermark: start,
+                pending: BTreeSet::new(),
+            }),
+            state: Mutex::new(RunState::Running),
+            wake: Condvar::new(),
+            workers: AtomicUsize::new(self.threads),
+            error: Mutex::new(None),
+            progress: Mutex::new(None),
+        });
+
+        let workers = (0..self.threads)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.work())
+            })
+            .collect();
+
+        let reporter = {
+            let shared = shared.clone();
+            let reporter = Reporter {
+                checkpoint: self.checkpoint,
+                events: self.events,
+                interval: self.report_interval,
+            };
+            thread::spawn(move || reporter.run(&shared, start))
+        };
 
-        if self.current_step >= perimeter {
-            self.current_step = 0;
-            self.current_r += 1;
-            if self.current_r > self.radius {
-                self.is_finished = true;
-                return None;
+        PregenHandle {
+            shared,
+            workers,
+            reporter,
+        }
+    }
+
+    // The number of chunks a previous run of the same job finished, or zero
+    // when there is no matching checkpoint.
+    fn read_checkpoint(&self) -> u64 {
+        let Some(path) = &self.checkpoint else {
+            return 0;
+        };
+        let Ok(text) = fs::read_to_string(path) else {
+            return 0;
+        };
+        let checkpoint = Checkpoint::parse(&text);
+        match checkpoint {
+            Some(checkpoint)
+                if checkpoint.center == (self.spiral.center_x, self.spiral.center_z)
+                    && checkpoint.radius == self.spiral.radius =>
+            {
+                checkpoint.done
+            }
+            _ => {
+                warn!(
+                    "ignoring pregeneration checkpoint {} made for another job",
+                    path.display()
+                );
+                0
             }
         }
+    }
+}
 
-        let width = self.radius * 2 + 1;
-        let area = width * width;
+pub struct PregenHandle {
+    shared: Arc<Shared>,
+    workers: Vec<JoinHandle<()>>,
+    reporter: JoinHandle<()>,
+}
 
-        if self.current_step >= area {
-            self.is_finished = true;
-            return None;
+impl PregenHandle {
+    pub fn pause(&self) {
+        self.shared.set_state(RunState::Paused);
+    }
+
+    pub fn resume(&self) {
+        self.shared.set_state(RunState::Running);
+    }
+
+    // Stops after the chunks in progress. The checkpoint is kept so the job
+    // picks up from there when started again.
+    pub fn cancel(&self) {
+        self.shared.set_state(RunState::Cancelled);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        *self.shared.state.lock() == RunState::Paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.workers.iter().all(JoinHandle::is_finished) && self.reporter.is_finished()
+    }
+
+    // As of the last report, or the start of the job before the first one.
+    pub fn progress(&self) -> PregenProgress {
+        (*self.shared.progress.lock()).unwrap_or_else(|| self.shared.progress(0.0, false))
+    }
+
+    // Blocks until every chunk is done or the job was cancelled.
+    pub fn wait(self) -> Result<PregenProgress, LoaderError> {
+        for worker in self.workers {
+            let _ = worker.join();
+        }
+        let _ = self.reporter.join();
+        if let Some(error) = self.shared.error.lock().take() {
+            return Err(error);
+        }
+        Ok(self
+            .shared
+            .progress
+            .lock()
+            .unwrap_or_else(|| self.shared.progress(0.0, true)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RunState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+// Workers finish chunks out of order; everything below the watermark is
+// done, which is what the checkpoint records.
+struct Completed {
+    watermark: u64,
+    pending: BTreeSet<u64>,
+}
+
+struct Shared {
+    spiral: Pregenerator,
+    dimension: Arc<Dimension>,
+    next: AtomicU64,
+    generated: AtomicU64,
+    completed: Mutex<Completed>,
+    state: Mutex<RunState>,
+    wake: Condvar,
+    workers: AtomicUsize,
+    error: Mutex<Option<LoaderError>>,
+    progress: Mutex<Option<PregenProgress>>,
+}
+
+impl Shared {
+    fn set_state(&self, state: RunState) {
+        let mut current = self.state.lock();
+        // A cancelled job stays cancelled.
+        if *current != RunState::Cancelled {
+            *current = state;
+        }
+        self.wake.notify_all();
+    }
+
+    // Blocks while paused. False once the job should stop.
+    fn keep_going(&self) -> bool {
+        let mut state = self.state.lock();
+        while *state == RunState::Paused {
+            self.wake.wait(&mut state);
+        }
+        *state == RunState::Running
+    }
+
+    fn work(&self) {
+        let total = self.spiral.total();
+        while self.keep_going() {
+            let index = self.next.fetch_add(1, Ordering::Relaxed);
+            if index >= total {
+                break;
+            }
+            let (chunk_x, chunk_z) = self.spiral.chunk_at(index);
+            match self.generate(chunk_x, chunk_z) {
+                Ok(generated) => {
+                    if generated {
+                        self.generated.fetch_add(1, Ordering::Relaxed);
+                    }
+                    self.complete(index);
+                }
+                Err(error) => {
+                    warn!(
+                        "pregeneration failed at chunk {}, {}: {}",
+                        chunk_x, chunk_z, error
+                    );
+                    self.error.lock().get_or_insert(error);
+                    self.set_state(RunState::Cancelled);
+                }
+            }
+        }
+
+        if self.workers.fetch_sub(1, Ordering::AcqRel) == 1 {
+            let _state = self.state.lock();
+            self.wake.notify_all();
+        }
+    }
+
+    // Chunks already in memory or on disk are left alone.
+    fn generate(&self, chunk_x: i32, chunk_z: i32) -> Result<bool, LoaderError> {
+        let dimension = &self.dimension;
+        if dimension.chunks.contains_key(&(chunk_x, chunk_z)) {
+            return Ok(false);
+        }
+        match &dimension.storage {
+            Some(storage) => {
+                if storage.has_chunk(chunk_x, chunk_z)? {
+                    return Ok(false);
+                }
+                let chunk = dimension.generator.generate_chunk(chunk_x, chunk_z);
+                storage.save_chunk(&chunk)?;
+            }
+            None => dimension.load_chunk(chunk_x, chunk_z)?,
+        }
+        Ok(true)
+    }
+
+    fn complete(&self, index: u64) {
+        let mut completed = self.completed.lock();
+        completed.pending.insert(index);
+        while completed.pending.first() == Some(&completed.watermark) {
+            completed.pending.pop_first();
+            completed.watermark += 1;
+        }
+    }
+
+    fn done(&self) -> u64 {
+        let completed = self.completed.lock();
+        completed.watermark + completed.pending.len() as u64
+    }
+
+    fn progress(&self, chunks_per_second: f64, finished: bool) -> PregenProgress {
+        let (done, total) = (self.done(), self.spiral.total());
+        let eta = (chunks_per_second > 0.0)
+            .then(|| Duration::from_secs_f64((total - done) as f64 / chunks_per_second));
+        PregenProgress {
+            done,
+            total,
+            generated: self.generated.load(Ordering::Relaxed),
+            chunks_per_second,
+            eta,
+            finished,
+        }
+    }
+}
+
+struct Reporter {
+    checkpoint: Option<PathBuf>,
+    events: Option<Arc<EventBus>>,
+    interval: Duration,
+}
+
+impl Reporter {
+    fn run(&self, shared: &Shared, start: u64) {
+        let started = Instant::now();
+        let mut active = Duration::ZERO;
+        let mut last = Instant::now();
+
+        loop {
+            let (running, paused) = {
+                let mut state = shared.state.lock();
+                if shared.workers.load(Ordering::Acquire) > 0 {
+                    shared.wake.wait_for(&mut state, self.interval);
+                }
+                (
+                    shared.workers.load(Ordering::Acquire) > 0,
+                    *state == RunState::Paused,
+                )
+            };
+            // Time spent paused doesn't count towards the rate.
+            if !paused {
+                active += last.elapsed();
+            }
+            last = Instant::now();
+
+            let finished = !running && shared.done() == shared.spiral.total();
+            let rate = match active.as_secs_f64() {
+                seconds if seconds > 0.0 => (shared.done() - start) as f64 / seconds,
+                _ => 0.0,
+            };
+            let progress = shared.progress(rate, finished);
+            *shared.progress.lock() = Some(progress);
+
+            if let Err(error) = self.save_checkpoint(shared, finished) {
+                warn!("could not save pregeneration checkpoint: {}", error);
+            }
+
+            if finished {
+                info!(
+                    "pregenerated {} chunks ({} new) in {:.1}s",
+                    progress.total,
+                    progress.generated,
+                    started.elapsed().as_secs_f64()
+                );
+            } else if running && !paused {
+                info!(
+                    "pregenerating: {}/{} chunks ({:.1}%), {:.1} chunks/s, ETA {}",
+                    progress.done,
+                    progress.total,
+                    progress.percent(),
+                    progress.chunks_per_second,
+                    progress.eta.map_or("unknown".to_string(), format_duration)
+                );
+            }
+            if let Some(events) = &self.events {
+                let mut event = PregenProgressEvent::new(progress);
+                if let Err(error) = events.emit(&mut event) {
+                    warn!("pregeneration progress listener failed: {}", error);
+                }
+            }
+
+            if !running {
+                break;
+            }
         }
+    }
 
-        let x = (self.current_step % width) - self.radius;
-        let z = (self.current_step / width) - self.radius;
+    // Region data is synced before the checkpoint claims it is there.
+    fn save_checkpoint(&self, shared: &Shared, finished: bool) -> Result<(), LoaderError> {
+        if let Some(storage) = &shared.dimension.storage {
+            storage.flush()?;
+        }
+        let Some(path) = &self.checkpoint else {
+            return Ok(());
+        };
+        if finished {
+            return match fs::remove_file(path) {
+                Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+                _ => Ok(()),
+            };
+        }
 
-        self.current_step += 1;
+        let checkpoint = Checkpoint {
+            center: (shared.spiral.center_x, shared.spiral.center_z),
+            radius: shared.spiral.radius,
+            done: shared.completed.lock().watermark,
+        };
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, checkpoint.to_string())?;
+        fs::rename(&temporary, path)?;
+        Ok(())
+    }
+}
 
-        Some((self.center_x + x, self.center_z + z))
+// A few `key value` lines, so an operator can read or delete it by hand.
+struct Checkpoint {
+    center: (i32, i32),
+    radius: i32,
+    done: u64,
+}
+
+impl Checkpoint {
+    fn parse(text: &str) -> Option<Checkpoint> {
+        let (mut center, mut radius, mut done) = (None, None, None);
+        for line in text.lines() {
+            let mut parts = line.split_whitespace();
+            match parts.next() {
+                Some("center") => {
+                    center = Some((parts.next()?.parse().ok()?, parts.next()?.parse().ok()?))
+                }
+                Some("radius") => radius = Some(parts.next()?.parse().ok()?),
+                Some("done") => done = Some(parts.next()?.parse().ok()?),
+                _ => {}
+            }
+        }
+        Some(Checkpoint {
+            center: center?,
+            radius: radius?,
+            done: done?,
+        })
+    }
+}
+
+impl std::fmt::Display for Checkpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "center {} {}", self.center.0, self.center.1)?;
+        writeln!(f, "radius {}", self.radius)?;
+        writeln!(f, "done {}", self.done)
     }
 }
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,472 @@
+use crate::{
+    biome,
+    block::{
+        Block, BlockState,
+        property::{DISTANCE, PERSISTENT, SNOWY},
+    },
+    chunk::Chunk,
+    generator::{
+        WorldGenerator,
+        noise::{OctaveNoise, Random, position_hash},
+    },
+    palette::{PalettedContainer, Strategy},
+};
+
+pub const SEA_LEVEL: i32 = 63;
+
+const MIN_Y: i32 = -64;
+const HEIGHT: i32 = 384;
+// Caves below this are flooded with lava, like vanilla's lava level.
+const LAVA_LEVEL: i32 = MIN_Y + 10;
+// Cave noise is sampled on a coarse grid and interpolated in between.
+const CELL_WIDTH: usize = 4;
+const CELL_HEIGHT: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    DeepOcean,
+    Ocean,
+    FrozenOcean,
+    Beach,
+    SnowyBeach,
+    Desert,
+    Plains,
+    Forest,
+    Taiga,
+    SnowyPlains,
+    WindsweptHills,
+    StonyPeaks,
+}
+
+impl Biome {
+    pub fn name(self) -> &'static str {
+        match self {
+            Biome::DeepOcean => "deep_ocean",
+            Biome::Ocean => "ocean",
+            Biome::FrozenOcean => "frozen_ocean",
+            Biome::Beach => "beach",
+            Biome::SnowyBeach => "snowy_beach",
+            Biome::Desert => "desert",
+            Biome::Plains => "plains",
+            Biome::Forest => "forest",
+            Biome::Taiga => "taiga",
+            Biome::SnowyPlains => "snowy_plains",
+            Biome::WindsweptHills => "windswept_hills",
+            Biome::StonyPeaks => "stony_peaks",
+        }
+    }
+
+    // The id in the biome registry.
+    pub fn id(self) -> u8 {
+        biome::biome_id(self.name()).expect("generated biomes are registered")
+    }
+
+    fn is_cold(self) -> bool {
+        matches!(
+            self,
+            Biome::FrozenOcean | Biome::SnowyBeach | Biome::SnowyPlains
+        )
+    }
+
+    // The top block and the blocks just under it on dry land.
+    fn surface(self) -> (BlockState, BlockState) {
+        match self {
+            Biome::Beach | Biome::SnowyBeach | Biome::Desert => {
+                (Block::SAND.default_state(), Block::SAND.default_state())
+            }
+            Biome::StonyPeaks => (Block::STONE.default_state(), Block::STONE.default_state()),
+            Biome::SnowyPlains => (
+                Block::GRASS_BLOCK.with(SNOWY, true),
+                Block::DIRT.default_state(),
+            ),
+            Biome::Taiga => (Block::PODZOL.default_state(), Block::DIRT.default_state()),
+            _ => (
+                Block::GRASS_BLOCK.default_state(),
+                Block::DIRT.default_state(),
+            ),
+        }
+    }
+
+    // Attempts at placing a tree per chunk.
+    fn trees(self) -> u32 {
+        match self {
+            Biome::Forest | Biome::Taiga => 8,
+            Biome::Plains | Biome::SnowyPlains | Biome::WindsweptHills => 1,
+            _ => 0,
+        }
+    }
+}
+
+// The terrain shape and biome of one block column.
+#[derive(Debug, Clone, Copy)]
+struct Column {
+    // The y just above the surface block.
+    height: i32,
+    biome: Biome,
+    // How many blocks of the biome's filler sit under the top block.
+    depth: i32,
+}
+
+// Hills, oceans, caves and biomes shaped by octave noise. Every chunk depends
+// only on the seed and its coordinates, so chunks can be generated in any
+// order and on any number of threads.
+pub struct NoiseGenerator {
+    seed: u64,
+    continents: OctaveNoise,
+    roughness: OctaveNoise,
+    detail: OctaveNoise,
+    temperature: OctaveNoise,
+    humidity: OctaveNoise,
+    surface: OctaveNoise,
+    tunnels: [OctaveNoise; 2],
+    caverns: OctaveNoise,
+}
+
+impl NoiseGenerator {
+    pub fn new(seed: i64) -> Self {
+        let seed = seed as u64;
+        let mut random = Random::new(seed);
+        NoiseGenerator {
+            seed,
+            continents: OctaveNoise::new(&mut random.fork(), 5, 1024.0),
+            roughness: OctaveNoise::new(&mut random.fork(), 3, 512.0),
+            detail: OctaveNoise::new(&mut random.fork(), 5, 128.0),
+            temperature: OctaveNoise::new(&mut random.fork(), 3, 1024.0),
+            humidity: OctaveNoise::new(&mut random.fork(), 3, 768.0),
+            surface: OctaveNoise::new(&mut random.fork(), 2, 32.0),
+            tunnels: [
+                OctaveNoise::new(&mut random.fork(), 2, 96.0),
+                OctaveNoise::new(&mut random.fork(), 2, 96.0),
+            ],
+            caverns: OctaveNoise::new(&mut random.fork(), 3, 128.0),
+        }
+    }
+
+    pub fn seed(&self) -> i64 {
+        self.seed as i64
+    }
+
+    // The y just above the terrain surface, before caves are carved.
+    pub fn surface_height(&self, x: i32, z: i32) -> i32 {
+        self.column(x, z).height
+    }
+
+    pub fn biome_at(&self, x: i32, z: i32) -> Biome {
+        self.column(x, z).biome
+    }
+
+    fn column(&self, x: i32, z: i32) -> Column {
+        let (fx, fz) = (x as f64, z as f64);
+
+        // Continentalness decides between ocean and land, roughness between
+        // plains and mountains, and detail adds the bumps on top.
+        let continent = (self.continents.sample_2d(fx, fz) * 2.5).clamp(-1.0, 1.0);
+        let roughness = self.roughness.sample_2d(fx, fz) * 2.0;
+        let detail = self.detail.sample_2d(fx, fz) * 2.0;
+
+        let inland = ((continent - 0.05) * 4.0).clamp(0.0, 1.0);
+        let mountains = smoothstep(((roughness - 0.1) * 2.0).clamp(0.0, 1.0)) * inland;
+        let base = SEA_LEVEL as f64 + 3.0 + continent * 36.0;
+        let amplitude = 6.0 + mountains * 70.0;
+        // Mountains take the absolute value for sharp ridges.
+        let shape = detail * (1.0 - mountains) + detail.abs() * mountains;
+        let height = (base + shape * amplitude).round() as i32;
+        let height = height.clamp(MIN_Y + 8, MIN_Y + HEIGHT - 16);
+
+        let temperature = self.temperature.sample_2d(fx, fz) * 2.5;
+        let humidity = self.humidity.sample_2d(fx, fz) * 2.5;
+        let cold = temperature < -0.4;
+
+        let biome = if height < SEA_LEVEL - 12 {
+            if cold {
+                Biome::FrozenOcean
+            } else {
+                Biome::DeepOcean
+            }
+        } else if height < SEA_LEVEL {
+            if cold {
+                Biome::FrozenOcean
+            } else {
+                Biome::Ocean
+            }
+        } else if height <= SEA_LEVEL + 2 && mountains < 0.2 {
+            if cold {
+                Biome::SnowyBeach
+            } else {
+                Biome::Beach
+            }
+        } else if height > 130 {
+            Biome::StonyPeaks
+        } else if mountains > 0.5 {
+            Biome::WindsweptHills
+        } else if temperature > 0.4 && humidity < 0.2 {
+            Biome::Desert
+        } else if cold {
+            Biome::SnowyPlains
+        } else if temperature < -0.15 && humidity > 0.0 {
+            Biome::Taiga
+        } else if humidity > 0.1 {
+            Biome::Forest
+        } else {
+            Biome::Plains
+        };
+
+        let depth = 3 + (self.surface.sample_2d(fx, fz) * 4.0).round() as i32;
+        Column {
+            height,
+            biome,
+            depth: depth.clamp(1, 6),
+        }
+    }
+
+    fn bedrock(&self, x: i32, y: i32, z: i32) -> bool {
+        let above_floor = y - MIN_Y;
+        above_floor == 0
+            || (above_floor < 5 && (position_hash(self.seed, x, y, z) % 5) as i32 >= above_floor)
+    }
+
+    fn stone(&self, x: i32, y: i32, z: i32) -> BlockState {
+        // Deepslate fades in over the eight blocks above y 0.
+        if y < 0 || (y < 8 && (position_hash(self.seed ^ 1, x, y, z) % 8) as i32 >= y) {
+            Block::DEEPSLATE.default_state()
+        } else {
+            Block::STONE.default_state()
+        }
+    }
+
+    // Caves are long tunnels where two noises are both near zero, and large
+    // caverns deep down where a third one peaks.
+    fn cave(&self, x: f64, y: f64, z: f64) -> f64 {
+        let a = self.tunnels[0].sample(x, y * 1.5, z);
+        let b = self.tunnels[1].sample(x, y * 1.5, z);
+        let tunnel = 0.006 - (a * a + b * b);
+        let cavern = if y < 32.0 {
+            self.caverns.sample(x, y * 2.0, z) - 0.35
+        } else {
+            -1.0
+        };
+        tunnel.max(cavern)
+    }
+
+    // Cave density at the corners of the coarse grid, positive where carved.
+    fn cave_grid(&self, chunk_x: i32, chunk_z: i32) -> Vec<f64> {
+        let (cells_x, cells_y) = (16 / CELL_WIDTH + 1, HEIGHT as usize / CELL_HEIGHT + 1);
+        let mut grid = Vec::with_capacity(cells_x * cells_x * cells_y);
+        for cy in 0..cells_y {
+            for cz in 0..cells_x {
+                for cx in 0..cells_x {
+                    grid.push(self.cave(
+                        (chunk_x * 16 + (cx * CELL_WIDTH) as i32) as f64,
+                        (MIN_Y + (cy * CELL_HEIGHT) as i32) as f64,
+                        (chunk_z * 16 + (cz * CELL_WIDTH) as i32) as f64,
+                    ));
+                }
+            }
+        }
+        grid
+    }
+
+    fn carved(grid: &[f64], x: usize, rel_y: usize, z: usize) -> bool {
+        let cells = 16 / CELL_WIDTH + 1;
+        let at = |cx: usize, cy: usize, cz: usize| grid[(cy * cells + cz) * cells + cx];
+        let (cx, cy, cz) = (x / CELL_WIDTH, rel_y / CELL_HEIGHT, z / CELL_WIDTH);
+        let tx = (x % CELL_WIDTH) as f64 / CELL_WIDTH as f64;
+        let ty = (rel_y % CELL_HEIGHT) as f64 / CELL_HEIGHT as f64;
+        let tz = (z % CELL_WIDTH) as f64 / CELL_WIDTH as f64;
+        let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+        let plane = |cy| {
+            lerp(
+                tz,
+                lerp(tx, at(cx, cy, cz), at(cx + 1, cy, cz)),
+                lerp(tx, at(cx, cy, cz + 1), at(cx + 1, cy, cz + 1)),
+            )
+        };
+        lerp(ty, plane(cy), plane(cy + 1)) > 0.0
+    }
+
+    fn fill_column(
+        &self,
+        blocks: &mut [u16],
+        grid: &[f64],
+        chunk: (i32, i32),
+        x: usize,
+        z: usize,
+        column: Column,
+    ) {
+        let (wx, wz) = (chunk.0 * 16 + x as i32, chunk.1 * 16 + z as i32);
+        let (top, filler) = column.biome.surface();
+        let underwater = column.height <= SEA_LEVEL;
+        // Keeps sea floors sealed so caves don't drain the ocean into them.
+        let carve_below = if underwater {
+            column.height - 8
+        } else {
+            column.height
+        };
+
+        let index = |y: i32| block_index(x, y, z);
+        for y in MIN_Y..column.height.max(SEA_LEVEL) {
+            let state = if y >= column.height {
+                if y == SEA_LEVEL - 1 && column.biome.is_cold() {
+                    Block::ICE.default_state()
+                } else {
+                    Block::WATER.default_state()
+                }
+            } else if self.bedrock(wx, y, wz) {
+                Block::BEDROCK.default_state()
+            } else if y < carve_below && Self::carved(grid, x, (y - MIN_Y) as usize, z) {
+                if y < LAVA_LEVEL {
+                    Block::LAVA.default_state()
+                } else {
+                    BlockState::AIR
+                }
+            } else if y < column.height - 1 - column.depth {
+                if filler.block() == Block::SAND && y >= column.height - 1 - column.depth - 3 {
+                    Block::SANDSTONE.default_state()
+                } else {
+                    self.stone(wx, y, wz)
+                }
+            } else if underwater {
+                // Sea and lake floors.
+                if column.height < SEA_LEVEL - 8 {
+                    Block::GRAVEL.default_state()
+                } else if column.biome == Biome::StonyPeaks {
+                    Block::STONE.default_state()
+                } else {
+                    Block::SAND.default_state()
+                }
+            } else if y == column.height - 1 {
+                top
+            } else {
+                filler
+            };
+            blocks[index(y)] = state.id();
+        }
+
+        let surface = blocks[index(column.height - 1)];
+        if column.biome.is_cold() && !underwater && BlockState(surface).is_solid() {
+            blocks[index(column.height)] = Block::SNOW.default_state().id();
+        }
+    }
+
+    fn place_trees(&self, blocks: &mut [u16], chunk: (i32, i32), columns: &[Column]) {
+        // Trees stay two blocks away from the chunk's sides so none of them
+        // depend on a neighbor.
+        let mut random = Random::new(position_hash(self.seed ^ 2, chunk.0, 0, chunk.1));
+        let center = columns[8 * 16 + 8];
+        for _ in 0..center.biome.trees() {
+            let x = 2 + random.next_below(12) as usize;
+            let z = 2 + random.next_below(12) as usize;
+            let trunk = 4 + random.next_below(3) as i32;
+            let column = columns[z * 16 + x];
+            if column.height <= SEA_LEVEL || column.height + trunk + 2 >= MIN_Y + HEIGHT {
+                continue;
+            }
+            let ground = BlockState(blocks[block_index(x, column.height - 1, z)]).block();
+            if ground != Block::GRASS_BLOCK && ground != Block::PODZOL {
+                continue;
+            }
+            let (log, leaves) = if column.biome == Biome::Taiga || column.biome.is_cold() {
+                (Block::SPRUCE_LOG, Block::SPRUCE_LEAVES)
+            } else if random.next_below(5) == 0 {
+                (Block::BIRCH_LOG, Block::BIRCH_LEAVES)
+            } else {
+                (Block::OAK_LOG, Block::OAK_LEAVES)
+            };
+
+            let base = column.height;
+            let crown = base + trunk;
+            for y in crown - 3..=crown {
+                let radius: i32 = if y >= crown - 1 { 1 } else { 2 };
+                for dz in -radius..=radius {
+                    for dx in -radius..=radius {
+                        // Rounds off the corners, randomly on the wide layers.
+                        let corner = dx.abs() == radius && dz.abs() == radius;
+                        if corner && (y == crown || random.next_below(2) == 0) {
+                            continue;
+                        }
+                        let (lx, lz) = ((x as i32 + dx) as usize, (z as i32 + dz) as usize);
+                        let index = block_index(lx, y, lz);
+                        if BlockState(blocks[index]).is_air() {
+                            let distance =
+                                (dx.abs() + dz.abs() + (y - (crown - 1)).max(0)).clamp(1, 7);
+                            let state = leaves
+                                .with(DISTANCE, distance as u8)
+                                .with(PERSISTENT, false);
+                            blocks[index] = state.id();
+                        }
+                    }
+                }
+            }
+            for y in base..crown {
+                blocks[block_index(x, y, z)] = log.default_state().id();
+            }
+            blocks[block_index(x, base - 1, z)] = Block::DIRT.default_state().id();
+        }
+    }
+}
+
+impl WorldGenerator for NoiseGenerator {
+    fn generate_chunk(&self, chunk_x: i32, chunk_z: i32) -> Chunk {
+        let mut chunk = Chunk::with_height(chunk_x, chunk_z, MIN_Y, HEIGHT);
+
+        let mut columns = Vec::with_capacity(256);
+        for z in 0..16 {
+            for x in 0..16 {
+                columns.push(self.column(chunk_x * 16 + x, chunk_z * 16 + z));
+            }
+        }
+
+        // The whole chunk as one array laid out like its sections, so each
+        // section is a contiguous slice of it.
+        let grid = self.cave_grid(chunk_x, chunk_z);
+        let mut blocks = vec![0u16; HEIGHT as usize * 256];
+        for z in 0..16 {
+            for x in 0..16 {
+                self.fill_column(
+                    &mut blocks,
+                    &grid,
+                    (chunk_x, chunk_z),
+                    x,
+                    z,
+                    columns[z * 16 + x],
+                );
+            }
+        }
+        self.place_trees(&mut blocks, (chunk_x, chunk_z), &columns);
+
+        // Biomes are stored per 4x4x4 cell, taken from the cell's middle column.
+        let mut biomes = [0u16; 64];
+        for z in 0..4 {
+            for x in 0..4 {
+                let id = columns[(z * 4 + 2) * 16 + x * 4 + 2].biome.id() as u16;
+                for y in 0..4 {
+                    biomes[(y * 4 + z) * 4 + x] = id;
+                }
+            }
+        }
+
+        for (section, values) in chunk.sections.iter_mut().zip(blocks.chunks(4096)) {
+            section.blocks = PalettedContainer::from_values(Strategy::BLOCKS, values);
+            section.biomes = PalettedContainer::from_values(Strategy::BIOMES, &biomes);
+            section.recount();
+        }
+        chunk.recompute_heightmaps();
+        chunk
+    }
+
+    fn min_y(&self) -> i32 {
+        MIN_Y
+    }
+
+    fn height(&self) -> i32 {
+        HEIGHT
+    }
+}
+
+fn block_index(x: usize, y: i32, z: usize) -> usize {
+    (((y - MIN_Y) as usize * 16 + z) * 16) + x
+}
+
+fn smoothstep(t: f64) -> f64 {
+    t * t * (3.0 - 2.0 * t)
+}
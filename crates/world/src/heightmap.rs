@@ -0,0 +1,138 @@
+use crate::block::BlockState;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HeightmapKind {
+    WorldSurface,
+    OceanFloor,
+    MotionBlocking,
+    MotionBlockingNoLeaves,
+}
+
+impl HeightmapKind {
+    pub const ALL: [HeightmapKind; 4] = [
+        HeightmapKind::WorldSurface,
+        HeightmapKind::OceanFloor,
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::MotionBlockingNoLeaves,
+    ];
+
+    // The ones clients use for rendering rain and snow.
+    pub const CLIENT: [HeightmapKind; 3] = [
+        HeightmapKind::WorldSurface,
+        HeightmapKind::MotionBlocking,
+        HeightmapKind::MotionBlockingNoLeaves,
+    ];
+
+    pub fn network_id(self) -> i32 {
+        match self {
+            HeightmapKind::WorldSurface => 1,
+            HeightmapKind::OceanFloor => 3,
+            HeightmapKind::MotionBlocking => 4,
+            HeightmapKind::MotionBlockingNoLeaves => 5,
+        }
+    }
+
+    // The key in Anvil's `Heightmaps` compound.
+    pub fn name(self) -> &'static str {
+        match self {
+            HeightmapKind::WorldSurface => "WORLD_SURFACE",
+            HeightmapKind::OceanFloor => "OCEAN_FLOOR",
+            HeightmapKind::MotionBlocking => "MOTION_BLOCKING",
+            HeightmapKind::MotionBlockingNoLeaves => "MOTION_BLOCKING_NO_LEAVES",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<HeightmapKind> {
+        HeightmapKind::ALL
+            .into_iter()
+            .find(|kind| kind.name() == name)
+    }
+
+    pub fn matches(self, state: BlockState) -> bool {
+        match self {
+            HeightmapKind::WorldSurface => !state.is_air(),
+            HeightmapKind::OceanFloor => state.is_solid(),
+            HeightmapKind::MotionBlocking => state.is_solid() || state.has_fluid(),
+            HeightmapKind::MotionBlockingNoLeaves => {
+                (state.is_solid() || state.has_fluid()) && !state.is_leaves()
+            }
+        }
+    }
+}
+
+// For each column, one above the highest block the heightmap counts,
+// relative to the bottom of the chunk. Zero when there is none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heightmap {
+    heights: [u16; 256],
+}
+
+impl Default for Heightmap {
+    fn default() -> Self {
+        Heightmap::new()
+    }
+}
+
+impl Heightmap {
+    pub fn new() -> Self {
+        Heightmap { heights: [0; 256] }
+    }
+
+    pub fn get(&self, x: usize, z: usize) -> u16 {
+        self.heights[z * 16 + x]
+    }
+
+    pub fn set(&mut self, x: usize, z: usize, height: u16) {
+        self.heights[z * 16 + x] = height;
+    }
+
+    // Packed like paletted containers, with just enough bits for `0..=height`
+    // and no entry split across two longs.
+    pub fn pack(&self, height: i32) -> Vec<i64> {
+        let bits = bits_for(height);
+        let per_long = (64 / bits) as usize;
+        self.heights
+            .chunks(per_long)
+            .map(|chunk| {
+                chunk.iter().enumerate().fold(0u64, |long, (i, &value)| {
+                    long | ((value as u64) << (i as u32 * bits))
+                }) as i64
+            })
+            .collect()
+    }
+
+    pub fn unpack(data: &[i64], height: i32) -> Option<Heightmap> {
+        let bits = bits_for(height);
+        let per_long = (64 / bits) as usize;
+        if data.len() != 256usize.div_ceil(per_long) {
+            return None;
+        }
+
+        let mask = (1u64 << bits) - 1;
+        let mut heightmap = Heightmap::new();
+        for (i, value) in heightmap.heights.iter_mut().enumerate() {
+            let long = data[i / per_long] as u64;
+            *value = ((long >> ((i % per_long) as u32 * bits)) & mask) as u16;
+        }
+        Some(heightmap)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Heightmaps {
+    maps: [Heightmap; 4],
+}
+
+impl Heightmaps {
+    pub fn get(&self, kind: HeightmapKind) -> &Heightmap {
+        &self.maps[kind as usize]
+    }
+
+    pub fn get_mut(&mut self, kind: HeightmapKind) -> &mut Heightmap {
+        &mut self.maps[kind as usize]
+    }
+}
+
+fn bits_for(height: i32) -> u32 {
+    u32::BITS - (height as u32).leading_zeros()
+}
@@ -1,58 +1,663 @@
-use std::sync::Arc;
+use std::{collections::VecDeque, sync::Arc};
 
+use ahash::AHashMap;
 use dashmap::DashMap;
+use parking_lot::{Mutex, RwLock};
 
-use crate::{chunk::Chunk, generator::WorldGenerator};
+use crate::{
+    block::{Block, BlockState, shape},
+    block_entity::BlockEntity,
+    border::{BorderChange, WorldBorder},
+    chunk::Chunk,
+    dimension::{DimensionError, DimensionType, DimensionTypes},
+    generator::WorldGenerator,
+    loader::{LoaderError, RegionStorage},
+    pos::{BlockPos, Direction},
+    ticket::{ChunkStats, Ticket, TicketId, Tickets},
+    time::Clock,
+    update::{
+        BlockFlags, ChangedSection, DimensionTick, ScheduledTick, ScheduledTicks, SectionChanges,
+    },
+    weather::{Sky, WeatherChange},
+};
 
+pub mod biome;
+pub mod block;
+pub mod block_entity;
+pub mod border;
 pub mod chunk;
+pub mod command;
+pub mod dimension;
+pub mod edit;
+mod encode;
 pub mod generator;
+pub mod heightmap;
+pub mod light;
+pub mod loader;
+pub mod palette;
+pub mod pos;
+pub mod schematic;
+pub mod ticket;
+pub mod time;
+pub mod transform;
+pub mod update;
+pub mod view;
+pub mod weather;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub enum DimensionId {
-    Overworld,
-    Nether,
-    TheEnd,
-}
+pub use dimension::DimensionId;
 
 pub struct World {
     pub dimensions: DashMap<DimensionId, Arc<Dimension>>,
+    types: RwLock<DimensionTypes>,
 }
 
 pub struct Dimension {
-    pub min_y: i32,
-    pub height: i32,
+    pub id: DimensionId,
+    pub type_id: DimensionId,
+    pub dimension_type: DimensionType,
     pub chunks: DashMap<(i32, i32), Chunk>,
     pub generator: Box<dyn WorldGenerator>,
+    pub storage: Option<RegionStorage>,
+    tickets: Mutex<Tickets>,
+    chunk_limit: Option<usize>,
+    unload_delay: u64,
+    scheduled: Mutex<ScheduledTicks>,
+    changes: Mutex<AHashMap<(i32, i32, i32), ChangedSection>>,
+    border: Mutex<WorldBorder>,
+    border_changes: Mutex<Vec<BorderChange>>,
+    clock: Mutex<Clock>,
+    sky: Mutex<Sky>,
+    weather_changes: Mutex<Vec<WeatherChange>>,
+}
+
+// How many neighbor shape updates one block change may cause, as in vanilla.
+const MAX_SHAPE_UPDATES: usize = 512;
+
+impl Default for World {
+    fn default() -> Self {
+        World::new()
+    }
 }
 
 impl World {
     pub fn new() -> Self {
         World {
             dimensions: DashMap::new(),
+            types: RwLock::new(DimensionTypes::vanilla()),
+        }
+    }
+
+    // Types from config, such as those under `dimension_types`, replacing
+    // vanilla's of the same id.
+    pub fn with_types(
+        mut self,
+        types: impl IntoIterator<Item = (DimensionId, DimensionType)>,
+    ) -> Result<Self, DimensionError> {
+        for (id, kind) in types {
+            self.types.get_mut().register(id, kind)?;
+        }
+        Ok(self)
+    }
+
+    // The dimension_type registry to send clients during configuration. A
+    // client only knows the types it was sent, so dimensions with new types
+    // should be added before players join.
+    pub fn types(&self) -> DimensionTypes {
+        self.types.read().clone()
+    }
+
+    // The dimension's type is registered if it wasn't yet, so clients learn
+    // about it. A type that differs from the registered one of the same id
+    // is rejected, since clients would see different heights.
+    pub fn add_dimension(&self, dimension: Dimension) -> Result<Arc<Dimension>, DimensionError> {
+        if self.dimensions.contains_key(&dimension.id) {
+            return Err(DimensionError::Duplicate(dimension.id));
+        }
+        let generator = (dimension.generator.min_y(), dimension.generator.height());
+        if generator != (dimension.min_y(), dimension.height()) {
+            return Err(DimensionError::InvalidHeight(format!(
+                "the generator of {} spans {} blocks from y {}, its type {} from y {}",
+                dimension.id,
+                generator.1,
+                generator.0,
+                dimension.height(),
+                dimension.min_y()
+            )));
         }
+        let mut types = self.types.write();
+        match types.get(&dimension.type_id) {
+            Some(kind) if *kind != dimension.dimension_type => {
+                return Err(DimensionError::TypeMismatch(dimension.type_id));
+            }
+            Some(_) => {}
+            None => types.register(dimension.type_id.clone(), dimension.dimension_type.clone())?,
+        }
+        let dimension = Arc::new(dimension);
+        self.dimensions
+            .insert(dimension.id.clone(), dimension.clone());
+        Ok(dimension)
     }
 
-    pub fn add_dimension(&mut self, id: DimensionId, dimension: Dimension) {
-        self.dimensions.insert(id, Arc::new(dimension));
+    pub fn get_dimension(&self, id: &DimensionId) -> Option<Arc<Dimension>> {
+        self.dimensions.get(id).map(|r| r.value().clone())
     }
 
-    pub fn get_dimension(&self, id: DimensionId) -> Option<Arc<Dimension>> {
-        self.dimensions.get(&id).map(|r| r.value().clone())
+    // Players still in the dimension keep it alive until they leave.
+    pub fn remove_dimension(&self, id: &DimensionId) -> Option<Arc<Dimension>> {
+        self.dimensions.remove(id).map(|(_, dimension)| dimension)
     }
 }
 
 impl Dimension {
+    // Vanilla dimensions get their own type, others the overworld's until
+    // `with_type` says otherwise.
     pub fn new(id: DimensionId, generator: Box<dyn WorldGenerator>) -> Self {
-        let (min_y, height) = match id {
-            DimensionId::Overworld => (-64, 320),
-            DimensionId::Nether => (0, 128),
-            DimensionId::TheEnd => (0, 256),
+        let vanilla = DimensionTypes::vanilla();
+        let (type_id, dimension_type) = match vanilla.get(&id) {
+            Some(kind) => (id.clone(), kind.clone()),
+            None => (DimensionId::overworld(), DimensionType::overworld()),
         };
+        let sky = Sky::new(&id);
         Dimension {
-            min_y,
-            height,
+            id,
+            type_id,
+            dimension_type,
             chunks: DashMap::new(),
             generator,
+            storage: None,
+            tickets: Mutex::new(Tickets::default()),
+            chunk_limit: None,
+            unload_delay: 300,
+            scheduled: Mutex::new(ScheduledTicks::default()),
+            changes: Mutex::new(AHashMap::new()),
+            border: Mutex::new(WorldBorder::default()),
+            border_changes: Mutex::new(Vec::new()),
+            clock: Mutex::new(Clock::default()),
+            sky: Mutex::new(sky),
+            weather_changes: Mutex::new(Vec::new()),
+        }
+    }
+
+    // The generator and storage have to use the type's `min_y` and `height`;
+    // `World::add_dimension` rejects a generator that doesn't and
+    // `load_chunk` any chunk that doesn't.
+    pub fn with_type(mut self, type_id: DimensionId, dimension_type: DimensionType) -> Self {
+        self.type_id = type_id;
+        self.dimension_type = dimension_type;
+        self
+    }
+
+    pub fn min_y(&self) -> i32 {
+        self.dimension_type.min_y
+    }
+
+    pub fn height(&self) -> i32 {
+        self.dimension_type.height
+    }
+
+    // Also reads the world border, time and weather saved with the
+    // dimension. What can't be read is left at its default.
+    pub fn with_storage(mut self, storage: RegionStorage) -> Self {
+        self.storage = Some(storage);
+        let loaded = [
+            ("world border", self.load_border()),
+            ("time", self.load_time()),
+            ("weather", self.load_weather()),
+        ];
+        for (what, result) in loaded {
+            if let Err(error) = result {
+                log::warn!("Could not read the {} of {}: {}", what, self.id, error);
+            }
         }
+        self
+    }
+
+    // Beyond this many loaded chunks, idle ones are evicted right away
+    // instead of after the unload delay. Ticketed chunks are never evicted,
+    // so the limit can still be exceeded.
+    pub fn with_chunk_limit(mut self, limit: usize) -> Self {
+        self.chunk_limit = Some(limit);
+        self
+    }
+
+    // How many ticks chunks no ticket holds stay loaded, in case they are
+    // needed again soon.
+    pub fn with_unload_delay(mut self, ticks: u64) -> Self {
+        self.unload_delay = ticks;
+        self
     }
+
+    // Reads the chunk from disk when it was saved before, otherwise generates it.
+    pub fn load_chunk(&self, chunk_x: i32, chunk_z: i32) -> Result<(), LoaderError> {
+        if self.chunks.contains_key(&(chunk_x, chunk_z)) {
+            return Ok(());
+        }
+
+        let stored = match &self.storage {
+            Some(storage) => storage.load_chunk(chunk_x, chunk_z)?,
+            None => None,
+        };
+        let chunk = stored.unwrap_or_else(|| self.generator.generate_chunk(chunk_x, chunk_z));
+        // Clients can't read sections past the dimension's height.
+        if (chunk.min_y, chunk.height()) != (self.min_y(), self.height()) {
+            return Err(LoaderError::WrongHeight(
+                chunk.min_y,
+                chunk.height(),
+                self.min_y(),
+                self.height(),
+            ));
+        }
+        self.chunks.entry((chunk_x, chunk_z)).or_insert(chunk);
+        light::light_chunk(&self.chunks, chunk_x, chunk_z);
+        Ok(())
+    }
+
+    // Relights around blocks changed in the chunk since the last call.
+    pub fn update_light(&self, chunk_x: i32, chunk_z: i32) {
+        light::update_light(&self.chunks, chunk_x, chunk_z);
+    }
+
+    // Air where the chunk is not loaded.
+    pub fn get_block(&self, pos: BlockPos) -> BlockState {
+        let (x, z) = pos.local();
+        self.chunks
+            .get(&pos.chunk())
+            .map_or(BlockState::AIR, |chunk| chunk.get_block(x, pos.y, z))
+    }
+
+    // Loads the chunk if needed and places the block, then does what the
+    // flags ask for. Neighbors in chunks that are not loaded are left alone.
+    // Returns the state that was replaced, air outside the dimension's height.
+    //
+    // Light is left for `update_light`.
+    pub fn set_block(
+        &self,
+        pos: BlockPos,
+        state: BlockState,
+        flags: BlockFlags,
+    ) -> Result<BlockState, LoaderError> {
+        let (chunk_x, chunk_z) = pos.chunk();
+        self.load_chunk(chunk_x, chunk_z)?;
+        let state = if flags.contains(BlockFlags::NEIGHBORS) {
+            shape::update_shape(state, pos, |pos| self.get_block(pos))
+        } else {
+            state
+        };
+        let Some(old) = self.place(pos, state, flags) else {
+            return Ok(BlockState::AIR);
+        };
+        if old != state && flags.contains(BlockFlags::NEIGHBORS) {
+            self.update_neighbors(pos, flags);
+        }
+        Ok(old)
+    }
+
+    pub fn block_entity(&self, pos: BlockPos) -> Option<BlockEntity> {
+        let (x, z) = pos.local();
+        self.chunks
+            .get(&pos.chunk())?
+            .block_entity(x, pos.y, z)
+            .cloned()
+    }
+
+    // Loads the chunk if needed. Only where the block has that type of block
+    // entity; players with the chunk are sent the new data. Returns whether
+    // it was set.
+    pub fn set_block_entity(
+        &self,
+        pos: BlockPos,
+        entity: BlockEntity,
+    ) -> Result<bool, LoaderError> {
+        let (chunk_x, chunk_z) = pos.chunk();
+        self.load_chunk(chunk_x, chunk_z)?;
+        let (x, z) = pos.local();
+        let set = self
+            .chunks
+            .get_mut(&(chunk_x, chunk_z))
+            .is_some_and(|mut chunk| chunk.set_block_entity(x, pos.y, z, entity));
+        if set {
+            self.changes
+                .lock()
+                .entry((chunk_x, pos.section_y(), chunk_z))
+                .or_default()
+                .block_entities
+                .insert(section_index(pos));
+        }
+        Ok(set)
+    }
+
+    pub fn remove_block_entity(&self, pos: BlockPos) -> Option<BlockEntity> {
+        let (x, z) = pos.local();
+        let removed = self
+            .chunks
+            .get_mut(&pos.chunk())?
+            .remove_block_entity(x, pos.y, z)?;
+        let (chunk_x, chunk_z) = pos.chunk();
+        self.changes
+            .lock()
+            .entry((chunk_x, pos.section_y(), chunk_z))
+            .or_default()
+            .block_entities
+            .insert(section_index(pos));
+        Some(removed)
+    }
+
+    // Schedules a tick for the block at the position, unless one is already
+    // pending. Returns whether it was scheduled.
+    pub fn schedule_tick(&self, pos: BlockPos, block: Block, delay: u32) -> bool {
+        self.scheduled
+            .lock()
+            .schedule(ScheduledTick { pos, block }, delay)
+    }
+
+    pub fn scheduled_ticks(&self) -> usize {
+        self.scheduled.lock().len()
+    }
+
+    // Runs once per game tick and returns the ticks that became due for the
+    // game to run, dropping those whose block has since been replaced.
+    pub fn tick_blocks(&self) -> Vec<ScheduledTick> {
+        let due = self.scheduled.lock().advance();
+        due.into_iter()
+            .filter(|tick| {
+                let state = self.get_block(tick.pos);
+                update::scheduled_tick(state).map(|(block, _)| block) == Some(tick.block)
+                    || state.block() == tick.block
+            })
+            .collect()
+    }
+
+    // The blocks changed with `BlockFlags::CLIENTS` and the block entities
+    // changed since the last call, grouped by section for Block Update,
+    // Update Section Blocks and Block Entity Data.
+    pub fn take_block_changes(&self) -> Vec<SectionChanges> {
+        let changes = std::mem::take(&mut *self.changes.lock());
+        let mut sections = changes
+            .into_iter()
+            .filter_map(|((chunk_x, section_y, chunk_z), changed)| {
+                let chunk = self.chunks.get(&(chunk_x, chunk_z))?;
+                let position = |index: usize| {
+                    let (x, y, z) = (index & 15, index >> 8, index >> 4 & 15);
+                    let y = section_y * 16 + y as i32;
+                    let pos = BlockPos::new(chunk_x * 16 + x as i32, y, chunk_z * 16 + z as i32);
+                    (pos, x, z)
+                };
+                let mut blocks = changed
+                    .blocks
+                    .iter()
+                    .map(|index| {
+                        let (pos, x, z) = position(index);
+                        (pos, chunk.get_block(x, pos.y, z))
+                    })
+                    .collect::<Vec<_>>();
+                blocks.sort_unstable_by_key(|(pos, _)| *pos);
+                let mut block_entities = changed
+                    .block_entities
+                    .iter()
+                    .filter_map(|index| {
+                        let (pos, x, z) = position(index);
+                        let entity = match chunk.block_entity(x, pos.y, z) {
+                            Some(entity) => entity.clone(),
+                            None => BlockEntity::for_block(chunk.get_block(x, pos.y, z).block())?,
+                        };
+                        Some((pos, entity))
+                    })
+                    .collect::<Vec<_>>();
+                block_entities.sort_unstable_by_key(|(pos, _)| *pos);
+                (!blocks.is_empty() || !block_entities.is_empty()).then_some(SectionChanges {
+                    chunk_x,
+                    section_y,
+                    chunk_z,
+                    blocks,
+                    block_entities,
+                })
+            })
+            .collect::<Vec<_>>();
+        sections
+            .sort_unstable_by_key(|section| (section.chunk_x, section.chunk_z, section.section_y));
+        sections
+    }
+
+    // Breadth first from the changed block, each neighbor that changes shape
+    // updating its own neighbors in turn.
+    fn update_neighbors(&self, pos: BlockPos, flags: BlockFlags) {
+        let mut queue = VecDeque::from([pos]);
+        let mut updates = 0;
+        while let Some(changed) = queue.pop_front() {
+            for direction in Direction::ALL {
+                let neighbor = changed.offset(direction);
+                if !self.chunks.contains_key(&neighbor.chunk()) {
+                    continue;
+                }
+                let current = self.get_block(neighbor);
+                if flags.contains(BlockFlags::TICKS) {
+                    self.schedule_block_tick(neighbor, current);
+                }
+                if updates >= MAX_SHAPE_UPDATES {
+                    continue;
+                }
+                let updated = shape::update_shape(current, neighbor, |pos| self.get_block(pos));
+                if updated != current {
+                    updates += 1;
+                    self.place(neighbor, updated, flags);
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+    }
+
+    // Returns the replaced state, or nothing when the chunk is not loaded or
+    // the position is outside its height.
+    fn place(&self, pos: BlockPos, state: BlockState, flags: BlockFlags) -> Option<BlockState> {
+        let old = {
+            let mut chunk = self.chunks.get_mut(&pos.chunk())?;
+            if pos.y < chunk.min_y || pos.y >= chunk.min_y + chunk.height() {
+                return None;
+            }
+            let (x, z) = pos.local();
+            chunk.set_block(x, pos.y, z, state)
+        };
+        if old != state {
+            if flags.contains(BlockFlags::CLIENTS) {
+                let (chunk_x, chunk_z) = pos.chunk();
+                self.changes
+                    .lock()
+                    .entry((chunk_x, pos.section_y(), chunk_z))
+                    .or_default()
+                    .blocks
+                    .insert(section_index(pos));
+            }
+            if flags.contains(BlockFlags::TICKS) {
+                self.schedule_block_tick(pos, state);
+            }
+        }
+        Some(old)
+    }
+
+    fn schedule_block_tick(&self, pos: BlockPos, state: BlockState) {
+        if let Some((block, delay)) = update::scheduled_tick(state) {
+            self.schedule_tick(pos, block, delay);
+        }
+    }
+
+    // Keeps the chunks the ticket covers loaded until it is removed or
+    // expires. Nothing is loaded yet; see `load_ticket`.
+    pub fn add_ticket(&self, ticket: Ticket) -> TicketId {
+        self.tickets.lock().add(ticket)
+    }
+
+    pub fn ticket(&self, id: TicketId) -> Option<Ticket> {
+        self.tickets.lock().get(id)
+    }
+
+    // Its chunks stay loaded for the unload delay before being evicted.
+    pub fn remove_ticket(&self, id: TicketId) -> Option<Ticket> {
+        self.tickets.lock().remove(id)
+    }
+
+    // Recenters a ticket, as when a player crosses into another chunk.
+    pub fn move_ticket(&self, id: TicketId, chunk_x: i32, chunk_z: i32) -> bool {
+        let mut tickets = self.tickets.lock();
+        let Some(old) = tickets.get(id) else {
+            return false;
+        };
+        tickets.replace(
+            id,
+            Ticket {
+                chunk_x,
+                chunk_z,
+                ..old
+            },
+        );
+        true
+    }
+
+    // Loads the chunks of the ticket that aren't yet and returns how many
+    // that was. Reading and generating chunks blocks, so async code runs this
+    // off the runtime. Player tickets skip it: chunk streaming loads each
+    // chunk when its batch is sent.
+    pub fn load_ticket(&self, id: TicketId) -> Result<usize, LoaderError> {
+        let Some(ticket) = self.ticket(id) else {
+            return Ok(0);
+        };
+        let mut loaded = 0;
+        for (chunk_x, chunk_z) in ticket.chunks() {
+            if !self.chunks.contains_key(&(chunk_x, chunk_z)) {
+                self.load_chunk(chunk_x, chunk_z)?;
+                loaded += 1;
+            }
+        }
+        Ok(loaded)
+    }
+
+    pub fn is_ticketed(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.tickets.lock().is_referenced((chunk_x, chunk_z))
+    }
+
+    // Runs once per game tick: expires tickets, then saves and evicts the
+    // chunks that have been idle for the unload delay, oldest first and more
+    // of them while over the chunk limit. Returns how many were evicted.
+    //
+    // Chunks loaded without a ticket count as idle from the first tick that
+    // sees them.
+    pub fn tick_chunks(&self) -> Result<usize, LoaderError> {
+        let mut tickets = self.tickets.lock();
+        tickets.tick();
+        let now = tickets.tick;
+
+        let loaded = self
+            .chunks
+            .iter()
+            .map(|entry| *entry.key())
+            .collect::<Vec<_>>();
+        for &chunk in &loaded {
+            if !tickets.is_referenced(chunk) {
+                tickets.idle.entry(chunk).or_insert(now);
+            }
+        }
+        tickets
+            .idle
+            .retain(|chunk, _| self.chunks.contains_key(chunk));
+
+        let mut idle = tickets
+            .idle
+            .iter()
+            .map(|(&chunk, &since)| (since, chunk))
+            .collect::<Vec<_>>();
+        idle.sort_unstable();
+
+        let over_limit = self
+            .chunk_limit
+            .map_or(0, |limit| loaded.len().saturating_sub(limit));
+        let mut evicted = 0;
+        for (since, chunk) in idle {
+            if evicted >= over_limit && now - since < self.unload_delay {
+                break;
+            }
+            self.evict(chunk)?;
+            tickets.idle.remove(&chunk);
+            evicted += 1;
+        }
+        Ok(evicted)
+    }
+
+    // One game tick of the clock, weather, border and chunk unloading. Scheduled
+    // block ticks are left to the caller. On error the changes stay pending
+    // for the next tick.
+    pub fn tick(&self) -> Result<DimensionTick, LoaderError> {
+        self.tick_chunks()?;
+        self.tick_time();
+        self.tick_weather();
+        self.tick_border();
+        Ok(DimensionTick {
+            dimension: self.id.clone(),
+            blocks: self.take_block_changes(),
+            border: self.take_border_changes(),
+            time: self.take_time_update(),
+            weather: self.take_weather_changes(),
+        })
+    }
+
+    // Saves and evicts a chunk right away unless a ticket holds it.
+    pub fn unload_chunk(&self, chunk_x: i32, chunk_z: i32) -> Result<bool, LoaderError> {
+        let mut tickets = self.tickets.lock();
+        if tickets.is_referenced((chunk_x, chunk_z)) {
+            return Ok(false);
+        }
+        tickets.idle.remove(&(chunk_x, chunk_z));
+        self.evict((chunk_x, chunk_z))
+    }
+
+    pub fn chunk_stats(&self) -> ChunkStats {
+        let tickets = self.tickets.lock();
+        let mut stats = ChunkStats {
+            tickets: tickets.len(),
+            limit: self.chunk_limit,
+            ..ChunkStats::default()
+        };
+        for chunk in self.chunks.iter() {
+            stats.loaded += 1;
+            stats.memory += chunk.memory_usage();
+            if tickets.is_referenced(*chunk.key()) {
+                stats.ticketed += 1;
+            }
+        }
+        stats.idle = stats.loaded - stats.ticketed;
+        stats
+    }
+
+    // Without storage the chunk is dropped and generated again next time.
+    fn evict(&self, chunk: (i32, i32)) -> Result<bool, LoaderError> {
+        let Some((_, removed)) = self.chunks.remove(&chunk) else {
+            return Ok(false);
+        };
+        if let Some(storage) = &self.storage
+            && let Err(error) = storage.save_chunk(&removed)
+        {
+            self.chunks.insert(chunk, removed);
+            return Err(error);
+        }
+        Ok(true)
+    }
+
+    // Saves the world border, time and weather along with the chunks.
+    pub fn save_chunks(&self) -> Result<usize, LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(0);
+        };
+        self.save_border()?;
+        self.save_time()?;
+        self.save_weather()?;
+        for chunk in self.chunks.iter() {
+            storage.save_chunk(chunk.value())?;
+        }
+        storage.flush()?;
+        Ok(self.chunks.len())
+    }
+}
+
+// A block's index within its section, as sections store them.
+fn section_index(pos: BlockPos) -> usize {
+    let (x, z) = pos.local();
+    (pos.y.rem_euclid(16) as usize * 16 + z) * 16 + x
 }
@@ -0,0 +1,359 @@
+use std::collections::VecDeque;
+
+use dashmap::DashMap;
+
+use crate::{block::BlockState, chunk::Chunk, light::LightKind};
+
+const DIRECTIONS: [(i32, i32, i32); 6] = [
+    (0, -1, 0),
+    (0, 1, 0),
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+type Pos = (i32, i32, i32);
+
+// Block and light access in world coordinates. `state` is `None` where light
+// cannot go: unloaded chunks and outside the world's height.
+trait LightAccess {
+    fn state(&self, pos: Pos) -> Option<BlockState>;
+    fn light(&self, kind: LightKind, pos: Pos) -> u8;
+    fn set_light(&mut self, kind: LightKind, pos: Pos, level: u8);
+}
+
+// A single chunk, used while lighting it before its neighbors are involved.
+struct ChunkView<'a> {
+    chunk: &'a mut Chunk,
+}
+
+impl ChunkView<'_> {
+    fn local(&self, (x, _, z): Pos) -> Option<(usize, usize)> {
+        (x >> 4 == self.chunk.x && z >> 4 == self.chunk.z)
+            .then_some(((x & 15) as usize, (z & 15) as usize))
+    }
+}
+
+impl LightAccess for ChunkView<'_> {
+    fn state(&self, pos: Pos) -> Option<BlockState> {
+        let (x, z) = self.local(pos)?;
+        in_height(self.chunk, pos.1).then(|| self.chunk.get_block(x, pos.1, z))
+    }
+
+    fn light(&self, kind: LightKind, pos: Pos) -> u8 {
+        self.local(pos)
+            .map_or(0, |(x, z)| self.chunk.light(kind, x, pos.1, z))
+    }
+
+    fn set_light(&mut self, kind: LightKind, pos: Pos, level: u8) {
+        if let Some((x, z)) = self.local(pos) {
+            self.chunk.set_light(kind, x, pos.1, z, level);
+        }
+    }
+}
+
+// Every loaded chunk of a dimension. Each access locks the chunk on its own so
+// no guard is held while another chunk is touched.
+struct DimensionView<'a> {
+    chunks: &'a DashMap<(i32, i32), Chunk>,
+}
+
+impl LightAccess for DimensionView<'_> {
+    fn state(&self, (x, y, z): Pos) -> Option<BlockState> {
+        let chunk = self.chunks.get(&(x >> 4, z >> 4))?;
+        in_height(&chunk, y).then(|| chunk.get_block((x & 15) as usize, y, (z & 15) as usize))
+    }
+
+    fn light(&self, kind: LightKind, (x, y, z): Pos) -> u8 {
+        self.chunks.get(&(x >> 4, z >> 4)).map_or(0, |chunk| {
+            chunk.light(kind, (x & 15) as usize, y, (z & 15) as usize)
+        })
+    }
+
+    fn set_light(&mut self, kind: LightKind, (x, y, z): Pos, level: u8) {
+        if let Some(mut chunk) = self.chunks.get_mut(&(x >> 4, z >> 4)) {
+            chunk.set_light(kind, (x & 15) as usize, y, (z & 15) as usize, level);
+        }
+    }
+}
+
+fn in_height(chunk: &Chunk, y: i32) -> bool {
+    y >= chunk.min_y && y < chunk.min_y + chunk.height()
+}
+
+// Lights a chunk from scratch, then exchanges light with the loaded chunks
+// around it.
+pub fn light_chunk(chunks: &DashMap<(i32, i32), Chunk>, chunk_x: i32, chunk_z: i32) {
+    {
+        let Some(mut chunk) = chunks.get_mut(&(chunk_x, chunk_z)) else {
+            return;
+        };
+        light_locally(&mut chunk);
+    }
+
+    let mut view = DimensionView { chunks };
+    for (dx, dz) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        for kind in [LightKind::Sky, LightKind::Block] {
+            let mut queue = border_seeds(chunks, kind, (chunk_x, chunk_z), (dx, dz));
+            propagate(&mut view, kind, &mut queue);
+        }
+    }
+}
+
+// Relights around the blocks whose emission or opacity changed since the
+// last run, removing light that no longer reaches and spreading new light,
+// across chunk borders where needed.
+pub fn update_light(chunks: &DashMap<(i32, i32), Chunk>, chunk_x: i32, chunk_z: i32) {
+    let changes = {
+        let Some(mut chunk) = chunks.get_mut(&(chunk_x, chunk_z)) else {
+            return;
+        };
+        let min_y = chunk.min_y;
+        let mut changes = Vec::new();
+        for (index, section) in chunk.sections.iter_mut().enumerate() {
+            let mut updates = std::mem::take(&mut section.light_updates);
+            updates.sort_unstable();
+            updates.dedup();
+            changes.extend(updates.into_iter().map(|i| {
+                let i = i as i32;
+                (
+                    chunk_x * 16 + (i & 15),
+                    min_y + index as i32 * 16 + (i >> 8),
+                    chunk_z * 16 + ((i >> 4) & 15),
+                )
+            }));
+        }
+        changes
+    };
+    if changes.is_empty() {
+        return;
+    }
+
+    let mut view = DimensionView { chunks };
+    for kind in [LightKind::Sky, LightKind::Block] {
+        let mut removal = VecDeque::new();
+        let mut queue = VecDeque::new();
+
+        for &pos in &changes {
+            let level = view.light(kind, pos);
+            if level > 0 {
+                view.set_light(kind, pos, 0);
+                removal.push_back((pos, level));
+            }
+            let emission = match kind {
+                LightKind::Sky => 0,
+                LightKind::Block => view.state(pos).map_or(0, BlockState::light_emission),
+            };
+            if emission > 0 {
+                view.set_light(kind, pos, emission);
+                queue.push_back(pos);
+            }
+            // Neighbors, including the open sky above the world, light the
+            // changed block back up if it lets light through.
+            for (dx, dy, dz) in DIRECTIONS {
+                queue.push_back((pos.0 + dx, pos.1 + dy, pos.2 + dz));
+            }
+        }
+
+        remove(&mut view, kind, &mut removal, &mut queue);
+        propagate(&mut view, kind, &mut queue);
+    }
+}
+
+fn light_locally(chunk: &mut Chunk) {
+    chunk.mark_dirty();
+    let highest = chunk
+        .sections
+        .iter()
+        .rposition(|section| section.blocks_count > 0);
+    for (index, section) in chunk.sections.iter_mut().enumerate() {
+        let open = highest.is_none_or(|highest| index > highest);
+        section.light_updates.clear();
+        section.block_light.fill(0);
+        section.sky_light.fill(if open { 15 } else { 0 });
+    }
+
+    let (base_x, base_z) = (chunk.x * 16, chunk.z * 16);
+    let min_y = chunk.min_y;
+    let start = highest.map_or(min_y, |h| min_y + (h as i32 + 1) * 16);
+
+    let mut emitters = Vec::new();
+    for (index, section) in chunk.sections.iter().enumerate() {
+        if !section
+            .blocks
+            .may_contain(|id| BlockState(id).light_emission() > 0)
+        {
+            continue;
+        }
+        for (i, id) in section.blocks.iter().enumerate() {
+            let emission = BlockState(id).light_emission();
+            if emission > 0 {
+                let i = i as i32;
+                let pos = (
+                    base_x + (i & 15),
+                    min_y + index as i32 * 16 + (i >> 8),
+                    base_z + ((i >> 4) & 15),
+                );
+                emitters.push((pos, emission));
+            }
+        }
+    }
+
+    let mut view = ChunkView { chunk };
+
+    // Sky light falls straight down at full strength until something stops
+    // it, then spreads from every lit block.
+    let mut queue = VecDeque::new();
+    for z in base_z..base_z + 16 {
+        for x in base_x..base_x + 16 {
+            let mut y = start - 1;
+            while let Some(state) = view.state((x, y, z)) {
+                if state.opacity() > 0 {
+                    break;
+                }
+                view.set_light(LightKind::Sky, (x, y, z), 15);
+                queue.push_back((x, y, z));
+                y -= 1;
+            }
+            // Lets the block that stopped the column take what gets through.
+            queue.push_back((x, y + 1, z));
+        }
+    }
+    propagate(&mut view, LightKind::Sky, &mut queue);
+
+    for (pos, emission) in emitters {
+        view.set_light(LightKind::Block, pos, emission);
+        queue.push_back(pos);
+    }
+    propagate(&mut view, LightKind::Block, &mut queue);
+}
+
+// Blocks on the chunk's side facing `(dx, dz)` and the blocks across from
+// them in the neighbor, whichever is brighter by more than the one level
+// light loses per block.
+fn border_seeds(
+    chunks: &DashMap<(i32, i32), Chunk>,
+    kind: LightKind,
+    (chunk_x, chunk_z): (i32, i32),
+    (dx, dz): (i32, i32),
+) -> VecDeque<Pos> {
+    let read = |key: (i32, i32), edge_x: Option<usize>, edge_z: Option<usize>| {
+        let chunk = chunks.get(&key)?;
+        let mut levels = Vec::with_capacity(16 * chunk.height() as usize);
+        for y in chunk.min_y..chunk.min_y + chunk.height() {
+            for t in 0..16 {
+                let (x, z) = (edge_x.unwrap_or(t), edge_z.unwrap_or(t));
+                levels.push((y, t, chunk.light(kind, x, y, z)));
+            }
+        }
+        Some(levels)
+    };
+
+    let edge = |d: i32| match d {
+        -1 => Some(0),
+        1 => Some(15),
+        _ => None,
+    };
+    let own = read((chunk_x, chunk_z), edge(dx), edge(dz));
+    let other = read((chunk_x + dx, chunk_z + dz), edge(-dx), edge(-dz));
+    let (Some(own), Some(other)) = (own, other) else {
+        return VecDeque::new();
+    };
+
+    let position =
+        |chunk_x: i32, chunk_z: i32, edge_x: Option<usize>, edge_z: Option<usize>, y, t| {
+            (
+                chunk_x * 16 + edge_x.unwrap_or(t) as i32,
+                y,
+                chunk_z * 16 + edge_z.unwrap_or(t) as i32,
+            )
+        };
+
+    let mut queue = VecDeque::new();
+    for (&(y, t, a), &(_, _, b)) in own.iter().zip(&other) {
+        if a > b + 1 {
+            queue.push_back(position(chunk_x, chunk_z, edge(dx), edge(dz), y, t));
+        } else if b > a + 1 {
+            queue.push_back(position(
+                chunk_x + dx,
+                chunk_z + dz,
+                edge(-dx),
+                edge(-dz),
+                y,
+                t,
+            ));
+        }
+    }
+    queue
+}
+
+// Light entering a block loses its opacity, and at least one level, except
+// for full sky light going straight down through transparent blocks.
+fn next_level(kind: LightKind, level: u8, dy: i32, state: BlockState) -> u8 {
+    let opacity = state.opacity();
+    if kind == LightKind::Sky && dy == -1 && level == 15 && opacity == 0 {
+        15
+    } else {
+        level.saturating_sub(opacity.max(1))
+    }
+}
+
+fn propagate(access: &mut impl LightAccess, kind: LightKind, queue: &mut VecDeque<Pos>) {
+    while let Some(pos) = queue.pop_front() {
+        let level = access.light(kind, pos);
+        if level <= 1 {
+            continue;
+        }
+        for (dx, dy, dz) in DIRECTIONS {
+            let next = (pos.0 + dx, pos.1 + dy, pos.2 + dz);
+            let Some(state) = access.state(next) else {
+                continue;
+            };
+            let level = next_level(kind, level, dy, state);
+            if level > access.light(kind, next) {
+                access.set_light(kind, next, level);
+                queue.push_back(next);
+            }
+        }
+    }
+}
+
+// Darkens everything that was lit through the removed positions. Blocks lit
+// from elsewhere are queued so `propagate` fills the gap back in.
+fn remove(
+    access: &mut impl LightAccess,
+    kind: LightKind,
+    removal: &mut VecDeque<(Pos, u8)>,
+    queue: &mut VecDeque<Pos>,
+) {
+    while let Some((pos, level)) = removal.pop_front() {
+        for (dx, dy, dz) in DIRECTIONS {
+            let next = (pos.0 + dx, pos.1 + dy, pos.2 + dz);
+            let Some(state) = access.state(next) else {
+                continue;
+            };
+            let current = access.light(kind, next);
+            if current == 0 {
+                continue;
+            }
+
+            let straight_down = kind == LightKind::Sky && dy == -1 && level == 15;
+            if current < level || (straight_down && current == 15) {
+                access.set_light(kind, next, 0);
+                removal.push_back((next, current));
+
+                let emission = match kind {
+                    LightKind::Sky => 0,
+                    LightKind::Block => state.light_emission(),
+                };
+                if emission > 0 {
+                    access.set_light(kind, next, emission);
+                    queue.push_back(next);
+                }
+            } else {
+                queue.push_back(next);
+            }
+        }
+    }
+}
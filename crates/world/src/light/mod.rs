@@ -0,0 +1,94 @@
+use bytes::{BufMut, BytesMut};
+use protocol::{
+    serial::{PacketError, PacketWrite},
+    types::var_int::VarInt,
+};
+
+mod engine;
+
+pub use engine::{light_chunk, update_light};
+
+pub const NIBBLE_BYTES: usize = 2048;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LightKind {
+    Sky,
+    Block,
+}
+
+// 4096 light levels of one section, two per byte with the even index in the
+// low nibble. Sections with a single level, which is most of them, skip the
+// array until a level changes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NibbleArray {
+    Uniform(u8),
+    Data(Box<[u8; NIBBLE_BYTES]>),
+}
+
+impl Default for NibbleArray {
+    fn default() -> Self {
+        NibbleArray::Uniform(0)
+    }
+}
+
+impl NibbleArray {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let data: [u8; NIBBLE_BYTES] = bytes.try_into().ok()?;
+        Some(NibbleArray::Data(Box::new(data)))
+    }
+
+    pub fn get(&self, index: usize) -> u8 {
+        match self {
+            NibbleArray::Uniform(level) => *level,
+            NibbleArray::Data(data) => (data[index >> 1] >> ((index & 1) * 4)) & 0xf,
+        }
+    }
+
+    pub fn set(&mut self, index: usize, level: u8) {
+        if let NibbleArray::Uniform(current) = *self {
+            if current == level {
+                return;
+            }
+            *self = NibbleArray::Data(Box::new([current * 0x11; NIBBLE_BYTES]));
+        }
+        if let NibbleArray::Data(data) = self {
+            let shift = (index & 1) * 4;
+            let byte = &mut data[index >> 1];
+            *byte = (*byte & !(0xf << shift)) | ((level & 0xf) << shift);
+        }
+    }
+
+    pub fn fill(&mut self, level: u8) {
+        *self = NibbleArray::Uniform(level);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        matches!(self, NibbleArray::Uniform(0))
+    }
+
+    pub fn memory_usage(&self) -> usize {
+        match self {
+            NibbleArray::Uniform(_) => 0,
+            NibbleArray::Data(_) => NIBBLE_BYTES,
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        match self {
+            NibbleArray::Uniform(level) => vec![level * 0x11; NIBBLE_BYTES],
+            NibbleArray::Data(data) => data.to_vec(),
+        }
+    }
+}
+
+// Light arrays are sent as a length prefixed byte array.
+impl PacketWrite for NibbleArray {
+    fn write(&self, buffer: &mut BytesMut) -> Result<(), PacketError> {
+        VarInt(NIBBLE_BYTES as i32).write(buffer)?;
+        match self {
+            NibbleArray::Uniform(level) => buffer.put_bytes(level * 0x11, NIBBLE_BYTES),
+            NibbleArray::Data(data) => buffer.put_slice(&data[..]),
+        }
+        Ok(())
+    }
+}
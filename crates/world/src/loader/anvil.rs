@@ -0,0 +1,294 @@
+use nbt::{Compound, LongArray, Value, compound};
+
+use crate::{
+    biome,
+    block_entity::BlockEntity,
+    chunk::{Chunk, ChunkSection},
+    heightmap::HeightmapKind,
+    loader::LoaderError,
+    palette::{PalettedContainer, Strategy},
+};
+
+// 1.21.11
+pub const DATA_VERSION: i32 = 4671;
+const FULL_STATUS: &str = "minecraft:full";
+// Stored alongside a block entity's own fields.
+const BLOCK_ENTITY_KEYS: [&str; 5] = ["id", "x", "y", "z", "keepPacked"];
+
+// Translates between our numeric block state ids and the `{Name, Properties}`
+// compounds that Anvil stores in section palettes.
+pub trait StateMapper: Send + Sync {
+    fn state_id(&self, state: &Compound) -> Option<u16>;
+    fn state_nbt(&self, id: u16) -> Option<Compound>;
+}
+
+pub fn is_full(nbt: &Compound) -> bool {
+    nbt.get_str("Status")
+        .is_none_or(|status| status == FULL_STATUS || status == "full")
+}
+
+pub fn read_chunk(
+    nbt: &Compound,
+    min_y: i32,
+    height: i32,
+    mapper: &dyn StateMapper,
+) -> Result<Chunk, LoaderError> {
+    let (Some(x), Some(z)) = (nbt.get_i32("xPos"), nbt.get_i32("zPos")) else {
+        return Err(LoaderError::Corrupt(
+            "chunk is missing its position".to_string(),
+        ));
+    };
+    let mut chunk = Chunk::with_height(x, z, min_y, height);
+    let min_section = min_y >> 4;
+
+    for section in nbt.get_list_of::<&Compound>("sections").unwrap_or_default() {
+        let Some(y) = section.get_i32("Y") else {
+            continue;
+        };
+        let Some(target) = usize::try_from(y - min_section)
+            .ok()
+            .and_then(|index| chunk.sections.get_mut(index))
+        else {
+            continue;
+        };
+
+        if let Some(states) = section.get_compound("block_states") {
+            read_block_states(states, target, mapper)?;
+        }
+        if let Some(biomes) = section.get_compound("biomes") {
+            read_biomes(biomes, target)?;
+        }
+    }
+
+    for entry in nbt
+        .get_list_of::<&Compound>("block_entities")
+        .unwrap_or_default()
+    {
+        let (Some(id), Some(x), Some(y), Some(z)) = (
+            entry.get_str("id"),
+            entry.get_i32("x"),
+            entry.get_i32("y"),
+            entry.get_i32("z"),
+        ) else {
+            continue;
+        };
+        let mut data = entry.clone();
+        data.retain(|key, _| !BLOCK_ENTITY_KEYS.contains(&key));
+        let entity = BlockEntity {
+            id: id.to_string(),
+            data,
+        };
+        if !chunk.set_block_entity((x & 15) as usize, y, (z & 15) as usize, entity) {
+            log::warn!(
+                "Block entity {} at {} {} {} does not match its block, dropping it",
+                id,
+                x,
+                y,
+                z
+            );
+        }
+    }
+
+    // Recomputed rather than read so they always agree with our registry.
+    chunk.recompute_heightmaps();
+    Ok(chunk)
+}
+
+pub fn write_chunk(chunk: &Chunk, mapper: &dyn StateMapper) -> Compound {
+    let min_section = chunk.min_y >> 4;
+    let sections = chunk
+        .sections
+        .iter()
+        .enumerate()
+        .map(|(index, section)| {
+            Value::Compound(compound! {
+                "Y" => (min_section + index as i32) as i8,
+                "block_states" => write_block_states(section, mapper),
+                "biomes" => write_biomes(section),
+            })
+        })
+        .collect::<Vec<_>>();
+    let block_entities = chunk
+        .block_entities()
+        .map(|((x, y, z), entity)| {
+            let mut nbt = compound! {
+                "id" => entity.id.as_str(),
+                "x" => chunk.x * 16 + x as i32,
+                "y" => y,
+                "z" => chunk.z * 16 + z as i32,
+                "keepPacked" => false,
+            };
+            for (key, value) in entity.data.iter() {
+                nbt.insert(key.as_str(), value.clone());
+            }
+            Value::Compound(nbt)
+        })
+        .collect::<Vec<_>>();
+    let heightmaps = HeightmapKind::ALL
+        .into_iter()
+        .map(|kind| {
+            let heights = chunk.heightmaps.get(kind).pack(chunk.height());
+            (kind.name(), Value::from(LongArray(heights)))
+        })
+        .collect::<Compound>();
+
+    compound! {
+        "DataVersion" => DATA_VERSION,
+        "xPos" => chunk.x,
+        "zPos" => chunk.z,
+        "yPos" => min_section,
+        "Status" => FULL_STATUS,
+        "LastUpdate" => 0i64,
+        "sections" => sections,
+        "Heightmaps" => heightmaps,
+        "block_entities" => block_entities,
+        // Light isn't saved; both vanilla and `load_chunk` relight chunks
+        // without it.
+        "isLightOn" => false,
+    }
+}
+
+fn read_block_states(
+    nbt: &Compound,
+    section: &mut ChunkSection,
+    mapper: &dyn StateMapper,
+) -> Result<(), LoaderError> {
+    let palette = nbt
+        .get_list_of::<&Compound>("palette")
+        .ok_or_else(|| LoaderError::Corrupt("block_states has no palette".to_string()))?;
+    // Reading unknown states as air would lose them when the chunk is saved,
+    // so the chunk isn't loaded at all.
+    let palette = palette
+        .into_iter()
+        .map(|state| {
+            mapper.state_id(state).ok_or_else(|| {
+                LoaderError::UnknownBlockState(Value::Compound(state.clone()).to_snbt())
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let bits = storage_bits(palette.len(), 4);
+    let values = unpack(nbt.get_long_array("data").unwrap_or_default(), bits, 4096)?
+        .into_iter()
+        .map(|index| palette.get(index).copied().ok_or(index))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|index| LoaderError::Corrupt(format!("palette index {} out of range", index)))?;
+    section.blocks = PalettedContainer::from_values(Strategy::BLOCKS, &values);
+    section.recount();
+    Ok(())
+}
+
+fn read_biomes(nbt: &Compound, section: &mut ChunkSection) -> Result<(), LoaderError> {
+    let palette = nbt
+        .get_list_of::<&str>("palette")
+        .ok_or_else(|| LoaderError::Corrupt("biomes has no palette".to_string()))?;
+    let palette = palette
+        .into_iter()
+        .map(|name| biome::biome_id(name).unwrap_or(biome::PLAINS))
+        .collect::<Vec<_>>();
+
+    let bits = storage_bits(palette.len(), 1);
+    let values = unpack(nbt.get_long_array("data").unwrap_or_default(), bits, 64)?
+        .into_iter()
+        .map(|index| palette.get(index).map(|&id| id as u16).ok_or(index))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|index| LoaderError::Corrupt(format!("biome index {} out of range", index)))?;
+    section.biomes = PalettedContainer::from_values(Strategy::BIOMES, &values);
+    Ok(())
+}
+
+fn write_block_states(section: &ChunkSection, mapper: &dyn StateMapper) -> Compound {
+    let (palette, indices) = build_palette(section.blocks.iter());
+    let palette = palette
+        .into_iter()
+        .map(|id| {
+            Value::Compound(
+                mapper
+                    .state_nbt(id)
+                    .unwrap_or_else(|| compound! { "Name" => "minecraft:air" }),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    write_container(palette, &indices, 4)
+}
+
+fn write_biomes(section: &ChunkSection) -> Compound {
+    let (palette, indices) = build_palette(section.biomes.iter().map(|id| id as u8));
+    let palette = palette
+        .into_iter()
+        .map(|id| {
+            let name = biome::biome_name(id).unwrap_or("plains");
+            Value::String(format!("minecraft:{}", name))
+        })
+        .collect::<Vec<_>>();
+
+    write_container(palette, &indices, 1)
+}
+
+fn write_container(palette: Vec<Value>, indices: &[usize], min_bits: u32) -> Compound {
+    let bits = storage_bits(palette.len(), min_bits);
+    let mut container = compound! { "palette" => palette };
+    if bits > 0 {
+        container.insert("data", LongArray(pack(indices, bits)));
+    }
+    container
+}
+
+fn build_palette<T: PartialEq + Copy>(values: impl Iterator<Item = T>) -> (Vec<T>, Vec<usize>) {
+    let mut palette = Vec::new();
+    let indices = values
+        .map(|value| match palette.iter().position(|&v| v == value) {
+            Some(index) => index,
+            None => {
+                palette.push(value);
+                palette.len() - 1
+            }
+        })
+        .collect();
+    (palette, indices)
+}
+
+// Anvil packs palette indices with just enough bits for the palette, never
+// splitting an index across two longs. A single entry palette has no data.
+fn storage_bits(palette_len: usize, min_bits: u32) -> u32 {
+    match palette_len {
+        0 | 1 => 0,
+        len => (usize::BITS - (len - 1).leading_zeros()).max(min_bits),
+    }
+}
+
+fn unpack(data: &[i64], bits: u32, len: usize) -> Result<Vec<usize>, LoaderError> {
+    if bits == 0 {
+        return Ok(vec![0; len]);
+    }
+    let per_long = (64 / bits) as usize;
+    if data.len() < len.div_ceil(per_long) {
+        return Err(LoaderError::Corrupt(format!(
+            "{} longs cannot hold {} entries of {} bits",
+            data.len(),
+            len,
+            bits
+        )));
+    }
+
+    let mask = (1u64 << bits) - 1;
+    Ok((0..len)
+        .map(|i| {
+            let long = data[i / per_long] as u64;
+            ((long >> ((i % per_long) as u32 * bits)) & mask) as usize
+        })
+        .collect())
+}
+
+fn pack(indices: &[usize], bits: u32) -> Vec<i64> {
+    let per_long = (64 / bits) as usize;
+    indices
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk.iter().enumerate().fold(0u64, |long, (i, &index)| {
+                long | ((index as u64) << (i as u32 * bits))
+            }) as i64
+        })
+        .collect()
+}
@@ -0,0 +1,97 @@
+use std::io;
+
+use xxhash_rust::xxh32::xxh32;
+
+// Vanilla compresses LZ4 chunks with lz4-java's `LZ4BlockOutputStream`, which
+// is not the standard LZ4 frame format: every block carries a 21 byte header
+// (magic, method/level token, compressed and original sizes, and a masked
+// xxHash32 of the original bytes) and the stream ends with an empty block.
+const MAGIC: &[u8; 8] = b"LZ4Block";
+const HEADER_LEN: usize = MAGIC.len() + 13;
+const BLOCK_SIZE: usize = 64 * 1024;
+const MAX_BLOCK_SIZE: usize = 32 * 1024 * 1024;
+const METHOD_RAW: u8 = 0x10;
+const METHOD_LZ4: u8 = 0x20;
+// log2(BLOCK_SIZE) - 10, as lz4-java encodes it in the token.
+const LEVEL: u8 = 6;
+const SEED: u32 = 0x9747b28c;
+
+pub(crate) fn decompress(mut input: &[u8]) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    while !input.is_empty() {
+        if input.len() < HEADER_LEN || &input[..MAGIC.len()] != MAGIC {
+            return Err(invalid("missing LZ4Block header"));
+        }
+        let method = input[8] & 0xf0;
+        let compressed_len = read_len(&input[9..13])?;
+        let original_len = read_len(&input[13..17])?;
+        let checksum = u32::from_le_bytes(input[17..21].try_into().unwrap());
+        input = &input[HEADER_LEN..];
+
+        if original_len == 0 {
+            break;
+        }
+        if original_len > MAX_BLOCK_SIZE || compressed_len > input.len() {
+            return Err(invalid("LZ4 block is truncated or oversized"));
+        }
+
+        let (block, rest) = input.split_at(compressed_len);
+        let start = output.len();
+        match method {
+            METHOD_RAW if compressed_len == original_len => output.extend_from_slice(block),
+            METHOD_LZ4 => {
+                output.resize(start + original_len, 0);
+                let written = lz4_flex::block::decompress_into(block, &mut output[start..])
+                    .map_err(|e| invalid(&e.to_string()))?;
+                if written != original_len {
+                    return Err(invalid("LZ4 block has the wrong length"));
+                }
+            }
+            _ => return Err(invalid("unknown LZ4 block method")),
+        }
+
+        if xxh32(&output[start..], SEED) & 0x0fff_ffff != checksum {
+            return Err(invalid("LZ4 block checksum mismatch"));
+        }
+        input = rest;
+    }
+
+    Ok(output)
+}
+
+pub(crate) fn compress(input: &[u8]) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len() / 2 + HEADER_LEN * 2);
+
+    for chunk in input.chunks(BLOCK_SIZE) {
+        let compressed = lz4_flex::block::compress(chunk);
+        let (method, data) = if compressed.len() < chunk.len() {
+            (METHOD_LZ4, &compressed[..])
+        } else {
+            (METHOD_RAW, chunk)
+        };
+        let checksum = xxh32(chunk, SEED) & 0x0fff_ffff;
+        write_header(&mut output, method, data.len(), chunk.len(), checksum);
+        output.extend_from_slice(data);
+    }
+
+    write_header(&mut output, METHOD_RAW, 0, 0, 0);
+    output
+}
+
+fn write_header(output: &mut Vec<u8>, method: u8, compressed: usize, original: usize, check: u32) {
+    output.extend_from_slice(MAGIC);
+    output.push(method | LEVEL);
+    output.extend_from_slice(&(compressed as u32).to_le_bytes());
+    output.extend_from_slice(&(original as u32).to_le_bytes());
+    output.extend_from_slice(&check.to_le_bytes());
+}
+
+fn read_len(bytes: &[u8]) -> io::Result<usize> {
+    let len = i32::from_le_bytes(bytes.try_into().unwrap());
+    usize::try_from(len).map_err(|_| invalid("negative LZ4 block length"))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
@@ -0,0 +1,166 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ahash::AHashMap;
+use nbt::{Compound, Compression, NbtError, compound};
+use parking_lot::Mutex;
+
+use crate::{chunk::Chunk, loader::anvil::DATA_VERSION};
+
+pub mod anvil;
+mod lz4;
+pub mod region;
+
+pub use anvil::StateMapper;
+pub use region::{ChunkCompression, RegionFile};
+
+#[derive(thiserror::Error, Debug)]
+pub enum LoaderError {
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("NBT error: {0}")]
+    Nbt(#[from] NbtError),
+
+    #[error("Unsupported chunk compression {0}")]
+    UnsupportedCompression(u8),
+
+    #[error("Corrupt region data: {0}")]
+    Corrupt(String),
+
+    #[error("Chunk spans {1} blocks from y {0}, its dimension {3} from y {2}")]
+    WrongHeight(i32, i32, i32, i32),
+
+    #[error("Unknown block state {0}")]
+    UnknownBlockState(String),
+}
+
+// The `region` directory of one dimension. Region files are opened lazily
+// and kept open until `flush`.
+pub struct RegionStorage {
+    directory: PathBuf,
+    min_y: i32,
+    height: i32,
+    compression: ChunkCompression,
+    mapper: Arc<dyn StateMapper>,
+    regions: Mutex<AHashMap<(i32, i32), RegionFile>>,
+}
+
+impl RegionStorage {
+    pub fn new<P: AsRef<Path>>(
+        directory: P,
+        min_y: i32,
+        height: i32,
+        mapper: Arc<dyn StateMapper>,
+    ) -> Self {
+        RegionStorage {
+            directory: directory.as_ref().to_path_buf(),
+            min_y,
+            height,
+            compression: ChunkCompression::default(),
+            mapper,
+            regions: Mutex::new(AHashMap::new()),
+        }
+    }
+
+    pub fn compression(mut self, compression: ChunkCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    // Chunks vanilla has not finished generating are treated as missing.
+    pub fn load_chunk(&self, chunk_x: i32, chunk_z: i32) -> Result<Option<Chunk>, LoaderError> {
+        let Some(nbt) = self.with_region(chunk_x, chunk_z, false, |region| {
+            region.read_chunk(local(chunk_x), local(chunk_z))
+        })?
+        else {
+            return Ok(None);
+        };
+        if !anvil::is_full(&nbt) {
+            return Ok(None);
+        }
+
+        anvil::read_chunk(&nbt, self.min_y, self.height, &*self.mapper).map(Some)
+    }
+
+    pub fn save_chunk(&self, chunk: &Chunk) -> Result<(), LoaderError> {
+        let nbt = anvil::write_chunk(chunk, &*self.mapper);
+        self.with_region(chunk.x, chunk.z, true, |region| {
+            region
+                .write_chunk(local(chunk.x), local(chunk.z), &nbt, self.compression)
+                .map(Some)
+        })?;
+        Ok(())
+    }
+
+    pub fn has_chunk(&self, chunk_x: i32, chunk_z: i32) -> Result<bool, LoaderError> {
+        let found = self.with_region(chunk_x, chunk_z, false, |region| {
+            Ok(Some(region.has_chunk(local(chunk_x), local(chunk_z))))
+        })?;
+        Ok(found.unwrap_or(false))
+    }
+
+    pub fn flush(&self) -> Result<(), LoaderError> {
+        let mut regions = self.regions.lock();
+        for region in regions.values_mut() {
+            region.flush()?;
+        }
+        regions.clear();
+        Ok(())
+    }
+
+    // Saved data in the dimension's `data` directory, next to `region`, as
+    // vanilla keeps it: a gzipped file with the fields under `data`.
+    pub fn read_data(&self, name: &str) -> Result<Option<Compound>, LoaderError> {
+        let path = self.data_path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let (_, root) = nbt::read_file(path)?;
+        Ok(Some(root.get_compound("data").cloned().unwrap_or_default()))
+    }
+
+    pub fn write_data(&self, name: &str, data: Compound) -> Result<(), LoaderError> {
+        let path = self.data_path(name);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let root = compound! { "data" => data, "DataVersion" => DATA_VERSION };
+        nbt::write_file(path, "", &root, Compression::Gzip)?;
+        Ok(())
+    }
+
+    fn data_path(&self, name: &str) -> PathBuf {
+        let root = self.directory.parent().unwrap_or(&self.directory);
+        root.join("data").join(format!("{}.dat", name))
+    }
+
+    fn with_region<T>(
+        &self,
+        chunk_x: i32,
+        chunk_z: i32,
+        create: bool,
+        f: impl FnOnce(&mut RegionFile) -> Result<Option<T>, LoaderError>,
+    ) -> Result<Option<T>, LoaderError> {
+        let key = (chunk_x >> 5, chunk_z >> 5);
+        let mut regions = self.regions.lock();
+
+        if !regions.contains_key(&key) {
+            let path = self.directory.join(RegionFile::file_name(key.0, key.1));
+            if !create && !path.exists() {
+                return Ok(None);
+            }
+            fs::create_dir_all(&self.directory)?;
+            regions.insert(key, RegionFile::open(path, key.0, key.1)?);
+        }
+
+        f(regions.get_mut(&key).unwrap())
+    }
+}
+
+fn local(coord: i32) -> usize {
+    (coord & 31) as usize
+}
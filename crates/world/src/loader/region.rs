@@ -0,0 +1,365 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use flate2::{
+    Compression,
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+use nbt::{Compound, NbtReader};
+
+use crate::loader::{LoaderError, lz4};
+
+pub const SECTOR_SIZE: usize = 4096;
+const HEADER_SECTORS: usize = 2;
+const MAX_SECTORS: usize = 255;
+const EXTERNAL_FLAG: u8 = 0x80;
+
+// The most a chunk's NBT may take up once decoded, far above any real chunk
+// but low enough that a corrupt one can't exhaust memory.
+const CHUNK_MAX_BYTES: usize = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ChunkCompression {
+    Gzip = 1,
+    #[default]
+    Zlib = 2,
+    None = 3,
+    Lz4 = 4,
+}
+
+impl TryFrom<u8> for ChunkCompression {
+    type Error = LoaderError;
+
+    fn try_from(value: u8) -> Result<Self, LoaderError> {
+        match value {
+            1 => Ok(ChunkCompression::Gzip),
+            2 => Ok(ChunkCompression::Zlib),
+            3 => Ok(ChunkCompression::None),
+            4 => Ok(ChunkCompression::Lz4),
+            _ => Err(LoaderError::UnsupportedCompression(value)),
+        }
+    }
+}
+
+impl ChunkCompression {
+    fn compress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        match self {
+            ChunkCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            ChunkCompression::Zlib => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+                encoder.write_all(data)?;
+                encoder.finish()
+            }
+            ChunkCompression::None => Ok(data.to_vec()),
+            ChunkCompression::Lz4 => Ok(lz4::compress(data)),
+        }
+    }
+
+    fn decompress(self, data: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut output = Vec::new();
+        match self {
+            ChunkCompression::Gzip => _ = GzDecoder::new(data).read_to_end(&mut output)?,
+            ChunkCompression::Zlib => _ = ZlibDecoder::new(data).read_to_end(&mut output)?,
+            ChunkCompression::None => output.extend_from_slice(data),
+            ChunkCompression::Lz4 => output = lz4::decompress(data)?,
+        }
+        Ok(output)
+    }
+}
+
+// An open `r.<x>.<z>.mca` file. The first two sectors hold the location and
+// timestamp tables for the region's 32x32 chunks; each chunk occupies a run
+// of whole sectors prefixed by its length and compression type. Chunks too
+// big for 255 sectors live next to the region in `c.<x>.<z>.mcc`.
+pub struct RegionFile {
+    path: PathBuf,
+    file: File,
+    region_x: i32,
+    region_z: i32,
+    locations: [u32; 1024],
+    timestamps: [u32; 1024],
+    used: Vec<bool>,
+}
+
+impl RegionFile {
+    pub fn file_name(region_x: i32, region_z: i32) -> String {
+        format!("r.{}.{}.mca", region_x, region_z)
+    }
+
+    pub fn open<P: AsRef<Path>>(
+        path: P,
+        region_x: i32,
+        region_z: i32,
+    ) -> Result<Self, LoaderError> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)?;
+
+        let mut header = vec![0u8; SECTOR_SIZE * HEADER_SECTORS];
+        let len = file.metadata()?.len() as usize;
+        if len < header.len() {
+            file.set_len(header.len() as u64)?;
+        }
+        file.seek(SeekFrom::Start(0))?;
+        file.read_exact(&mut header)?;
+
+        let mut locations = [0u32; 1024];
+        let mut timestamps = [0u32; 1024];
+        for i in 0..1024 {
+            locations[i] = u32::from_be_bytes(header[i * 4..i * 4 + 4].try_into().unwrap());
+            let offset = SECTOR_SIZE + i * 4;
+            timestamps[i] = u32::from_be_bytes(header[offset..offset + 4].try_into().unwrap());
+        }
+
+        let total_sectors = len.max(header.len()).div_ceil(SECTOR_SIZE);
+        let mut used = vec![false; total_sectors];
+        used[..HEADER_SECTORS].fill(true);
+
+        for (index, location) in locations.iter_mut().enumerate() {
+            let (offset, count) = split_location(*location);
+            if *location == 0 {
+                continue;
+            }
+            if offset < HEADER_SECTORS || count == 0 || offset + count > total_sectors {
+                log::warn!(
+                    "Dropping chunk {} of {} with invalid location",
+                    index,
+                    path.display()
+                );
+                *location = 0;
+                continue;
+            }
+            // Sectors claimed twice would be freed with one chunk while the
+            // other still points at them, so the later chunk is dropped.
+            if used[offset..offset + count].iter().any(|&used| used) {
+                log::warn!(
+                    "Dropping chunk {} of {} overlapping another chunk",
+                    index,
+                    path.display()
+                );
+                *location = 0;
+                continue;
+            }
+            used[offset..offset + count].fill(true);
+        }
+
+        Ok(RegionFile {
+            path,
+            file,
+            region_x,
+            region_z,
+            locations,
+            timestamps,
+            used,
+        })
+    }
+
+    pub fn has_chunk(&self, local_x: usize, local_z: usize) -> bool {
+        self.locations[index(local_x, local_z)] != 0
+    }
+
+    pub fn timestamp(&self, local_x: usize, local_z: usize) -> u32 {
+        self.timestamps[index(local_x, local_z)]
+    }
+
+    pub fn read_chunk(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+    ) -> Result<Option<Compound>, LoaderError> {
+        let (offset, count) = split_location(self.locations[index(local_x, local_z)]);
+        if count == 0 {
+            return Ok(None);
+        }
+
+        let mut data = vec![0u8; count * SECTOR_SIZE];
+        self.file
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.file.read_exact(&mut data)?;
+
+        let len = u32::from_be_bytes(data[..4].try_into().unwrap()) as usize;
+        if len == 0 || len + 4 > data.len() {
+            return Err(LoaderError::Corrupt(format!(
+                "chunk {},{} has length {} in {} sectors",
+                local_x, local_z, len, count
+            )));
+        }
+
+        let kind = data[4];
+        let compression = ChunkCompression::try_from(kind & !EXTERNAL_FLAG)?;
+        let payload = if kind & EXTERNAL_FLAG != 0 {
+            fs::read(self.external_path(local_x, local_z))?
+        } else {
+            data[5..4 + len].to_vec()
+        };
+
+        let bytes = compression.decompress(&payload)?;
+        let (_, root) = NbtReader::new(&mut &bytes[..])
+            .max_bytes(CHUNK_MAX_BYTES)
+            .read_named()?;
+
+        let expected = (
+            self.region_x * 32 + local_x as i32,
+            self.region_z * 32 + local_z as i32,
+        );
+        let stored = (root.get_i32("xPos"), root.get_i32("zPos"));
+        if let (Some(x), Some(z)) = stored
+            && (x, z) != expected
+        {
+            return Err(LoaderError::Corrupt(format!(
+                "chunk {},{} is stored as chunk {},{}",
+                expected.0, expected.1, x, z
+            )));
+        }
+        Ok(Some(root))
+    }
+
+    pub fn write_chunk(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+        chunk: &Compound,
+        compression: ChunkCompression,
+    ) -> Result<(), LoaderError> {
+        let mut bytes = Vec::new();
+        nbt::write_compressed(&mut bytes, "", chunk, nbt::Compression::None)?;
+        let payload = compression.compress(&bytes)?;
+
+        let external = self.external_path(local_x, local_z);
+        let mut data = Vec::with_capacity(payload.len() + 5);
+        if payload.len() + 5 > MAX_SECTORS * SECTOR_SIZE {
+            fs::write(&external, &payload)?;
+            data.extend_from_slice(&1u32.to_be_bytes());
+            data.push(compression as u8 | EXTERNAL_FLAG);
+        } else {
+            data.extend_from_slice(&(payload.len() as u32 + 1).to_be_bytes());
+            data.push(compression as u8);
+            data.extend_from_slice(&payload);
+            if external.exists() {
+                fs::remove_file(&external)?;
+            }
+        }
+
+        let count = data.len().div_ceil(SECTOR_SIZE);
+        data.resize(count * SECTOR_SIZE, 0);
+
+        // The new copy is written before the old sectors are released so a
+        // crash mid-write leaves the previous version readable.
+        let offset = self.allocate(count);
+        self.file
+            .seek(SeekFrom::Start((offset * SECTOR_SIZE) as u64))?;
+        self.file.write_all(&data)?;
+
+        let previous = self.locations[index(local_x, local_z)];
+        self.set_header(
+            local_x,
+            local_z,
+            ((offset as u32) << 8) | count as u32,
+            now(),
+        )?;
+        self.release(previous);
+        Ok(())
+    }
+
+    pub fn remove_chunk(&mut self, local_x: usize, local_z: usize) -> Result<(), LoaderError> {
+        let previous = self.locations[index(local_x, local_z)];
+        if previous == 0 {
+            return Ok(());
+        }
+        self.set_header(local_x, local_z, 0, 0)?;
+        self.release(previous);
+
+        let external = self.external_path(local_x, local_z);
+        if external.exists() {
+            fs::remove_file(external)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> Result<(), LoaderError> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    fn set_header(
+        &mut self,
+        local_x: usize,
+        local_z: usize,
+        location: u32,
+        timestamp: u32,
+    ) -> Result<(), LoaderError> {
+        let index = index(local_x, local_z);
+        self.locations[index] = location;
+        self.timestamps[index] = timestamp;
+
+        self.file.seek(SeekFrom::Start((index * 4) as u64))?;
+        self.file.write_all(&location.to_be_bytes())?;
+        self.file
+            .seek(SeekFrom::Start((SECTOR_SIZE + index * 4) as u64))?;
+        self.file.write_all(&timestamp.to_be_bytes())?;
+        Ok(())
+    }
+
+    // First fit over the free sectors, growing the file when nothing fits.
+    fn allocate(&mut self, count: usize) -> usize {
+        let mut run = 0;
+        for sector in HEADER_SECTORS..self.used.len() {
+            if self.used[sector] {
+                run = 0;
+                continue;
+            }
+            run += 1;
+            if run == count {
+                let start = sector + 1 - count;
+                self.used[start..=sector].fill(true);
+                return start;
+            }
+        }
+
+        let start = self.used.len() - run;
+        self.used.resize(start + count, false);
+        self.used[start..].fill(true);
+        start
+    }
+
+    fn release(&mut self, location: u32) {
+        let (offset, count) = split_location(location);
+        if let Some(sectors) = self.used.get_mut(offset..offset + count) {
+            sectors.fill(false);
+        }
+    }
+
+    fn external_path(&self, local_x: usize, local_z: usize) -> PathBuf {
+        let chunk_x = self.region_x * 32 + local_x as i32;
+        let chunk_z = self.region_z * 32 + local_z as i32;
+        self.path
+            .with_file_name(format!("c.{}.{}.mcc", chunk_x, chunk_z))
+    }
+}
+
+fn index(local_x: usize, local_z: usize) -> usize {
+    (local_x & 31) + (local_z & 31) * 32
+}
+
+fn split_location(location: u32) -> (usize, usize) {
+    ((location >> 8) as usize, (location & 0xff) as usize)
+}
+
+fn now() -> u32 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs() as u32)
+}
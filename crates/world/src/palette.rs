@@ -0,0 +1,267 @@
+use bytes::{BufMut, BytesMut};
+use protocol::{
+    serial::{PacketError, PacketWrite},
+    types::var_int::VarInt,
+};
+
+// How a container of `size` entries picks its representation: a single value
+// with no data, an indirect palette of `min_bits..=max_indirect_bits` bits per
+// index, or global ids stored directly with `direct_bits` each.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strategy {
+    pub size: usize,
+    pub min_bits: u32,
+    pub max_indirect_bits: u32,
+    pub direct_bits: u32,
+}
+
+impl Strategy {
+    pub const BLOCKS: Strategy = Strategy {
+        size: 4096,
+        min_bits: 4,
+        max_indirect_bits: 8,
+        direct_bits: 15,
+    };
+
+    pub const BIOMES: Strategy = Strategy {
+        size: 64,
+        min_bits: 1,
+        max_indirect_bits: 3,
+        direct_bits: 7,
+    };
+}
+
+#[derive(Debug, Clone)]
+pub enum Palette {
+    Single(u16),
+    Indirect(Vec<u16>),
+    Direct,
+}
+
+// Entries never straddle two longs, matching the vanilla layout on the wire.
+#[derive(Debug, Clone)]
+pub struct PalettedContainer {
+    strategy: Strategy,
+    palette: Palette,
+    bits: u32,
+    data: Vec<u64>,
+}
+
+impl PalettedContainer {
+    pub fn new(strategy: Strategy, value: u16) -> Self {
+        PalettedContainer {
+            strategy,
+            palette: Palette::Single(value),
+            bits: 0,
+            data: Vec::new(),
+        }
+    }
+
+    pub fn from_values(strategy: Strategy, values: &[u16]) -> Self {
+        assert_eq!(values.len(), strategy.size, "container size mismatch");
+
+        let mut distinct = values.to_vec();
+        distinct.sort_unstable();
+        distinct.dedup();
+
+        let mut container = match distinct.len() {
+            0 | 1 => {
+                return PalettedContainer::new(strategy, values.first().copied().unwrap_or(0));
+            }
+            len => {
+                let bits = bits_for(len).max(strategy.min_bits);
+                if bits > strategy.max_indirect_bits {
+                    PalettedContainer::with_palette(strategy, Palette::Direct, strategy.direct_bits)
+                } else {
+                    PalettedContainer::with_palette(strategy, Palette::Indirect(distinct), bits)
+                }
+            }
+        };
+
+        for (index, &value) in values.iter().enumerate() {
+            let id = container.palette_index(value).unwrap();
+            container.write_index(index, id);
+        }
+        container
+    }
+
+    fn with_palette(strategy: Strategy, palette: Palette, bits: u32) -> Self {
+        let per_long = (64 / bits) as usize;
+        PalettedContainer {
+            strategy,
+            palette,
+            bits,
+            data: vec![0; strategy.size.div_ceil(per_long)],
+        }
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+
+    pub fn palette(&self) -> &Palette {
+        &self.palette
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    pub fn len(&self) -> usize {
+        self.strategy.size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strategy.size == 0
+    }
+
+    pub fn get(&self, index: usize) -> u16 {
+        match &self.palette {
+            Palette::Single(value) => *value,
+            Palette::Indirect(palette) => palette[self.read_index(index)],
+            Palette::Direct => self.read_index(index) as u16,
+        }
+    }
+
+    pub fn set(&mut self, index: usize, value: u16) -> u16 {
+        let old = self.get(index);
+        if old == value {
+            return old;
+        }
+
+        let id = match self.palette_index(value) {
+            Some(id) => id,
+            None => self.grow(value),
+        };
+        self.write_index(index, id);
+        old
+    }
+
+    pub fn fill(&mut self, value: u16) {
+        *self = PalettedContainer::new(self.strategy, value);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        (0..self.strategy.size).map(|index| self.get(index))
+    }
+
+    pub fn count(&self, mut predicate: impl FnMut(u16) -> bool) -> usize {
+        match &self.palette {
+            Palette::Single(value) if predicate(*value) => self.strategy.size,
+            Palette::Single(_) => 0,
+            _ => self.iter().filter(|&value| predicate(value)).count(),
+        }
+    }
+
+    // Heap bytes held by the palette and the packed data.
+    pub fn memory_usage(&self) -> usize {
+        let palette = match &self.palette {
+            Palette::Indirect(values) => values.capacity() * size_of::<u16>(),
+            _ => 0,
+        };
+        palette + self.data.capacity() * size_of::<u64>()
+    }
+
+    // Cheap check against the palette, which may still hold values that were
+    // since overwritten, so a match only means the value might be present.
+    pub fn may_contain(&self, mut predicate: impl FnMut(u16) -> bool) -> bool {
+        match &self.palette {
+            Palette::Single(value) => predicate(*value),
+            Palette::Indirect(palette) => palette.iter().any(|&value| predicate(value)),
+            Palette::Direct => self.iter().any(predicate),
+        }
+    }
+
+    fn palette_index(&self, value: u16) -> Option<usize> {
+        match &self.palette {
+            Palette::Single(single) => (*single == value).then_some(0),
+            Palette::Indirect(palette) => palette.iter().position(|&v| v == value),
+            Palette::Direct => Some(value as usize),
+        }
+    }
+
+    // Adds `value` to the palette, widening or going direct when it is full,
+    // and returns its index.
+    fn grow(&mut self, value: u16) -> usize {
+        let mut palette = match &self.palette {
+            Palette::Single(single) => vec![*single],
+            Palette::Indirect(palette) => palette.clone(),
+            Palette::Direct => unreachable!("direct containers hold every value"),
+        };
+        palette.push(value);
+
+        let bits = bits_for(palette.len()).max(self.strategy.min_bits);
+        if bits == self.bits && matches!(self.palette, Palette::Indirect(_)) {
+            self.palette = Palette::Indirect(palette);
+            return self.palette_index(value).unwrap();
+        }
+
+        let values: Vec<u16> = self.iter().collect();
+        *self = if bits > self.strategy.max_indirect_bits {
+            PalettedContainer::with_palette(
+                self.strategy,
+                Palette::Direct,
+                self.strategy.direct_bits,
+            )
+        } else {
+            PalettedContainer::with_palette(self.strategy, Palette::Indirect(palette), bits)
+        };
+        for (index, value) in values.into_iter().enumerate() {
+            let id = self.palette_index(value).unwrap();
+            self.write_index(index, id);
+        }
+        self.palette_index(value).unwrap()
+    }
+
+    fn read_index(&self, index: usize) -> usize {
+        let per_long = (64 / self.bits) as usize;
+        let shift = (index % per_long) as u32 * self.bits;
+        ((self.data[index / per_long] >> shift) & self.mask()) as usize
+    }
+
+    fn write_index(&mut self, index: usize, id: usize) {
+        if self.bits == 0 {
+            return;
+        }
+        let per_long = (64 / self.bits) as usize;
+        let shift = (index % per_long) as u32 * self.bits;
+        let mask = self.mask() << shift;
+        let long = &mut self.data[index / per_long];
+        *long = (*long & !mask) | ((id as u64) << shift);
+    }
+
+    fn mask(&self) -> u64 {
+        (1u64 << self.bits) - 1
+    }
+}
+
+impl PartialEq for PalettedContainer {
+    fn eq(&self, other: &Self) -> bool {
+        self.strategy == other.strategy && self.iter().eq(other.iter())
+    }
+}
+
+impl PacketWrite for PalettedContainer {
+    fn write(&self, buffer: &mut BytesMut) -> Result<(), PacketError> {
+        buffer.put_u8(self.bits as u8);
+        match &self.palette {
+            Palette::Single(value) => VarInt(*value as i32).write(buffer)?,
+            Palette::Indirect(palette) => {
+                VarInt(palette.len() as i32).write(buffer)?;
+                for &value in palette {
+                    VarInt(value as i32).write(buffer)?;
+                }
+            }
+            Palette::Direct => {}
+        }
+        // The long count is implied by the size and bits since 1.21.5.
+        for &long in &self.data {
+            buffer.put_u64(long);
+        }
+        Ok(())
+    }
+}
+
+fn bits_for(len: usize) -> u32 {
+    usize::BITS - (len.max(1) - 1).leading_zeros()
+}
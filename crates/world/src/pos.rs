@@ -0,0 +1,145 @@
+use std::fmt;
+
+// A block position in world coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct BlockPos {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl BlockPos {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        BlockPos { x, y, z }
+    }
+
+    pub fn offset(self, direction: Direction) -> BlockPos {
+        let (dx, dy, dz) = direction.offset();
+        BlockPos::new(self.x + dx, self.y + dy, self.z + dz)
+    }
+
+    pub fn chunk(self) -> (i32, i32) {
+        (self.x >> 4, self.z >> 4)
+    }
+
+    // The section's y, counted in sections from y 0 like the client does.
+    pub fn section_y(self) -> i32 {
+        self.y >> 4
+    }
+
+    // `x` and `z` within the chunk, as `Chunk::set_block` takes them.
+    pub fn local(self) -> (usize, usize) {
+        ((self.x & 15) as usize, (self.z & 15) as usize)
+    }
+}
+
+impl fmt::Display for BlockPos {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.x, self.y, self.z)
+    }
+}
+
+// Ordered like vanilla's `Direction`, whose 3D data value this is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Down,
+    Up,
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 6] = [
+        Direction::Down,
+        Direction::Up,
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    pub const HORIZONTAL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+
+    pub fn offset(self) -> (i32, i32, i32) {
+        match self {
+            Direction::Down => (0, -1, 0),
+            Direction::Up => (0, 1, 0),
+            Direction::North => (0, 0, -1),
+            Direction::South => (0, 0, 1),
+            Direction::West => (-1, 0, 0),
+            Direction::East => (1, 0, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Direction {
+        match self {
+            Direction::Down => Direction::Up,
+            Direction::Up => Direction::Down,
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+
+    // Seen from above. Vertical directions stay as they are.
+    pub fn clockwise(self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+            vertical => vertical,
+        }
+    }
+
+    pub fn counter_clockwise(self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::West => Direction::South,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+            vertical => vertical,
+        }
+    }
+
+    pub fn is_horizontal(self) -> bool {
+        !matches!(self, Direction::Down | Direction::Up)
+    }
+
+    // Whether both lie along the same axis.
+    pub fn is_parallel(self, other: Direction) -> bool {
+        self == other || self == other.opposite()
+    }
+
+    // The name block state properties use, `north` for `facing=north`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Direction::Down => "down",
+            Direction::Up => "up",
+            Direction::North => "north",
+            Direction::South => "south",
+            Direction::West => "west",
+            Direction::East => "east",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Direction> {
+        Direction::ALL
+            .into_iter()
+            .find(|direction| direction.name() == name)
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,618 @@
+use std::path::Path;
+
+use ahash::AHashMap;
+use nbt::{Compound, Compression, IntArray, NbtError, Value, compound};
+
+use crate::{
+    Dimension,
+    block::{BlockRegistry, BlockState},
+    block_entity::BlockEntity,
+    edit::Cuboid,
+    loader::{LoaderError, StateMapper, anvil::DATA_VERSION},
+    pos::BlockPos,
+    transform::{Mirror, Rotation, Transform},
+    update::BlockFlags,
+};
+
+// The most blocks a structure file or a copy may span, several times what
+// structure blocks can save.
+const MAX_VOLUME: usize = 16 * 1024 * 1024;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SchematicError {
+    #[error("NBT error: {0}")]
+    Nbt(#[from] NbtError),
+
+    #[error("Loader error: {0}")]
+    Loader(#[from] LoaderError),
+
+    #[error("Unsupported schematic version {0}")]
+    UnsupportedVersion(i32),
+
+    #[error("Schematic is missing {0}")]
+    Missing(&'static str),
+
+    #[error("Malformed schematic: {0}")]
+    Malformed(String),
+
+    #[error("Region of {0}x{1}x{2} is too large for a schematic")]
+    TooLarge(i32, i32, i32),
+}
+
+// Positions are relative to the schematic's minimum corner. Data holds the
+// remaining fields, without the id or position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchematicBlockEntity {
+    pub pos: BlockPos,
+    pub id: String,
+    pub data: Compound,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchematicEntity {
+    pub pos: [f64; 3],
+    pub id: String,
+    pub data: Compound,
+}
+
+// A box of blocks with the block entities and entities inside, read from
+// Sponge schematics (`.schem`, versions 1 to 3) or vanilla structure files
+// (`.nbt`). Blocks missing from our registry become air, as in vanilla.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schematic {
+    pub width: u16,
+    pub height: u16,
+    pub length: u16,
+    // Where the minimum corner goes relative to the paste position, like
+    // WorldEdit's offset from the copy origin.
+    pub offset: BlockPos,
+    // Indexed by `(y * length + z) * width + x`. `None` leaves the world as
+    // it is, like a structure void.
+    blocks: Vec<Option<BlockState>>,
+    pub block_entities: Vec<SchematicBlockEntity>,
+    pub entities: Vec<SchematicEntity>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasteOptions {
+    pub transform: Transform,
+    pub ignore_air: bool,
+    pub flags: BlockFlags,
+}
+
+// What a paste placed. Entities, in world coordinates, are left for the
+// caller to spawn.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PasteResult {
+    pub blocks: usize,
+    pub block_entities: usize,
+    pub entities: Vec<SchematicEntity>,
+}
+
+impl Default for PasteOptions {
+    fn default() -> Self {
+        PasteOptions::new()
+    }
+}
+
+// Pastes go to clients but leave neighbors and ticks alone, so a schematic
+// keeps the exact states it was saved with.
+impl PasteOptions {
+    pub fn new() -> Self {
+        PasteOptions {
+            transform: Transform::default(),
+            ignore_air: false,
+            flags: BlockFlags::CLIENTS,
+        }
+    }
+
+    pub fn rotation(mut self, rotation: Rotation) -> Self {
+        self.transform.rotation = rotation;
+        self
+    }
+
+    pub fn mirror(mut self, mirror: Mirror) -> Self {
+        self.transform.mirror = mirror;
+        self
+    }
+
+    // Air in the schematic keeps what is already in the world.
+    pub fn ignore_air(mut self, ignore_air: bool) -> Self {
+        self.ignore_air = ignore_air;
+        self
+    }
+
+    pub fn flags(mut self, flags: BlockFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+}
+
+impl Schematic {
+    // Filled with air.
+    pub fn new(width: u16, height: u16, length: u16) -> Self {
+        let volume = width as usize * height as usize * length as usize;
+        Schematic {
+            width,
+            height,
+            length,
+            offset: BlockPos::default(),
+            blocks: vec![Some(BlockState::AIR); volume],
+            block_entities: Vec::new(),
+            entities: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, pos: BlockPos) -> Option<BlockState> {
+        self.index(pos).and_then(|index| self.blocks[index])
+    }
+
+    pub fn set(&mut self, pos: BlockPos, state: Option<BlockState>) {
+        if let Some(index) = self.index(pos) {
+            self.blocks[index] = state;
+        }
+    }
+
+    // Every position with a block, in storage order.
+    pub fn blocks(&self) -> impl Iterator<Item = (BlockPos, BlockState)> + '_ {
+        let (width, length) = (self.width as i32, self.length as i32);
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(move |(index, state)| {
+                let index = index as i32;
+                let pos = BlockPos::new(
+                    index % width,
+                    index / (width * length),
+                    index / width % length,
+                );
+                state.map(|state| (pos, state))
+            })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Schematic, SchematicError> {
+        let (_, root) = nbt::read_file(path)?;
+        Schematic::from_nbt(&root)
+    }
+
+    // Writes a gzipped Sponge schematic, version 3.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SchematicError> {
+        nbt::write_file(path, "", &self.to_sponge(), Compression::Gzip)?;
+        Ok(())
+    }
+
+    // Tells the formats apart by their contents.
+    pub fn from_nbt(root: &Compound) -> Result<Schematic, SchematicError> {
+        if let Some(schematic) = root.get_compound("Schematic") {
+            Schematic::from_sponge(schematic)
+        } else if root.contains_key("Palette") || root.contains_key("BlockData") {
+            Schematic::from_sponge(root)
+        } else if root.contains_key("blocks") {
+            Schematic::from_structure(root)
+        } else {
+            Err(SchematicError::Malformed(
+                "neither a Sponge schematic nor a structure".to_string(),
+            ))
+        }
+    }
+
+    pub fn from_sponge(nbt: &Compound) -> Result<Schematic, SchematicError> {
+        let version = nbt
+            .get_i32("Version")
+            .ok_or(SchematicError::Missing("Version"))?;
+        if !(1..=3).contains(&version) {
+            return Err(SchematicError::UnsupportedVersion(version));
+        }
+        let dimension = |key| {
+            nbt.get_i16(key)
+                .map(|value| value as u16)
+                .ok_or(SchematicError::Missing(key))
+        };
+        let (width, height, length) = (
+            dimension("Width")?,
+            dimension("Height")?,
+            dimension("Length")?,
+        );
+
+        // Version 3 moved the blocks into their own compound and the extra
+        // fields of block entities and entities into `Data`.
+        let blocks = if version == 3 {
+            nbt.get_compound("Blocks")
+                .ok_or(SchematicError::Missing("Blocks"))?
+        } else {
+            nbt
+        };
+        let palette = blocks
+            .get_compound("Palette")
+            .ok_or(SchematicError::Missing("Palette"))?;
+        let data_key = if version == 3 { "Data" } else { "BlockData" };
+        let data = blocks
+            .get_byte_array(data_key)
+            .ok_or(SchematicError::Missing("BlockData"))?;
+
+        // Every block takes at least a byte, so the header can't claim more
+        // blocks than the data holds.
+        if volume(width as i32, height as i32, length as i32) > data.len() {
+            return Err(SchematicError::Malformed(
+                "block data ends early".to_string(),
+            ));
+        }
+        let mut schematic = Schematic::new(width, height, length);
+        if let Some(&[x, y, z]) = nbt.get_int_array("Offset") {
+            schematic.offset = BlockPos::new(x, y, z);
+        }
+        schematic.read_sponge_blocks(palette, data)?;
+
+        let entities_key = if version == 1 {
+            "TileEntities"
+        } else {
+            "BlockEntities"
+        };
+        for entry in blocks
+            .get_list_of::<&Compound>(entities_key)
+            .unwrap_or_default()
+        {
+            let Some(&[x, y, z]) = entry.get_int_array("Pos") else {
+                return Err(SchematicError::Missing("block entity Pos"));
+            };
+            schematic.block_entities.push(SchematicBlockEntity {
+                pos: BlockPos::new(x, y, z),
+                id: entry.get_str("Id").unwrap_or_default().to_string(),
+                data: sponge_data(entry, version),
+            });
+        }
+
+        for entry in nbt.get_list_of::<&Compound>("Entities").unwrap_or_default() {
+            let pos = match entry.get_list_of::<f64>("Pos").as_deref() {
+                Some(&[x, y, z]) => [x, y, z],
+                _ => return Err(SchematicError::Missing("entity Pos")),
+            };
+            schematic.entities.push(SchematicEntity {
+                pos,
+                id: entry.get_str("Id").unwrap_or_default().to_string(),
+                data: sponge_data(entry, version),
+            });
+        }
+        Ok(schematic)
+    }
+
+    // Vanilla's structure block format. With several palettes, as
+    // shipwrecks have, the first is used.
+    pub fn from_structure(nbt: &Compound) -> Result<Schematic, SchematicError> {
+        let size = match nbt.get_list_of::<i32>("size").as_deref() {
+            Some(&[x, y, z]) => [x, y, z],
+            _ => return Err(SchematicError::Missing("size")),
+        };
+        // Air may be left out of `blocks`, so only a limit bounds the size.
+        if size
+            .iter()
+            .any(|side| !(0..=u16::MAX as i32).contains(side))
+            || volume(size[0], size[1], size[2]) > MAX_VOLUME
+        {
+            return Err(SchematicError::TooLarge(size[0], size[1], size[2]));
+        }
+        let mut schematic = Schematic::new(size[0] as u16, size[1] as u16, size[2] as u16);
+        schematic.blocks.fill(None);
+
+        let palette = nbt
+            .get_list_of::<&Compound>("palette")
+            .or_else(|| {
+                nbt.get_list("palettes")?
+                    .first()?
+                    .as_list()?
+                    .iter()
+                    .map(Value::as_compound)
+                    .collect()
+            })
+            .ok_or(SchematicError::Missing("palette"))?
+            .into_iter()
+            .map(|state| {
+                BlockRegistry
+                    .state_id(state)
+                    .and_then(BlockState::from_id)
+                    .unwrap_or_else(|| {
+                        log::warn!(
+                            "Unknown block {} in structure, replacing with air",
+                            state.get_str("Name").unwrap_or_default()
+                        );
+                        BlockState::AIR
+                    })
+            })
+            .collect::<Vec<_>>();
+
+        for block in nbt.get_list_of::<&Compound>("blocks").unwrap_or_default() {
+            let pos = match block.get_list_of::<i32>("pos").as_deref() {
+                Some(&[x, y, z]) => BlockPos::new(x, y, z),
+                _ => return Err(SchematicError::Missing("block pos")),
+            };
+            let state = block
+                .get_i32("state")
+                .and_then(|index| palette.get(usize::try_from(index).ok()?))
+                .ok_or_else(|| {
+                    SchematicError::Malformed(format!("bad palette index at {}", pos))
+                })?;
+            schematic.set(pos, Some(*state));
+            if let Some(data) = block.get_compound("nbt") {
+                let mut data = data.clone();
+                let id = take_string(&mut data, "id");
+                schematic
+                    .block_entities
+                    .push(SchematicBlockEntity { pos, id, data });
+            }
+        }
+
+        for entity in nbt.get_list_of::<&Compound>("entities").unwrap_or_default() {
+            let pos = match entity.get_list_of::<f64>("pos").as_deref() {
+                Some(&[x, y, z]) => [x, y, z],
+                _ => return Err(SchematicError::Missing("entity pos")),
+            };
+            let mut data = entity.get_compound("nbt").cloned().unwrap_or_default();
+            let id = take_string(&mut data, "id");
+            schematic.entities.push(SchematicEntity { pos, id, data });
+        }
+        Ok(schematic)
+    }
+
+    pub fn to_sponge(&self) -> Compound {
+        let mut palette = Compound::new();
+        let mut ids = AHashMap::new();
+        let mut data = Vec::with_capacity(self.blocks.len());
+        for state in &self.blocks {
+            let state = state.unwrap_or(BlockState::AIR);
+            let next = ids.len() as i32;
+            let id = *ids.entry(state).or_insert_with(|| {
+                palette.insert(state.to_string(), next);
+                next
+            });
+            write_var_int(&mut data, id as u32);
+        }
+
+        let block_entities = self
+            .block_entities
+            .iter()
+            .map(|entity| {
+                let pos = entity.pos;
+                Value::Compound(compound! {
+                    "Pos" => IntArray(vec![pos.x, pos.y, pos.z]),
+                    "Id" => entity.id.as_str(),
+                    "Data" => entity.data.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+        let entities = self
+            .entities
+            .iter()
+            .map(|entity| {
+                Value::Compound(compound! {
+                    "Pos" => nbt::list![entity.pos[0], entity.pos[1], entity.pos[2]],
+                    "Id" => entity.id.as_str(),
+                    "Data" => entity.data.clone(),
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let offset = self.offset;
+        compound! {
+            "Schematic" => compound! {
+                "Version" => 3,
+                "DataVersion" => DATA_VERSION,
+                "Width" => self.width as i16,
+                "Height" => self.height as i16,
+                "Length" => self.length as i16,
+                "Offset" => IntArray(vec![offset.x, offset.y, offset.z]),
+                "Blocks" => compound! {
+                    "Palette" => palette,
+                    "Data" => Value::ByteArray(data),
+                    "BlockEntities" => block_entities,
+                },
+                "Entities" => entities,
+            },
+        }
+    }
+
+    // Copies the box between two corners with its block entities, loading
+    // chunks as needed.
+    pub fn copy(
+        dimension: &Dimension,
+        from: BlockPos,
+        to: BlockPos,
+    ) -> Result<Schematic, SchematicError> {
+        let region = Cuboid::new(from, to);
+        let [width, height, length] = region.size();
+        if region.size().iter().any(|side| *side > u16::MAX as i32)
+            || region.volume() > MAX_VOLUME as u64
+        {
+            return Err(SchematicError::TooLarge(width, height, length));
+        }
+
+        let clipboard = dimension.copy(region)?;
+        let mut schematic = Schematic::new(width as u16, height as u16, length as u16);
+        for y in 0..height {
+            for z in 0..length {
+                for x in 0..width {
+                    let pos = BlockPos::new(x, y, z);
+                    schematic.set(pos, Some(clipboard.get(pos)));
+                }
+            }
+        }
+        for (pos, entity) in clipboard.block_entities() {
+            schematic.block_entities.push(SchematicBlockEntity {
+                pos,
+                id: entity.id.clone(),
+                data: entity.data.clone(),
+            });
+        }
+        Ok(schematic)
+    }
+
+    // Places the schematic with its offset at `at`. The transform turns it
+    // around `at`.
+    pub fn paste(
+        &self,
+        dimension: &Dimension,
+        at: BlockPos,
+        options: &PasteOptions,
+    ) -> Result<PasteResult, SchematicError> {
+        let transform = options.transform;
+        let place = |pos: BlockPos| {
+            let relative = transform.pos(BlockPos::new(
+                self.offset.x + pos.x,
+                self.offset.y + pos.y,
+                self.offset.z + pos.z,
+            ));
+            BlockPos::new(at.x + relative.x, at.y + relative.y, at.z + relative.z)
+        };
+
+        let mut result = PasteResult::default();
+        for (pos, state) in self.blocks() {
+            if options.ignore_air && state.is_air() {
+                continue;
+            }
+            dimension.set_block(place(pos), transform.state(state), options.flags)?;
+            result.blocks += 1;
+        }
+
+        for entity in &self.block_entities {
+            let block_entity = BlockEntity {
+                id: entity.id.clone(),
+                data: entity.data.clone(),
+            };
+            if dimension.set_block_entity(place(entity.pos), block_entity)? {
+                result.block_entities += 1;
+            }
+        }
+        result.entities = self
+            .entities
+            .iter()
+            .map(|entity| {
+                let offset = self.offset;
+                let [x, y, z] = transform.point([
+                    offset.x as f64 + entity.pos[0],
+                    offset.y as f64 + entity.pos[1],
+                    offset.z as f64 + entity.pos[2],
+                ]);
+                let mut data = entity.data.clone();
+                if let Some(Value::List(rotation)) = data.get_mut("Rotation")
+                    && let Some(Value::Float(yaw)) = rotation.first_mut()
+                {
+                    *yaw = transform.yaw(*yaw);
+                }
+                SchematicEntity {
+                    pos: [at.x as f64 + x, at.y as f64 + y, at.z as f64 + z],
+                    id: entity.id.clone(),
+                    data,
+                }
+            })
+            .collect();
+        Ok(result)
+    }
+
+    fn index(&self, pos: BlockPos) -> Option<usize> {
+        let (width, height, length) = (self.width as i32, self.height as i32, self.length as i32);
+        if !(0..width).contains(&pos.x)
+            || !(0..height).contains(&pos.y)
+            || !(0..length).contains(&pos.z)
+        {
+            return None;
+        }
+        Some(((pos.y * length + pos.z) * width + pos.x) as usize)
+    }
+
+    fn read_sponge_blocks(
+        &mut self,
+        palette: &Compound,
+        data: &[u8],
+    ) -> Result<(), SchematicError> {
+        let mut states = Vec::new();
+        for (key, id) in palette.iter() {
+            let id = id
+                .as_i32()
+                .and_then(|id| usize::try_from(id).ok())
+                .filter(|&id| id < palette.len())
+                .ok_or_else(|| SchematicError::Malformed(format!("bad palette id for {}", key)))?;
+            if states.len() <= id {
+                states.resize(id + 1, BlockState::AIR);
+            }
+            states[id] = parse_state(key);
+        }
+
+        let mut data = data;
+        for block in &mut self.blocks {
+            let id = read_var_int(&mut data)
+                .ok_or_else(|| SchematicError::Malformed("block data ends early".to_string()))?;
+            let state = states.get(id as usize).ok_or_else(|| {
+                SchematicError::Malformed(format!("palette id {} out of range", id))
+            })?;
+            *block = Some(*state);
+        }
+        Ok(())
+    }
+}
+
+fn volume(width: i32, height: i32, length: i32) -> usize {
+    width.max(0) as usize * height.max(0) as usize * length.max(0) as usize
+}
+
+// Sponge palettes hold states as strings. Going through the Anvil mapper
+// keeps defaults for properties we don't know, as vanilla does.
+fn parse_state(key: &str) -> BlockState {
+    let (name, properties) = match key.split_once('[') {
+        Some((name, rest)) => (name, rest.trim_end_matches(']')),
+        None => (key, ""),
+    };
+    let properties = properties
+        .split(',')
+        .filter_map(|property| property.split_once('='))
+        .map(|(key, value)| (key.trim(), Value::from(value.trim())))
+        .collect::<Compound>();
+    let state = compound! { "Name" => name.trim(), "Properties" => properties };
+    BlockRegistry
+        .state_id(&state)
+        .and_then(BlockState::from_id)
+        .unwrap_or_else(|| {
+            log::warn!("Unknown block {} in schematic, replacing with air", key);
+            BlockState::AIR
+        })
+}
+
+fn sponge_data(entry: &Compound, version: i32) -> Compound {
+    if version == 3 {
+        return entry.get_compound("Data").cloned().unwrap_or_default();
+    }
+    let mut data = entry.clone();
+    data.remove("Pos");
+    data.remove("Id");
+    data
+}
+
+fn take_string(compound: &mut Compound, key: &str) -> String {
+    match compound.remove(key) {
+        Some(Value::String(value)) => value,
+        _ => String::new(),
+    }
+}
+
+fn read_var_int(data: &mut &[u8]) -> Option<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let (&byte, rest) = data.split_first()?;
+        *data = rest;
+        value |= ((byte & 0x7F) as u32) << shift;
+        if byte & 0x80 == 0 {
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn write_var_int(data: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+        if value == 0 {
+            data.push(byte);
+            return;
+        }
+        data.push(byte | 0x80);
+    }
+}
@@ -0,0 +1,170 @@
+use ahash::AHashMap;
+
+// Why a ticket keeps chunks loaded. Plugin tickets usually come with a
+// lifetime; the others last until removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TicketKind {
+    Player,
+    Spawn,
+    Forced,
+    Plugin,
+}
+
+// Keeps the chunks within `radius` (a square, like view distance) of the
+// center loaded. A lifetime, in ticks, removes it automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ticket {
+    pub kind: TicketKind,
+    pub chunk_x: i32,
+    pub chunk_z: i32,
+    pub radius: u32,
+    pub lifetime: Option<u64>,
+}
+
+impl Ticket {
+    pub fn new(kind: TicketKind, chunk_x: i32, chunk_z: i32, radius: u32) -> Self {
+        Ticket {
+            kind,
+            chunk_x,
+            chunk_z,
+            radius,
+            lifetime: None,
+        }
+    }
+
+    pub fn player(chunk_x: i32, chunk_z: i32, view_distance: u32) -> Self {
+        Ticket::new(TicketKind::Player, chunk_x, chunk_z, view_distance)
+    }
+
+    pub fn spawn(chunk_x: i32, chunk_z: i32, radius: u32) -> Self {
+        Ticket::new(TicketKind::Spawn, chunk_x, chunk_z, radius)
+    }
+
+    // A single chunk, like `/forceload`.
+    pub fn forced(chunk_x: i32, chunk_z: i32) -> Self {
+        Ticket::new(TicketKind::Forced, chunk_x, chunk_z, 0)
+    }
+
+    pub fn plugin(chunk_x: i32, chunk_z: i32, radius: u32, lifetime: u64) -> Self {
+        Ticket::new(TicketKind::Plugin, chunk_x, chunk_z, radius).with_lifetime(lifetime)
+    }
+
+    pub fn with_lifetime(mut self, ticks: u64) -> Self {
+        self.lifetime = Some(ticks);
+        self
+    }
+
+    pub fn contains(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        (chunk_x - self.chunk_x).unsigned_abs() <= self.radius
+            && (chunk_z - self.chunk_z).unsigned_abs() <= self.radius
+    }
+
+    pub fn chunks(&self) -> impl Iterator<Item = (i32, i32)> + use<> {
+        let (center_x, center_z, radius) = (self.chunk_x, self.chunk_z, self.radius as i32);
+        (center_z - radius..=center_z + radius)
+            .flat_map(move |z| (center_x - radius..=center_x + radius).map(move |x| (x, z)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TicketId(u64);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkStats {
+    pub loaded: usize,
+    // Loaded chunks held by at least one ticket.
+    pub ticketed: usize,
+    pub tickets: usize,
+    // Loaded chunks no ticket holds, waiting to be saved and evicted.
+    pub idle: usize,
+    pub memory: usize,
+    pub limit: Option<usize>,
+}
+
+// How many tickets hold each chunk, and since when the chunks nobody holds
+// have been idle.
+#[derive(Debug, Default)]
+pub(crate) struct Tickets {
+    pub(crate) tick: u64,
+    next_id: u64,
+    tickets: AHashMap<TicketId, (Ticket, Option<u64>)>,
+    references: AHashMap<(i32, i32), u32>,
+    pub(crate) idle: AHashMap<(i32, i32), u64>,
+}
+
+impl Tickets {
+    pub(crate) fn add(&mut self, ticket: Ticket) -> TicketId {
+        let id = TicketId(self.next_id);
+        self.next_id += 1;
+        let expiry = ticket.lifetime.map(|lifetime| self.tick + lifetime);
+        self.tickets.insert(id, (ticket, expiry));
+        self.reference(&ticket);
+        id
+    }
+
+    pub(crate) fn get(&self, id: TicketId) -> Option<Ticket> {
+        self.tickets.get(&id).map(|(ticket, _)| *ticket)
+    }
+
+    pub(crate) fn remove(&mut self, id: TicketId) -> Option<Ticket> {
+        let (ticket, _) = self.tickets.remove(&id)?;
+        self.release(&ticket);
+        Some(ticket)
+    }
+
+    // The new area is referenced before the old one is released so chunks in
+    // both never look unused.
+    pub(crate) fn replace(&mut self, id: TicketId, ticket: Ticket) -> Option<Ticket> {
+        let (old, expiry) = self.tickets.get_mut(&id)?;
+        let old = std::mem::replace(old, ticket);
+        if ticket.lifetime != old.lifetime {
+            *expiry = ticket.lifetime.map(|lifetime| self.tick + lifetime);
+        }
+        self.reference(&ticket);
+        self.release(&old);
+        Some(old)
+    }
+
+    // Advances a tick and drops the tickets whose lifetime ran out.
+    pub(crate) fn tick(&mut self) -> usize {
+        self.tick += 1;
+        let expired = self
+            .tickets
+            .iter()
+            .filter(|(_, (_, expiry))| expiry.is_some_and(|expiry| expiry <= self.tick))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in &expired {
+            self.remove(*id);
+        }
+        expired.len()
+    }
+
+    pub(crate) fn is_referenced(&self, chunk: (i32, i32)) -> bool {
+        self.references.contains_key(&chunk)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.tickets.len()
+    }
+
+    fn reference(&mut self, ticket: &Ticket) {
+        for chunk in ticket.chunks() {
+            *self.references.entry(chunk).or_default() += 1;
+            self.idle.remove(&chunk);
+        }
+    }
+
+    fn release(&mut self, ticket: &Ticket) {
+        for chunk in ticket.chunks() {
+            let Some(count) = self.references.get_mut(&chunk) else {
+                continue;
+            };
+            *count -= 1;
+            if *count == 0 {
+                self.references.remove(&chunk);
+                self.idle.insert(chunk, self.tick);
+            }
+        }
+    }
+}
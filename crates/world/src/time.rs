@@ -0,0 +1,115 @@
+use nbt::{Compound, compound};
+
+use crate::{Dimension, loader::LoaderError};
+
+pub const TICKS_PER_DAY: i64 = 24000;
+
+// How often players are sent the time when nothing set it, as in vanilla.
+const SYNC_INTERVAL: i64 = 20;
+
+const DATA_NAME: &str = "minecraft/time";
+
+// The ticks the dimension has run and its time of day, which keeps counting
+// past one day; `day_time` is the time within the current day. Frozen time
+// of day stays put while the world ages.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorldTime {
+    pub age: i64,
+    pub time_of_day: i64,
+    pub frozen: bool,
+}
+
+impl WorldTime {
+    pub fn day_time(&self) -> i64 {
+        self.time_of_day.rem_euclid(TICKS_PER_DAY)
+    }
+
+    pub fn day(&self) -> i64 {
+        self.time_of_day.div_euclid(TICKS_PER_DAY)
+    }
+
+    pub fn tick(&mut self) {
+        self.age += 1;
+        if !self.frozen {
+            self.time_of_day += 1;
+        }
+    }
+
+    pub fn to_nbt(&self) -> Compound {
+        compound! {
+            "Time" => self.age,
+            "DayTime" => self.time_of_day,
+            "Frozen" => self.frozen,
+        }
+    }
+
+    pub fn from_nbt(nbt: &Compound) -> Self {
+        WorldTime {
+            age: nbt.get_i64("Time").unwrap_or(0),
+            time_of_day: nbt.get_i64("DayTime").unwrap_or(0),
+            frozen: nbt.get_bool("Frozen").unwrap_or(false),
+        }
+    }
+}
+
+// The time and whether players need it before the next regular update.
+#[derive(Debug, Default)]
+pub(crate) struct Clock {
+    time: WorldTime,
+    changed: bool,
+}
+
+impl Dimension {
+    pub fn time(&self) -> WorldTime {
+        self.clock.lock().time
+    }
+
+    pub fn set_time_of_day(&self, time_of_day: i64) {
+        self.update_time(|time| time.time_of_day = time_of_day);
+    }
+
+    pub fn add_time(&self, ticks: i64) {
+        self.update_time(|time| time.time_of_day += ticks);
+    }
+
+    pub fn set_time_frozen(&self, frozen: bool) {
+        self.update_time(|time| time.frozen = frozen);
+    }
+
+    // Runs once per game tick.
+    pub fn tick_time(&self) {
+        self.clock.lock().time.tick();
+    }
+
+    // The time to send players with Update Time: after it was set, and once
+    // a second otherwise so clients don't drift.
+    pub fn take_time_update(&self) -> Option<WorldTime> {
+        let mut clock = self.clock.lock();
+        let due = std::mem::take(&mut clock.changed) || clock.time.age % SYNC_INTERVAL == 0;
+        due.then_some(clock.time)
+    }
+
+    pub fn load_time(&self) -> Result<bool, LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(false);
+        };
+        let Some(data) = storage.read_data(DATA_NAME)? else {
+            return Ok(false);
+        };
+        self.clock.lock().time = WorldTime::from_nbt(&data);
+        Ok(true)
+    }
+
+    pub fn save_time(&self) -> Result<(), LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        storage.write_data(DATA_NAME, self.time().to_nbt())
+    }
+
+    fn update_time(&self, update: impl FnOnce(&mut WorldTime)) {
+        let mut clock = self.clock.lock();
+        update(&mut clock.time);
+        clock.changed = true;
+    }
+}
@@ -0,0 +1,183 @@
+use crate::{
+    block::{BlockState, property},
+    pos::{BlockPos, Direction},
+};
+
+// Quarter turns seen from above, as in vanilla's `Rotation`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Clockwise90,
+    Clockwise180,
+    CounterClockwise90,
+}
+
+// As in vanilla's `Mirror`: `LeftRight` swaps north and south, `FrontBack`
+// swaps east and west.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Mirror {
+    #[default]
+    None,
+    LeftRight,
+    FrontBack,
+}
+
+// A mirror followed by a rotation around the origin, the way structures are
+// placed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Transform {
+    pub rotation: Rotation,
+    pub mirror: Mirror,
+}
+
+impl Rotation {
+    pub fn turns(self) -> u8 {
+        match self {
+            Rotation::None => 0,
+            Rotation::Clockwise90 => 1,
+            Rotation::Clockwise180 => 2,
+            Rotation::CounterClockwise90 => 3,
+        }
+    }
+
+    pub fn rotate(self, direction: Direction) -> Direction {
+        (0..self.turns()).fold(direction, |direction, _| direction.clockwise())
+    }
+}
+
+impl Mirror {
+    pub fn mirror(self, direction: Direction) -> Direction {
+        match (self, direction) {
+            (Mirror::LeftRight, Direction::North | Direction::South)
+            | (Mirror::FrontBack, Direction::East | Direction::West) => direction.opposite(),
+            _ => direction,
+        }
+    }
+}
+
+impl Transform {
+    pub fn new(rotation: Rotation, mirror: Mirror) -> Self {
+        Transform { rotation, mirror }
+    }
+
+    pub fn is_identity(self) -> bool {
+        self.rotation == Rotation::None && self.mirror == Mirror::None
+    }
+
+    pub fn direction(self, direction: Direction) -> Direction {
+        self.rotation.rotate(self.mirror.mirror(direction))
+    }
+
+    // Block positions map cell to cell, so the block at the origin stays put.
+    pub fn pos(self, pos: BlockPos) -> BlockPos {
+        let (x, z) = self.xz(pos.x, pos.z);
+        BlockPos::new(x, pos.y, z)
+    }
+
+    // Exact positions, like an entity's, keep their place within the block.
+    pub fn point(self, [x, y, z]: [f64; 3]) -> [f64; 3] {
+        let (x, z) = match self.mirror {
+            Mirror::None => (x, z),
+            Mirror::LeftRight => (x, 1.0 - z),
+            Mirror::FrontBack => (1.0 - x, z),
+        };
+        let (x, z) = match self.rotation {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (1.0 - z, x),
+            Rotation::Clockwise180 => (1.0 - x, 1.0 - z),
+            Rotation::CounterClockwise90 => (z, 1.0 - x),
+        };
+        [x, y, z]
+    }
+
+    // An entity's yaw in degrees.
+    pub fn yaw(self, yaw: f32) -> f32 {
+        let yaw = match self.mirror {
+            Mirror::None => yaw,
+            Mirror::LeftRight => 180.0 - yaw,
+            Mirror::FrontBack => -yaw,
+        };
+        (yaw + 90.0 * self.rotation.turns() as f32).rem_euclid(360.0)
+    }
+
+    // Turns the properties that depend on orientation: facing, sign and
+    // banner rotation, axis, the sides of fences and walls, and the handed
+    // shapes of stairs, chests and doors.
+    pub fn state(self, state: BlockState) -> BlockState {
+        if self.is_identity() {
+            return state;
+        }
+        let mut result = state;
+        for (name, value) in state.properties() {
+            let value = match name {
+                "facing" => Direction::from_name(value).map(|d| self.direction(d).name()),
+                "axis" if self.rotation.turns() % 2 == 1 => match value {
+                    "x" => Some("z"),
+                    "z" => Some("x"),
+                    _ => None,
+                },
+                "shape" | "type" | "hinge" if self.mirror != Mirror::None => swap_hand(value),
+                _ => None,
+            };
+            if let Some(value) = value {
+                result = result.with_value(name, value).unwrap_or(result);
+            }
+        }
+
+        if let Some(rotation) = state.get(property::ROTATION) {
+            result = result.with(property::ROTATION, self.sign_rotation(rotation));
+        }
+
+        // Side properties move together, each to its new direction.
+        let sides = Direction::HORIZONTAL.map(|direction| state.value(direction.name()));
+        if let [Some(north), Some(south), Some(west), Some(east)] = sides {
+            for (direction, value) in Direction::HORIZONTAL
+                .into_iter()
+                .zip([north, south, west, east])
+            {
+                let target = self.direction(direction).name();
+                result = result.with_value(target, value).unwrap_or(result);
+            }
+        }
+        result
+    }
+
+    // Sixteen steps clockwise from south.
+    fn sign_rotation(self, rotation: u8) -> u8 {
+        let rotation = rotation as i32;
+        let mirrored = match self.mirror {
+            Mirror::None => rotation,
+            Mirror::LeftRight => 8 - rotation,
+            Mirror::FrontBack => 16 - rotation,
+        };
+        (mirrored + 4 * self.rotation.turns() as i32).rem_euclid(16) as u8
+    }
+
+    fn xz(self, x: i32, z: i32) -> (i32, i32) {
+        let (x, z) = match self.mirror {
+            Mirror::None => (x, z),
+            Mirror::LeftRight => (x, -z),
+            Mirror::FrontBack => (-x, z),
+        };
+        match self.rotation {
+            Rotation::None => (x, z),
+            Rotation::Clockwise90 => (-z, x),
+            Rotation::Clockwise180 => (-x, -z),
+            Rotation::CounterClockwise90 => (z, -x),
+        }
+    }
+}
+
+// Mirroring turns left into right.
+fn swap_hand(value: &str) -> Option<&'static str> {
+    Some(match value {
+        "left" => "right",
+        "right" => "left",
+        "inner_left" => "inner_right",
+        "inner_right" => "inner_left",
+        "outer_left" => "outer_right",
+        "outer_right" => "outer_left",
+        _ => return None,
+    })
+}
@@ -0,0 +1,163 @@
+use std::{collections::BTreeMap, ops::BitOr};
+
+use ahash::AHashSet;
+
+use crate::{
+    DimensionId,
+    block::{Block, BlockState, property},
+    block_entity::BlockEntity,
+    border::BorderChange,
+    pos::BlockPos,
+    time::WorldTime,
+    weather::WeatherChange,
+};
+
+// What `Dimension::set_block` does besides changing the block. Combine them
+// with `|`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BlockFlags(u8);
+
+impl BlockFlags {
+    // Only the block changes, nobody hears of it.
+    pub const NONE: BlockFlags = BlockFlags(0);
+    // The block and its neighbors update their shape, so fences connect.
+    pub const NEIGHBORS: BlockFlags = BlockFlags(1);
+    // Players who have the chunk are sent the change.
+    pub const CLIENTS: BlockFlags = BlockFlags(2);
+    // Fluids and falling blocks at or next to the change are scheduled a tick.
+    pub const TICKS: BlockFlags = BlockFlags(4);
+    pub const DEFAULT: BlockFlags = BlockFlags(7);
+
+    pub fn contains(self, flags: BlockFlags) -> bool {
+        self.0 & flags.0 == flags.0
+    }
+}
+
+impl Default for BlockFlags {
+    fn default() -> Self {
+        BlockFlags::DEFAULT
+    }
+}
+
+impl BitOr for BlockFlags {
+    type Output = BlockFlags;
+
+    fn bitor(self, rhs: BlockFlags) -> BlockFlags {
+        BlockFlags(self.0 | rhs.0)
+    }
+}
+
+// The blocks and block entities of one section changed since the changes
+// were last taken, as they are now. A block entity removed from a block that
+// still takes one comes back empty so clients clear it; others are left out,
+// as clients drop them with their block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionChanges {
+    pub chunk_x: i32,
+    pub section_y: i32,
+    pub chunk_z: i32,
+    pub blocks: Vec<(BlockPos, BlockState)>,
+    pub block_entities: Vec<(BlockPos, BlockEntity)>,
+}
+
+// What players in a dimension are sent after one game tick.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DimensionTick {
+    pub dimension: DimensionId,
+    pub blocks: Vec<SectionChanges>,
+    pub border: Vec<BorderChange>,
+    pub time: Option<WorldTime>,
+    pub weather: Vec<WeatherChange>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ChangedSection {
+    pub(crate) blocks: ChangedBlocks,
+    pub(crate) block_entities: ChangedBlocks,
+}
+
+// Which blocks of a section changed, one bit per block index.
+#[derive(Debug, Clone)]
+pub(crate) struct ChangedBlocks(Box<[u64; 64]>);
+
+impl Default for ChangedBlocks {
+    fn default() -> Self {
+        ChangedBlocks(Box::new([0; 64]))
+    }
+}
+
+impl ChangedBlocks {
+    pub(crate) fn insert(&mut self, index: usize) {
+        self.0[index / 64] |= 1 << (index % 64);
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter().enumerate().flat_map(|(word, &bits)| {
+            (0..64)
+                .filter(move |bit| bits >> bit & 1 == 1)
+                .map(move |bit| word * 64 + bit)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ScheduledTick {
+    pub pos: BlockPos,
+    pub block: Block,
+}
+
+// Ticks waiting for their game tick, due first and then in the order they
+// were scheduled. A block is scheduled at most once at a time.
+#[derive(Debug, Default)]
+pub(crate) struct ScheduledTicks {
+    tick: u64,
+    next: u64,
+    queue: BTreeMap<(u64, u64), ScheduledTick>,
+    scheduled: AHashSet<ScheduledTick>,
+}
+
+impl ScheduledTicks {
+    pub(crate) fn schedule(&mut self, tick: ScheduledTick, delay: u32) -> bool {
+        if !self.scheduled.insert(tick) {
+            return false;
+        }
+        self.queue
+            .insert((self.tick + delay as u64, self.next), tick);
+        self.next += 1;
+        true
+    }
+
+    // Advances a tick and returns the ticks that became due.
+    pub(crate) fn advance(&mut self) -> Vec<ScheduledTick> {
+        self.tick += 1;
+        let later = self.queue.split_off(&(self.tick + 1, 0));
+        let due = std::mem::replace(&mut self.queue, later);
+        due.into_values()
+            .inspect(|tick| {
+                self.scheduled.remove(tick);
+            })
+            .collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.queue.len()
+    }
+}
+
+// The tick vanilla schedules when the block is placed or a neighbor changes,
+// with its delay. Waterlogged blocks tick their water. Lava uses its
+// overworld delay.
+pub fn scheduled_tick(state: BlockState) -> Option<(Block, u32)> {
+    let block = state.block();
+    match block {
+        Block::WATER => Some((block, 5)),
+        Block::LAVA => Some((block, 30)),
+        Block::SAND
+        | Block::RED_SAND
+        | Block::GRAVEL
+        | Block::SUSPICIOUS_SAND
+        | Block::SUSPICIOUS_GRAVEL => Some((block, 2)),
+        _ if state.get(property::WATERLOGGED) == Some(true) => Some((Block::WATER, 5)),
+        _ => None,
+    }
+}
@@ -0,0 +1,220 @@
+use std::collections::VecDeque;
+
+use ahash::AHashSet;
+
+// Bounds for the rate a client asks for in Chunk Batch Received, in chunks
+// per tick, as vanilla clamps it.
+const MIN_CHUNKS_PER_TICK: f32 = 0.01;
+const MAX_CHUNKS_PER_TICK: f32 = 64.0;
+const START_CHUNKS_PER_TICK: f32 = 9.0;
+// How many batches may be sent ahead of the client's acknowledgements once
+// it has acknowledged one, as in vanilla.
+const MAX_UNACKNOWLEDGED_BATCHES: u32 = 10;
+
+// What a change to the view means for the client.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ViewUpdate {
+    // The new center, for Set Center Chunk.
+    pub center: Option<(i32, i32)>,
+    // The new effective view distance, for Set Chunk Cache Radius.
+    pub view_distance: Option<u32>,
+    // Chunks the client had that left the view, for Unload Chunk.
+    pub unload: Vec<(i32, i32)>,
+}
+
+impl ViewUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.center.is_none() && self.view_distance.is_none() && self.unload.is_empty()
+    }
+}
+
+// The chunks one player can see: which were sent, which are still waiting
+// (nearest first), and how fast the client wants them.
+#[derive(Debug)]
+pub struct PlayerView {
+    center: Option<(i32, i32)>,
+    server_view_distance: u32,
+    client_view_distance: Option<u32>,
+    sent: AHashSet<(i32, i32)>,
+    pending: VecDeque<(i32, i32)>,
+    chunks_per_tick: f32,
+    quota: f32,
+    unacknowledged: u32,
+    max_unacknowledged: u32,
+}
+
+impl PlayerView {
+    pub fn new(server_view_distance: u32) -> Self {
+        PlayerView {
+            center: None,
+            server_view_distance,
+            client_view_distance: None,
+            sent: AHashSet::new(),
+            pending: VecDeque::new(),
+            chunks_per_tick: START_CHUNKS_PER_TICK,
+            quota: 0.0,
+            unacknowledged: 0,
+            // Until the client acknowledges its first batch only one may be
+            // in flight.
+            max_unacknowledged: 1,
+        }
+    }
+
+    pub fn center(&self) -> Option<(i32, i32)> {
+        self.center
+    }
+
+    // The lesser of the client's and the server's view distance.
+    pub fn view_distance(&self) -> u32 {
+        self.client_view_distance
+            .map_or(self.server_view_distance, |client| {
+                client.min(self.server_view_distance)
+            })
+    }
+
+    pub fn contains(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        let Some((center_x, center_z)) = self.center else {
+            return false;
+        };
+        let distance = self.view_distance();
+        (chunk_x - center_x).unsigned_abs() <= distance
+            && (chunk_z - center_z).unsigned_abs() <= distance
+    }
+
+    pub fn is_sent(&self, chunk_x: i32, chunk_z: i32) -> bool {
+        self.sent.contains(&(chunk_x, chunk_z))
+    }
+
+    pub fn sent_count(&self) -> usize {
+        self.sent.len()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    // Called with the player's chunk every time they move; nothing changes
+    // until they cross a chunk border.
+    pub fn move_to(&mut self, chunk_x: i32, chunk_z: i32) -> ViewUpdate {
+        if self.center == Some((chunk_x, chunk_z)) {
+            return ViewUpdate::default();
+        }
+        self.center = Some((chunk_x, chunk_z));
+        ViewUpdate {
+            center: self.center,
+            view_distance: None,
+            unload: self.refresh(),
+        }
+    }
+
+    // The client's view distance comes from Client Information and may
+    // change at any time.
+    pub fn set_client_view_distance(&mut self, distance: u32) -> ViewUpdate {
+        self.set_view_distances(self.server_view_distance, Some(distance))
+    }
+
+    pub fn set_server_view_distance(&mut self, distance: u32) -> ViewUpdate {
+        self.set_view_distances(distance, self.client_view_distance)
+    }
+
+    // Hands out the next batch, nearest chunks first, as long as the client
+    // keeps up. Call once per tick and send what comes back between Chunk
+    // Batch Start and Chunk Batch Finished.
+    pub fn next_batch(&mut self) -> Vec<(i32, i32)> {
+        if self.pending.is_empty() || self.unacknowledged >= self.max_unacknowledged {
+            return Vec::new();
+        }
+        let max = self.chunks_per_tick.max(1.0);
+        self.quota = (self.quota + self.chunks_per_tick).min(max);
+        if self.quota < 1.0 {
+            return Vec::new();
+        }
+
+        let count = (self.quota as usize).min(self.pending.len());
+        let batch = self.pending.drain(..count).collect::<Vec<_>>();
+        self.sent.extend(batch.iter().copied());
+        self.unacknowledged += 1;
+        self.quota -= count as f32;
+        batch
+    }
+
+    // Chunk Batch Received carries the rate the client can take.
+    pub fn batch_received(&mut self, chunks_per_tick: f32) {
+        self.unacknowledged = self.unacknowledged.saturating_sub(1);
+        self.chunks_per_tick = if chunks_per_tick.is_nan() {
+            MIN_CHUNKS_PER_TICK
+        } else {
+            chunks_per_tick.clamp(MIN_CHUNKS_PER_TICK, MAX_CHUNKS_PER_TICK)
+        };
+        if self.unacknowledged == 0 {
+            self.quota = 1.0;
+        }
+        self.max_unacknowledged = MAX_UNACKNOWLEDGED_BATCHES;
+    }
+
+    // Sends the chunk again, as after it changed in ways the client can't
+    // follow with block updates.
+    pub fn resend(&mut self, chunk_x: i32, chunk_z: i32) {
+        if self.sent.remove(&(chunk_x, chunk_z)) {
+            self.refresh();
+        }
+    }
+
+    // Forgets everything sent, as when the player changes dimension. The
+    // client drops its chunks on its own then.
+    pub fn reset(&mut self) {
+        self.center = None;
+        self.sent.clear();
+        self.pending.clear();
+    }
+
+    fn set_view_distances(&mut self, server: u32, client: Option<u32>) -> ViewUpdate {
+        let old = self.view_distance();
+        self.server_view_distance = server;
+        self.client_view_distance = client;
+        let distance = self.view_distance();
+        if distance == old {
+            return ViewUpdate::default();
+        }
+        ViewUpdate {
+            center: None,
+            view_distance: Some(distance),
+            unload: self.refresh(),
+        }
+    }
+
+    // Drops sent chunks out of range and queues the missing ones, ordered by
+    // distance to the center.
+    fn refresh(&mut self) -> Vec<(i32, i32)> {
+        let mut unload = self
+            .sent
+            .iter()
+            .filter(|(x, z)| !self.contains(*x, *z))
+            .copied()
+            .collect::<Vec<_>>();
+        unload.sort_unstable();
+        for chunk in &unload {
+            self.sent.remove(chunk);
+        }
+
+        self.pending.clear();
+        let Some((center_x, center_z)) = self.center else {
+            return unload;
+        };
+        let radius = self.view_distance() as i32;
+        let mut pending = Vec::new();
+        for z in center_z - radius..=center_z + radius {
+            for x in center_x - radius..=center_x + radius {
+                if !self.sent.contains(&(x, z)) {
+                    pending.push((x, z));
+                }
+            }
+        }
+        pending.sort_by_key(|(x, z)| {
+            let (dx, dz) = ((x - center_x) as i64, (z - center_z) as i64);
+            (dx * dx + dz * dz, *z, *x)
+        });
+        self.pending = pending.into();
+        unload
+    }
+}
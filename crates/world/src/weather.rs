@@ -0,0 +1,265 @@
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    ops::Range,
+};
+
+use nbt::{Compound, compound};
+
+use crate::{Dimension, generator::noise::Random, loader::LoaderError};
+
+// Vanilla's random durations in ticks: clear spells and the gaps between
+// storms, then how long rain and thunder last.
+const DELAY: Range<u32> = 12000..180000;
+const RAIN_DURATION: Range<u32> = 12000..24000;
+const THUNDER_DURATION: Range<u32> = 3600..15600;
+
+// How much rain and thunder fade in or out each tick.
+const LEVEL_STEP: f32 = 0.01;
+
+const DATA_NAME: &str = "minecraft/weather";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherKind {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+// Whether it rains or thunders and for how many more ticks before that
+// flips. While `clear_time` runs neither can start. The levels fade towards
+// the state, and are what clients see.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Weather {
+    pub raining: bool,
+    pub thundering: bool,
+    pub rain_time: u32,
+    pub thunder_time: u32,
+    pub clear_time: u32,
+    rain_level: f32,
+    thunder_level: f32,
+}
+
+// The Game Events players in the dimension are sent.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeatherChange {
+    StartRaining,
+    StopRaining,
+    RainLevel(f32),
+    ThunderLevel(f32),
+}
+
+impl Weather {
+    pub fn rain_level(&self) -> f32 {
+        self.rain_level
+    }
+
+    pub fn thunder_level(&self) -> f32 {
+        self.thunder_level
+    }
+
+    // As clients see it, once the rain has faded in a little.
+    pub fn is_raining(&self) -> bool {
+        self.rain_level > 0.2
+    }
+
+    pub fn is_thundering(&self) -> bool {
+        self.rain_level * self.thunder_level > 0.9
+    }
+
+    // Like `/weather`: the weather for the ticks, or for a random duration.
+    pub fn set(&mut self, kind: WeatherKind, ticks: Option<u32>, random: &mut Random) {
+        let (duration, raining, thundering) = match kind {
+            WeatherKind::Clear => (DELAY, false, false),
+            WeatherKind::Rain => (RAIN_DURATION, true, false),
+            WeatherKind::Thunder => (THUNDER_DURATION, true, true),
+        };
+        let ticks = ticks.unwrap_or_else(|| sample(random, duration));
+        let (clear, storm) = if raining { (0, ticks) } else { (ticks, 0) };
+        self.clear_time = clear;
+        self.rain_time = storm;
+        self.thunder_time = storm;
+        self.raining = raining;
+        self.thundering = thundering;
+    }
+
+    // Vanilla's weather cycle, then the fading. Returns what clients need
+    // to hear.
+    pub fn tick(&mut self, random: &mut Random) -> Vec<WeatherChange> {
+        let was_raining = self.is_raining();
+        let (rain_level, thunder_level) = (self.rain_level, self.thunder_level);
+
+        if self.clear_time > 0 {
+            self.clear_time -= 1;
+            self.thunder_time = if self.thundering { 0 } else { 1 };
+            self.rain_time = if self.raining { 0 } else { 1 };
+            self.thundering = false;
+            self.raining = false;
+        } else {
+            cycle(
+                &mut self.thundering,
+                &mut self.thunder_time,
+                THUNDER_DURATION,
+                random,
+            );
+            cycle(
+                &mut self.raining,
+                &mut self.rain_time,
+                RAIN_DURATION,
+                random,
+            );
+        }
+
+        self.rain_level = fade(self.rain_level, self.raining);
+        self.thunder_level = fade(self.thunder_level, self.thundering);
+
+        let mut changes = Vec::new();
+        if was_raining != self.is_raining() {
+            changes.push(if was_raining {
+                WeatherChange::StopRaining
+            } else {
+                WeatherChange::StartRaining
+            });
+        }
+        if rain_level != self.rain_level {
+            changes.push(WeatherChange::RainLevel(self.rain_level));
+        }
+        if thunder_level != self.thunder_level {
+            changes.push(WeatherChange::ThunderLevel(self.thunder_level));
+        }
+        changes
+    }
+
+    // What a player entering the dimension is sent.
+    pub fn client_state(&self) -> Vec<WeatherChange> {
+        if !self.is_raining() {
+            return Vec::new();
+        }
+        vec![
+            WeatherChange::StartRaining,
+            WeatherChange::RainLevel(self.rain_level),
+            WeatherChange::ThunderLevel(self.thunder_level),
+        ]
+    }
+
+    // With vanilla's level.dat names. The levels start where the weather
+    // is, so a storm doesn't fade in again after a restart.
+    pub fn to_nbt(&self) -> Compound {
+        compound! {
+            "raining" => self.raining,
+            "rainTime" => self.rain_time as i32,
+            "thundering" => self.thundering,
+            "thunderTime" => self.thunder_time as i32,
+            "clearWeatherTime" => self.clear_time as i32,
+        }
+    }
+
+    pub fn from_nbt(nbt: &Compound) -> Self {
+        let time = |key: &str| nbt.get_i32(key).unwrap_or(0).max(0) as u32;
+        let raining = nbt.get_bool("raining").unwrap_or(false);
+        let thundering = nbt.get_bool("thundering").unwrap_or(false);
+        Weather {
+            raining,
+            thundering,
+            rain_time: time("rainTime"),
+            thunder_time: time("thunderTime"),
+            clear_time: time("clearWeatherTime"),
+            rain_level: if raining { 1.0 } else { 0.0 },
+            thunder_level: if thundering { 1.0 } else { 0.0 },
+        }
+    }
+}
+
+// The weather and the randomness its cycle draws on.
+#[derive(Debug)]
+pub(crate) struct Sky {
+    weather: Weather,
+    random: Random,
+}
+
+impl Sky {
+    // Seeded from the dimension's id, so each dimension has its own cycle.
+    pub(crate) fn new(seed: impl Hash) -> Self {
+        let mut hasher = DefaultHasher::new();
+        seed.hash(&mut hasher);
+        Sky {
+            weather: Weather::default(),
+            random: Random::new(hasher.finish()),
+        }
+    }
+}
+
+// The weather methods only do something in dimensions with a sky; see
+// `has_weather`.
+impl Dimension {
+    pub fn weather(&self) -> Weather {
+        self.sky.lock().weather
+    }
+
+    pub fn has_weather(&self) -> bool {
+        self.dimension_type.has_skylight && !self.dimension_type.has_ceiling
+    }
+
+    pub fn set_weather(&self, kind: WeatherKind, ticks: Option<u32>) {
+        if !self.has_weather() {
+            return;
+        }
+        let Sky { weather, random } = &mut *self.sky.lock();
+        weather.set(kind, ticks, random);
+    }
+
+    // Runs once per game tick.
+    pub fn tick_weather(&self) {
+        if !self.has_weather() {
+            return;
+        }
+        let Sky { weather, random } = &mut *self.sky.lock();
+        let changes = weather.tick(random);
+        self.weather_changes.lock().extend(changes);
+    }
+
+    // The changes since the last call, for Game Event packets.
+    pub fn take_weather_changes(&self) -> Vec<WeatherChange> {
+        std::mem::take(&mut *self.weather_changes.lock())
+    }
+
+    pub fn load_weather(&self) -> Result<bool, LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(false);
+        };
+        let Some(data) = storage.read_data(DATA_NAME)? else {
+            return Ok(false);
+        };
+        self.sky.lock().weather = Weather::from_nbt(&data);
+        Ok(true)
+    }
+
+    pub fn save_weather(&self) -> Result<(), LoaderError> {
+        let Some(storage) = &self.storage else {
+            return Ok(());
+        };
+        storage.write_data(DATA_NAME, self.weather().to_nbt())
+    }
+}
+
+// Counts the state's time down and flips it at zero. Without a time a new
+// one is drawn: how long it lasts when on, how long until it starts when
+// off.
+fn cycle(state: &mut bool, time: &mut u32, duration: Range<u32>, random: &mut Random) {
+    if *time > 0 {
+        *time -= 1;
+        if *time == 0 {
+            *state = !*state;
+        }
+    } else {
+        *time = sample(random, if *state { duration } else { DELAY });
+    }
+}
+
+fn fade(level: f32, on: bool) -> f32 {
+    let step = if on { LEVEL_STEP } else { -LEVEL_STEP };
+    (level + step).clamp(0.0, 1.0)
+}
+
+fn sample(random: &mut Random, range: Range<u32>) -> u32 {
+    range.start + random.next_below(range.end - range.start)
+}
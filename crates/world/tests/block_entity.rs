@@ -0,0 +1,181 @@
+use components::{Component, colors::Color};
+use nbt::{Compound, Value, compound};
+use world::{
+    Dimension, DimensionId,
+    block::{Block, BlockRegistry},
+    block_entity::{Banner, BannerPattern, BlockEntity, Inventory, ItemStack, Sign, network_id},
+    chunk::Chunk,
+    edit::Cuboid,
+    generator::flat::FlatGenerator,
+    loader::anvil,
+    pos::BlockPos,
+    update::BlockFlags,
+};
+
+// The legacy flat world has grass at y 4 and air from y 5.
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+fn chest_with(id: &str, count: i32) -> BlockEntity {
+    let mut inventory = Inventory::default();
+    inventory.items.insert(
+        3,
+        ItemStack {
+            id: id.to_string(),
+            count,
+            components: Compound::new(),
+        },
+    );
+    BlockEntity::new("minecraft:chest").with(&inventory)
+}
+
+#[test]
+fn typed_data_round_trips() {
+    let mut sign = Sign::default();
+    sign.front.messages[0] = Component::text("Welcome");
+    sign.front.messages[1] = Component::text("home").color(Color::RED).bold(true);
+    sign.front.glowing = true;
+    sign.waxed = true;
+    let entity = BlockEntity::new("minecraft:sign").with(&sign);
+    assert_eq!(
+        entity.data.query("front_text.messages[0]"),
+        Some(&Value::String("Welcome".to_string()))
+    );
+    assert_eq!(entity.get::<Sign>(), sign);
+
+    let banner = Banner {
+        patterns: vec![BannerPattern {
+            pattern: "minecraft:stripe_top".to_string(),
+            color: "red".to_string(),
+        }],
+    };
+    let entity = BlockEntity::new("minecraft:banner").with(&banner);
+    assert_eq!(entity.get::<Banner>(), banner);
+
+    // Fields the typed view doesn't know are kept, and clients don't see
+    // what is inside a chest.
+    let mut chest = chest_with("minecraft:diamond", 5);
+    chest.data.insert("CustomName", "Loot");
+    assert_eq!(chest.get::<Inventory>().items[&3].count, 5);
+    assert_eq!(chest.client_data(), compound! { "CustomName" => "Loot" });
+    assert_eq!(network_id("minecraft:chest"), Some(1));
+    assert_eq!(network_id("banner"), Some(20));
+}
+
+#[test]
+fn every_block_entity_type_has_a_network_id() {
+    for block in Block::all() {
+        if let Some(kind) = block.block_entity() {
+            assert!(network_id(kind).is_some(), "{} has unknown {}", block, kind);
+        }
+    }
+    assert_eq!(Block::OAK_WALL_SIGN.block_entity(), Some("minecraft:sign"));
+    assert_eq!(
+        Block::WHITE_WALL_BANNER.block_entity(),
+        Some("minecraft:banner")
+    );
+    assert_eq!(Block::WALL_TORCH.block_entity(), None);
+}
+
+#[test]
+fn block_entities_follow_their_blocks() {
+    let dimension = dimension();
+    let pos = BlockPos::new(3, 5, 3);
+    assert!(
+        !dimension
+            .set_block_entity(pos, chest_with("minecraft:stone", 1))
+            .unwrap()
+    );
+    dimension
+        .set_block(pos, Block::CHEST.into(), BlockFlags::DEFAULT)
+        .unwrap();
+    assert!(
+        dimension
+            .set_block_entity(pos, chest_with("minecraft:stone", 1))
+            .unwrap()
+    );
+
+    let changes = dimension.take_block_changes();
+    assert_eq!(changes[0].block_entities.len(), 1);
+    assert_eq!(changes[0].block_entities[0].0, pos);
+
+    dimension
+        .set_block(pos, Block::STONE.into(), BlockFlags::DEFAULT)
+        .unwrap();
+    assert_eq!(dimension.block_entity(pos), None);
+
+    // Region operations keep them with their blocks too.
+    dimension
+        .set_block(pos, Block::CHEST.into(), BlockFlags::DEFAULT)
+        .unwrap();
+    dimension
+        .set_block_entity(pos, chest_with("minecraft:apple", 2))
+        .unwrap();
+    let arena = Cuboid::new(BlockPos::new(0, 0, 0), BlockPos::new(15, 20, 15));
+    let snapshot = dimension.snapshot(arena.chunks()).unwrap();
+    let clipboard = dimension.copy(arena).unwrap();
+    dimension.fill(arena, Block::GLASS.into()).unwrap();
+    assert_eq!(dimension.block_entity(pos), None);
+
+    dimension.restore(&snapshot).unwrap();
+    let restored = dimension.block_entity(pos).unwrap();
+    assert_eq!(restored.get::<Inventory>().items[&3].id, "minecraft:apple");
+
+    dimension
+        .paste(&clipboard, BlockPos::new(32, 0, 0))
+        .unwrap();
+    assert_eq!(
+        dimension.block_entity(BlockPos::new(35, 5, 3)),
+        Some(restored)
+    );
+}
+
+#[test]
+fn removed_block_entities_reach_clients() {
+    let dimension = dimension();
+    let pos = BlockPos::new(2, 5, 2);
+    dimension
+        .set_block(pos, Block::OAK_SIGN.into(), BlockFlags::DEFAULT)
+        .unwrap();
+    let mut sign = Sign::default();
+    sign.front.messages[0] = Component::text("Stale");
+    dimension
+        .set_block_entity(pos, BlockEntity::new("minecraft:sign").with(&sign))
+        .unwrap();
+    dimension.take_block_changes();
+
+    assert!(dimension.remove_block_entity(pos).is_some());
+    let changes = dimension.take_block_changes();
+    assert_eq!(changes.len(), 1);
+    assert!(changes[0].blocks.is_empty());
+    assert_eq!(
+        changes[0].block_entities,
+        vec![(pos, BlockEntity::new("minecraft:sign"))]
+    );
+
+    assert!(dimension.remove_block_entity(pos).is_none());
+    assert!(dimension.take_block_changes().is_empty());
+}
+
+#[test]
+fn anvil_and_chunk_data() {
+    let mut chunk = Chunk::new(2, -1);
+    chunk.set_block(4, 70, 9, Block::OAK_SIGN.into());
+    let mut sign = Sign::default();
+    sign.back.messages[3] = Component::text("back");
+    assert!(chunk.set_block_entity(4, 70, 9, BlockEntity::new("minecraft:sign").with(&sign)));
+
+    let nbt = anvil::write_chunk(&chunk, &BlockRegistry);
+    let saved = nbt.get_list_of::<&Compound>("block_entities").unwrap();
+    assert_eq!(saved[0].get_i32("x"), Some(36));
+    assert_eq!(saved[0].get_i32("z"), Some(-7));
+    let loaded = anvil::read_chunk(&nbt, -64, 384, &BlockRegistry).unwrap();
+    assert_eq!(loaded.block_entity(4, 70, 9).unwrap().get::<Sign>(), sign);
+
+    // One block entity in the packet where there were none.
+    let with = chunk.encoded().unwrap();
+    chunk.remove_block_entity(4, 70, 9);
+    let without = chunk.encoded().unwrap();
+    assert!(with.len() > without.len());
+}
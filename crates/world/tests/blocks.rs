@@ -0,0 +1,121 @@
+use nbt::compound;
+use world::{
+    block::{
+        Block, BlockRegistry, BlockState, BlockStateError,
+        property::{AXIS, Axis, FACING, Facing, HALF, Half, LEVEL, SNOWY, WATERLOGGED},
+    },
+    loader::StateMapper,
+};
+
+#[test]
+fn typed_properties() {
+    let grass = Block::GRASS_BLOCK.default_state();
+    assert_eq!(grass.get(SNOWY), Some(false));
+
+    let snowy = Block::GRASS_BLOCK.with(SNOWY, true);
+    assert_eq!(snowy.block(), Block::GRASS_BLOCK);
+    assert_eq!(snowy.get(SNOWY), Some(true));
+    assert_eq!(snowy.id() + 1, grass.id());
+    assert_eq!(snowy.with(SNOWY, false), grass);
+
+    let stairs = Block::OAK_STAIRS
+        .with(FACING, Facing::East)
+        .with(HALF, Half::Top)
+        .with(WATERLOGGED, true);
+    assert_eq!(stairs.get(FACING), Some(Facing::East));
+    assert_eq!(stairs.get(HALF), Some(Half::Top));
+    assert_eq!(stairs.get(WATERLOGGED), Some(true));
+    assert_eq!(stairs.get(LEVEL), None);
+
+    assert_eq!(Block::OAK_STAIRS.states().count(), 80);
+    assert!(
+        Block::OAK_STAIRS
+            .states()
+            .all(|s| s.block() == Block::OAK_STAIRS)
+    );
+    assert_eq!(Block::WATER.default_state().try_with(LEVEL, 16), None);
+    assert_eq!(Block::STONE.default_state().try_with(SNOWY, true), None);
+}
+
+#[test]
+fn lookup_and_parse() {
+    assert_eq!(
+        Block::from_name("minecraft:oak_stairs"),
+        Some(Block::OAK_STAIRS)
+    );
+    assert_eq!(Block::from_name("bedrock"), Some(Block::BEDROCK));
+    assert_eq!(Block::from_name("minecraft:nope"), None);
+    assert!(Block::all().all(|block| Block::from_name(block.name()) == Some(block)));
+
+    let state: BlockState = "oak_stairs[facing=east]".parse().unwrap();
+    assert_eq!(state, Block::OAK_STAIRS.with(FACING, Facing::East));
+    assert_eq!(
+        state.to_string(),
+        "minecraft:oak_stairs[facing=east,half=bottom,shape=straight,waterlogged=false]"
+    );
+    assert_eq!(state.to_string().parse::<BlockState>(), Ok(state));
+    assert_eq!("stone".parse(), Ok(Block::STONE.default_state()));
+    assert_eq!(
+        "minecraft:water[ level = 3 ]".parse(),
+        Ok(Block::WATER.with(LEVEL, 3))
+    );
+
+    assert_eq!(
+        "dirt[snowy=true]".parse::<BlockState>(),
+        Err(BlockStateError::UnknownProperty {
+            block: "minecraft:dirt".to_string(),
+            property: "snowy".to_string(),
+        })
+    );
+    assert!(matches!(
+        "oak_stairs[facing=up]".parse::<BlockState>(),
+        Err(BlockStateError::InvalidValue { .. })
+    ));
+    assert!(matches!(
+        "oak_stairs[facing=east".parse::<BlockState>(),
+        Err(BlockStateError::Malformed(_))
+    ));
+    assert!(matches!(
+        "marble".parse::<BlockState>(),
+        Err(BlockStateError::UnknownBlock(_))
+    ));
+}
+
+#[test]
+fn every_state_round_trips_through_anvil_nbt() {
+    for state in Block::all().flat_map(Block::states) {
+        let nbt = BlockRegistry.state_nbt(state.id()).unwrap();
+        assert_eq!(BlockRegistry.state_id(&nbt), Some(state.id()));
+    }
+
+    let chest = compound! {
+        "Name" => "minecraft:chest",
+        "Properties" => compound! { "facing" => "west", "unknown" => "1" },
+    };
+    let id = BlockRegistry.state_id(&chest).unwrap();
+    assert_eq!(
+        BlockState::from_id(id).unwrap().get(FACING),
+        Some(Facing::West)
+    );
+    assert_eq!(
+        BlockRegistry.state_nbt(0),
+        Some(compound! { "Name" => "minecraft:air" })
+    );
+}
+
+// The blocks the bundled data shares with vanilla's report keep vanilla's ids.
+#[test]
+fn vanilla_ids() {
+    assert_eq!(BlockState::AIR.id(), 0);
+    assert_eq!(Block::STONE.default_state().id(), 1);
+    assert_eq!(Block::GRASS_BLOCK.default_state().id(), 9);
+    assert_eq!(Block::DIRT.default_state().id(), 10);
+    assert_eq!(Block::BEDROCK.default_state().id(), 82);
+    assert_eq!(Block::WATER.default_state().id(), 83);
+    assert_eq!(Block::OAK_LOG.with(AXIS, Axis::Y).id(), 134);
+    assert_eq!(
+        "minecraft:oak_log[axis=y]".parse(),
+        Ok(BlockState::from_id(134).unwrap())
+    );
+    assert_eq!(Block::BIRCH_LOG.with(AXIS, Axis::Z).id(), 141);
+}
@@ -0,0 +1,116 @@
+use std::{path::Path, sync::Arc};
+
+use world::{
+    Dimension, DimensionId,
+    block::BlockRegistry,
+    border::{BorderChange, MAX_SIZE, WorldBorder},
+    generator::flat::FlatGenerator,
+    loader::RegionStorage,
+    pos::BlockPos,
+};
+
+mod common;
+
+use common::TempDir;
+
+fn dimension(dir: &Path) -> Dimension {
+    let storage = RegionStorage::new(dir.join("region"), -64, 384, Arc::new(BlockRegistry));
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new())).with_storage(storage)
+}
+
+#[test]
+fn bounds_lerp_and_damage() {
+    let mut border = WorldBorder::default();
+    assert_eq!(border.size(), MAX_SIZE);
+    border.set_size(20.0);
+    assert!(border.contains(-10.0, 9.9));
+    assert!(!border.contains(10.0, 0.0));
+    assert!(border.contains_block(BlockPos::new(9, 64, -10)));
+    assert!(!border.contains_block(BlockPos::new(10, 64, 0)));
+
+    border.lerp_size(10.0, 20);
+    assert_eq!(border.remaining_millis(), 1000);
+    for _ in 0..10 {
+        border.tick();
+    }
+    assert_eq!(border.size(), 15.0);
+    for _ in 0..20 {
+        border.tick();
+    }
+    assert_eq!((border.size(), border.remaining_ticks()), (10.0, 0));
+
+    // Within the buffer nothing happens, past it at least one point.
+    assert_eq!(border.damage(9.0, 0.0), 0.0);
+    assert_eq!(border.damage(10.0, 0.0), 0.0);
+    assert_eq!(border.damage(11.0, 0.0), 1.0);
+    assert_eq!(border.damage(15.0, 0.0), 1.0);
+    assert_eq!(border.damage(35.0, 0.0), 5.0);
+
+    let (x, z) = border.clamp_move((4.0, 0.0), (8.0, 2.0));
+    assert!(x < 5.0 && z == 2.0);
+    assert_eq!(border.clamp_move((9.0, 0.0), (8.0, 0.0)), (8.0, 0.0));
+}
+
+#[test]
+fn changes_and_building() {
+    let dir = TempDir::new("border-changes");
+    let dimension = dimension(&dir);
+    dimension.set_border_center(100.0, -50.0);
+    dimension.set_border_size(64.0);
+    dimension.lerp_border_size(32.0, 100);
+    dimension.set_border_warning_blocks(8);
+    dimension.set_border_damage(1.0, 0.0);
+    assert_eq!(
+        dimension.take_border_changes(),
+        vec![
+            BorderChange::Center { x: 100.0, z: -50.0 },
+            BorderChange::Size(64.0),
+            BorderChange::Lerp {
+                from: 64.0,
+                to: 32.0,
+                millis: 5000
+            },
+            BorderChange::WarningBlocks(8),
+        ]
+    );
+    assert!(dimension.take_border_changes().is_empty());
+
+    assert!(dimension.can_build(BlockPos::new(100, 0, -50)));
+    assert!(!dimension.can_build(BlockPos::new(0, 0, 0)));
+    assert!(!dimension.can_build(BlockPos::new(100, 320, -50)));
+}
+
+#[test]
+fn saved_with_the_dimension() {
+    let dir = TempDir::new("border-saved");
+    let dimension = dimension(&dir);
+    dimension.set_border_center(8.0, 8.0);
+    dimension.set_border_size(1000.0);
+    dimension.lerp_border_size(500.0, 200);
+    dimension.tick_border();
+    dimension.set_border_warning_time(30);
+    dimension.save_chunks().unwrap();
+    assert!(dir.join("data/minecraft/world_border.dat").exists());
+
+    let loaded = self::dimension(&dir).border();
+    let saved = dimension.border();
+    assert_eq!((loaded.center_x, loaded.center_z), (8.0, 8.0));
+    assert_eq!(loaded.size(), saved.size());
+    assert_eq!(loaded.target_size(), 500.0);
+    assert_eq!(loaded.remaining_ticks(), 199);
+    assert_eq!(loaded.warning_time, 30);
+}
+
+#[test]
+fn ticks_carry_border_changes() {
+    let dimension = Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()));
+    dimension.lerp_border_size(32.0, 2);
+    let tick = dimension.tick().unwrap();
+    assert!(matches!(
+        tick.border[..],
+        [BorderChange::Lerp { to: 32.0, .. }]
+    ));
+    dimension.tick().unwrap();
+    assert_eq!(dimension.border().size(), 32.0);
+    assert!(dimension.tick().unwrap().border.is_empty());
+}
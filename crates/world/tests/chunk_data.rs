@@ -0,0 +1,84 @@
+use bytes::Buf;
+use protocol::{serial::PacketRead, types::var_int::VarInt};
+use world::{
+    block::Block,
+    generator::{WorldGenerator, flat::FlatGenerator},
+};
+
+fn var_int(buffer: &mut &[u8]) -> i32 {
+    VarInt::read(buffer).unwrap().0
+}
+
+fn longs(buffer: &mut &[u8]) -> Vec<i64> {
+    let len = var_int(buffer);
+    (0..len).map(|_| buffer.get_i64()).collect()
+}
+
+#[test]
+fn flat_chunk_encodes_every_part() {
+    let chunk = FlatGenerator::new().generate_chunk(3, -2);
+    let bytes = chunk.encoded().unwrap();
+    let mut buffer = &bytes[..];
+
+    assert_eq!(var_int(&mut buffer), 3);
+    for kind in [1, 4, 5] {
+        assert_eq!(var_int(&mut buffer), kind);
+        let heights = longs(&mut buffer);
+        // 9 bits per column for 384 blocks of height, 7 columns per long.
+        assert_eq!(heights.len(), 37);
+        // The top layer sits at y=4, 69 blocks above the bottom at y=-64.
+        assert_eq!(heights[0] & 0x1ff, 69);
+    }
+
+    let data_len = var_int(&mut buffer) as usize;
+    let (mut data, rest) = buffer.split_at(data_len);
+    buffer = rest;
+    let mut counts = Vec::new();
+    while !data.is_empty() {
+        let count = data.get_i16();
+        let bits = data.get_u8();
+        if bits == 0 {
+            var_int(&mut data);
+        } else {
+            let palette = var_int(&mut data);
+            (0..palette).for_each(|_| _ = var_int(&mut data));
+            data.advance(4096 / (64 / bits as usize) * 8);
+        }
+        assert_eq!(data.get_u8(), 0);
+        assert_eq!(var_int(&mut data), 40);
+        counts.push(count);
+    }
+    // Only the section holding y=0..16 has the five layers.
+    assert_eq!(counts.len(), 24);
+    assert_eq!(counts[4], 5 * 256);
+    assert_eq!(counts.iter().filter(|&&count| count == 0).count(), 23);
+
+    assert_eq!(var_int(&mut buffer), 0);
+
+    // Nothing is lit until the light engine runs, except the open sky above.
+    let all = (1 << 26) - 1;
+    assert_eq!(longs(&mut buffer), [1 << 25]);
+    assert_eq!(longs(&mut buffer), [0]);
+    assert_eq!(longs(&mut buffer), [all & !(1 << 25)]);
+    assert_eq!(longs(&mut buffer), [all]);
+    assert_eq!(var_int(&mut buffer), 1);
+    assert_eq!(var_int(&mut buffer), 2048);
+    assert!(buffer[..2048].iter().all(|&byte| byte == 0xff));
+    buffer.advance(2048);
+    assert_eq!(var_int(&mut buffer), 0);
+    assert!(buffer.is_empty());
+}
+
+#[test]
+fn cache_is_dropped_on_modification() {
+    let mut chunk = FlatGenerator::new().generate_chunk(0, 0);
+    let first = chunk.encoded().unwrap();
+    assert_eq!(first.as_ptr(), chunk.encoded().unwrap().as_ptr());
+
+    chunk.set_block(1, 70, 1, Block::STONE.default_state());
+    let second = chunk.encoded().unwrap();
+    assert_ne!(first, second);
+
+    let copy = chunk.clone();
+    assert_eq!(copy.encoded().unwrap(), second);
+}
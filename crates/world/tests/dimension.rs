@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+
+use nbt::Value;
+use world::{
+    Dimension, DimensionId, World,
+    dimension::{DimensionError, DimensionType, DimensionTypes},
+    generator::flat::{FlatGenerator, FlatSettings},
+    loader::LoaderError,
+};
+
+fn id(input: &str) -> DimensionId {
+    input.parse().unwrap()
+}
+
+#[test]
+fn identifiers() {
+    assert_eq!(id("overworld"), DimensionId::overworld());
+    assert_eq!(id("myteam:lobby/spawn").as_str(), "myteam:lobby/spawn");
+    for invalid in ["", "My:lobby", "myteam:", "my/team:lobby"] {
+        assert!(invalid.parse::<DimensionId>().is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn types_from_config() {
+    let config = serde_json::json!({
+        "myteam:lobby": { "min_y": 0, "height": 128, "logical_height": 128, "fixed_time": 6000 },
+        "minecraft:overworld": { "ambient_light": 0.5 },
+    });
+    let types: BTreeMap<DimensionId, DimensionType> = serde_json::from_value(config).unwrap();
+    let world = World::new().with_types(types).unwrap();
+
+    // Overriding a vanilla type keeps its id, new ones go last.
+    assert_eq!(world.types().network_id(&DimensionId::overworld()), Some(0));
+    assert_eq!(
+        world
+            .types()
+            .get(&DimensionId::overworld())
+            .unwrap()
+            .ambient_light,
+        0.5
+    );
+    assert_eq!(world.types().network_id(&id("myteam:lobby")), Some(4));
+    let types = world.types();
+    let lobby = types.get(&id("myteam:lobby")).unwrap();
+    assert_eq!(
+        (lobby.min_y, lobby.height, lobby.fixed_time),
+        (0, 128, Some(6000))
+    );
+    assert!(lobby.has_skylight);
+
+    let nbt = lobby.to_nbt();
+    assert_eq!(nbt.get("has_skylight"), Some(&Value::Byte(1)));
+    assert_eq!(nbt.get_i64("fixed_time"), Some(6000));
+    assert!(
+        !DimensionType::overworld()
+            .to_nbt()
+            .contains_key("fixed_time")
+    );
+
+    let mut types = DimensionTypes::vanilla();
+    let crooked = DimensionType {
+        height: 100,
+        ..DimensionType::overworld()
+    };
+    assert!(matches!(
+        types.register(id("myteam:crooked"), crooked),
+        Err(DimensionError::InvalidHeight(_))
+    ));
+}
+
+#[test]
+fn worlds_keyed_by_identifier() {
+    let nether = Dimension::new(DimensionId::nether(), Box::new(FlatGenerator::new()));
+    assert_eq!((nether.min_y(), nether.height()), (0, 256));
+    let overworld = Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()));
+    assert_eq!((overworld.min_y(), overworld.height()), (-64, 384));
+
+    let world = World::new();
+    world.add_dimension(overworld).unwrap();
+    let arena = DimensionType {
+        fixed_time: Some(18000),
+        ..DimensionType::overworld()
+    };
+    let lobby = Dimension::new(id("myteam:lobby"), Box::new(FlatGenerator::new()))
+        .with_type(id("myteam:arena"), arena.clone());
+    world.add_dimension(lobby).unwrap();
+    assert_eq!(world.types().get(&id("myteam:arena")), Some(&arena));
+    assert_eq!(
+        world.get_dimension(&id("myteam:lobby")).unwrap().type_id,
+        id("myteam:arena")
+    );
+
+    let again = Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()));
+    assert_eq!(
+        world.add_dimension(again).err(),
+        Some(DimensionError::Duplicate(DimensionId::overworld()))
+    );
+    let other = Dimension::new(id("myteam:other"), Box::new(FlatGenerator::new()))
+        .with_type(id("myteam:arena"), DimensionType::overworld());
+    assert_eq!(
+        world.add_dimension(other).err(),
+        Some(DimensionError::TypeMismatch(id("myteam:arena")))
+    );
+
+    assert!(world.remove_dimension(&id("myteam:lobby")).is_some());
+    assert!(world.get_dimension(&id("myteam:lobby")).is_none());
+}
+
+#[test]
+fn generators_must_match_their_type() {
+    // The default flat generator is overworld height, the nether's type isn't.
+    let nether = Dimension::new(DimensionId::nether(), Box::new(FlatGenerator::new()));
+    assert!(matches!(
+        nether.load_chunk(0, 0),
+        Err(LoaderError::WrongHeight(-64, 384, 0, 256))
+    ));
+    assert!(nether.chunks.is_empty());
+    let world = World::new();
+    assert!(matches!(
+        world.add_dimension(nether),
+        Err(DimensionError::InvalidHeight(_))
+    ));
+
+    let generator = FlatGenerator::with_settings(&FlatSettings::classic(), 0, 256).unwrap();
+    let nether = Dimension::new(DimensionId::nether(), Box::new(generator));
+    nether.load_chunk(0, 0).unwrap();
+    assert_eq!(nether.chunks.get(&(0, 0)).unwrap().height(), 256);
+    world.add_dimension(nether).unwrap();
+}
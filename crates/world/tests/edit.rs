@@ -0,0 +1,139 @@
+use world::{
+    Dimension, DimensionId,
+    block::{Block, BlockState},
+    edit::Cuboid,
+    generator::flat::FlatGenerator,
+    heightmap::HeightmapKind,
+    pos::BlockPos,
+    update::BlockFlags,
+};
+
+// The legacy flat world has grass at y 4 and air from y 5.
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+fn cuboid(from: (i32, i32, i32), to: (i32, i32, i32)) -> Cuboid {
+    Cuboid::new(
+        BlockPos::new(from.0, from.1, from.2),
+        BlockPos::new(to.0, to.1, to.2),
+    )
+}
+
+#[test]
+fn fill_whole_and_partial_sections() {
+    let dimension = dimension();
+    let stone = Block::STONE.default_state();
+    assert_eq!(
+        dimension
+            .fill(cuboid((0, 0, 0), (15, 15, 15)), stone)
+            .unwrap(),
+        4096
+    );
+    assert_eq!(dimension.get_block(BlockPos::new(7, 15, 7)), stone);
+    let chunk = dimension.chunks.get(&(0, 0)).unwrap();
+    assert_eq!(chunk.height_at(HeightmapKind::WorldSurface, 3, 3), 16);
+    drop(chunk);
+
+    let changes = dimension.take_block_changes();
+    assert_eq!(changes.len(), 1);
+    assert_eq!(changes[0].blocks.len(), 4096);
+
+    // Filling again changes nothing, so nothing is sent.
+    assert_eq!(
+        dimension
+            .fill(cuboid((0, 0, 0), (15, 15, 15)), stone)
+            .unwrap(),
+        0
+    );
+    assert!(dimension.take_block_changes().is_empty());
+
+    // Across a chunk border, only part of each section.
+    let glass = Block::GLASS.default_state();
+    assert_eq!(
+        dimension
+            .fill(cuboid((17, 5, 2), (14, 6, 2)), glass)
+            .unwrap(),
+        8
+    );
+    assert_eq!(dimension.get_block(BlockPos::new(14, 6, 2)), glass);
+    assert_eq!(dimension.get_block(BlockPos::new(17, 5, 2)), glass);
+    assert!(dimension.get_block(BlockPos::new(18, 5, 2)).is_air());
+    let changes = dimension.take_block_changes();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[0].blocks.len(), 4);
+    assert_eq!(changes[1].blocks.len(), 4);
+}
+
+#[test]
+fn copy_and_paste() {
+    let dimension = dimension();
+    let region = cuboid((0, 0, 0), (31, 20, 15));
+    dimension
+        .fill(cuboid((2, 5, 2), (20, 17, 3)), Block::OAK_PLANKS.into())
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(0, 16, 0),
+            Block::GLASS.into(),
+            BlockFlags::NONE,
+        )
+        .unwrap();
+    let clipboard = dimension.copy(region).unwrap();
+    assert_eq!(
+        clipboard.get(BlockPos::new(2, 5, 2)),
+        Block::OAK_PLANKS.into()
+    );
+    assert!(clipboard.get(BlockPos::new(40, 5, 2)).is_air());
+
+    // Whole sections along the section grid, block by block elsewhere.
+    for at in [BlockPos::new(64, 0, 32), BlockPos::new(-37, 3, 5)] {
+        let changed = dimension.paste(&clipboard, at).unwrap();
+        assert!(changed > 0);
+        for pos in region.positions() {
+            let target = BlockPos::new(at.x + pos.x, at.y + pos.y, at.z + pos.z);
+            assert_eq!(dimension.get_block(target), dimension.get_block(pos));
+        }
+        assert_eq!(dimension.paste(&clipboard, at).unwrap(), 0);
+    }
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let dimension = dimension();
+    let arena = cuboid((-20, 0, -20), (40, 40, 40));
+    let snapshot = dimension.snapshot(arena.chunks()).unwrap();
+    assert_eq!(snapshot.len(), 25);
+    let before = dimension.copy(arena).unwrap();
+
+    dimension
+        .fill(cuboid((-10, 3, -10), (30, 30, 30)), Block::STONE.into())
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(0, 35, 0),
+            Block::SAND.into(),
+            BlockFlags::NONE,
+        )
+        .unwrap();
+    dimension.take_block_changes();
+
+    let changed = dimension.restore(&snapshot).unwrap();
+    assert_eq!(changed, 41 * 28 * 41 + 1);
+    for pos in arena.positions() {
+        let min = arena.min;
+        let relative = BlockPos::new(pos.x - min.x, pos.y - min.y, pos.z - min.z);
+        assert_eq!(dimension.get_block(pos), before.get(relative));
+    }
+    let sent = dimension
+        .take_block_changes()
+        .iter()
+        .map(|section| section.blocks.len())
+        .sum::<usize>();
+    assert_eq!(sent, changed);
+    assert_eq!(
+        dimension.get_block(BlockPos::new(0, 35, 0)),
+        BlockState::AIR
+    );
+    assert_eq!(dimension.restore(&snapshot).unwrap(), 0);
+}
@@ -0,0 +1,115 @@
+use world::{
+    biome,
+    block::Block,
+    generator::{
+        WorldGenerator,
+        flat::{FlatError, FlatGenerator, FlatLayer, FlatSettings},
+    },
+    heightmap::HeightmapKind,
+};
+
+#[test]
+fn preset_strings_round_trip() {
+    let preset = "minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block;minecraft:plains";
+    let settings: FlatSettings = preset.parse().unwrap();
+    assert_eq!(settings, FlatSettings::classic());
+    assert_eq!(settings.to_string(), preset);
+
+    let settings: FlatSettings = " minecraft:stone , 3*sandstone ".parse().unwrap();
+    assert_eq!(
+        settings.layers,
+        vec![
+            FlatLayer::new("minecraft:stone", 1),
+            FlatLayer::new("sandstone", 3)
+        ]
+    );
+    assert_eq!(settings.biome, "minecraft:plains");
+
+    assert_eq!(
+        "x*minecraft:dirt".parse::<FlatSettings>(),
+        Err(FlatError::MalformedLayer("x*minecraft:dirt".to_string()))
+    );
+}
+
+#[test]
+fn settings_deserialize_from_either_form() {
+    let preset: FlatSettings =
+        serde_json::from_str(r#""minecraft:bedrock,2*minecraft:dirt,minecraft:grass_block""#)
+            .unwrap();
+    assert_eq!(preset, FlatSettings::classic());
+
+    let fields: FlatSettings = serde_json::from_str(
+        r#"{"layers": [{"block": "minecraft:bedrock"}, {"block": "minecraft:dirt", "height": 2},
+            {"block": "minecraft:grass_block", "height": 1}]}"#,
+    )
+    .unwrap();
+    assert_eq!(fields, FlatSettings::classic());
+}
+
+#[test]
+fn layers_stack_from_min_y() {
+    let settings =
+        "minecraft:bedrock,60*minecraft:stone,2*minecraft:dirt,minecraft:grass_block;desert"
+            .parse::<FlatSettings>()
+            .unwrap();
+    let generator = FlatGenerator::with_settings(&settings, -64, 384).unwrap();
+    assert_eq!(generator.surface_y(), 0);
+
+    let chunk = generator.generate_chunk(2, -5);
+    assert_eq!(chunk.get_block(3, -64, 3), Block::BEDROCK.default_state());
+    assert_eq!(chunk.get_block(3, -20, 3), Block::STONE.default_state());
+    assert_eq!(chunk.get_block(3, -2, 3), Block::DIRT.default_state());
+    assert_eq!(
+        chunk.get_block(3, -1, 3),
+        Block::GRASS_BLOCK.default_state()
+    );
+    assert!(chunk.get_block(3, 0, 3).is_air());
+    assert_eq!(chunk.height_at(HeightmapKind::WorldSurface, 9, 9), 0);
+    assert_eq!(chunk.sections[3].blocks_count, 4096);
+    assert_eq!(chunk.sections[4].blocks_count, 0);
+    assert_eq!(
+        chunk.sections[10].get_biome(0, 0, 0),
+        biome::biome_id("desert").unwrap()
+    );
+}
+
+#[test]
+fn void_and_validation() {
+    let generator = FlatGenerator::with_settings(&FlatSettings::void(), 0, 256).unwrap();
+    let chunk = generator.generate_chunk(0, 0);
+    assert_eq!(chunk.height(), 256);
+    assert!(
+        chunk
+            .sections
+            .iter()
+            .all(|section| section.blocks_count == 0)
+    );
+    assert_eq!(
+        FlatSettings::preset("minecraft:the_void"),
+        Some(FlatSettings::void())
+    );
+
+    let tall = FlatSettings {
+        layers: vec![FlatLayer::new("minecraft:stone", 129)],
+        biome: "minecraft:plains".to_string(),
+    };
+    assert!(matches!(
+        FlatGenerator::with_settings(&tall, 0, 128),
+        Err(FlatError::TooTall {
+            layers: 129,
+            height: 128
+        })
+    ));
+    assert!(FlatGenerator::with_settings(&tall, -64, 384).is_ok());
+
+    let unknown = "minecraft:bedrock;minecraft:nowhere".parse().unwrap();
+    assert_eq!(
+        FlatGenerator::with_settings(&unknown, -64, 384).err(),
+        Some(FlatError::UnknownBiome("minecraft:nowhere".to_string()))
+    );
+    let unknown = "minecraft:nothing".parse().unwrap();
+    assert!(matches!(
+        FlatGenerator::with_settings(&unknown, -64, 384),
+        Err(FlatError::Block(_))
+    ));
+}
@@ -0,0 +1,83 @@
+use world::{
+    block::Block,
+    generator::{WorldGenerator, flat::FlatGenerator},
+    heightmap::{
+        Heightmap,
+        HeightmapKind::{self, MotionBlocking, MotionBlockingNoLeaves, OceanFloor, WorldSurface},
+    },
+};
+
+fn heights(chunk: &world::chunk::Chunk, x: usize, z: usize) -> [i32; 4] {
+    HeightmapKind::ALL.map(|kind| chunk.height_at(kind, x, z))
+}
+
+#[test]
+fn heightmaps_follow_block_changes() {
+    let mut chunk = FlatGenerator::new().generate_chunk(0, 0);
+    assert_eq!(heights(&chunk, 7, 7), [5; 4]);
+
+    chunk.set_block(7, 10, 7, Block::OAK_LEAVES.default_state());
+    chunk.set_block(7, 12, 7, Block::WATER.default_state());
+    chunk.set_block(7, 20, 7, Block::TORCH.default_state());
+    assert_eq!(chunk.height_at(WorldSurface, 7, 7), 21);
+    assert_eq!(chunk.height_at(MotionBlocking, 7, 7), 13);
+    assert_eq!(chunk.height_at(MotionBlockingNoLeaves, 7, 7), 13);
+    assert_eq!(chunk.height_at(OceanFloor, 7, 7), 11);
+    assert_eq!(heights(&chunk, 6, 7), [5; 4]);
+
+    chunk.set_block(7, 12, 7, Block::AIR.default_state());
+    assert_eq!(chunk.height_at(MotionBlocking, 7, 7), 11);
+    assert_eq!(chunk.height_at(MotionBlockingNoLeaves, 7, 7), 5);
+
+    chunk.set_block(7, 20, 7, Block::AIR.default_state());
+    chunk.set_block(7, 10, 7, Block::AIR.default_state());
+    assert_eq!(heights(&chunk, 7, 7), [5; 4]);
+
+    chunk.fill_section(10, Block::STONE.default_state());
+    assert_eq!(heights(&chunk, 0, 15), [112; 4]);
+    chunk.fill_layer(200, Block::GLASS.default_state());
+    assert_eq!(heights(&chunk, 3, 3), [201; 4]);
+    chunk.fill_layer(200, Block::AIR.default_state());
+    chunk.fill_section(10, Block::AIR.default_state());
+    assert_eq!(heights(&chunk, 15, 0), [5; 4]);
+
+    chunk.fill_layer(4, Block::AIR.default_state());
+    chunk.fill_layer(3, Block::AIR.default_state());
+    chunk.fill_layer(2, Block::AIR.default_state());
+    chunk.fill_layer(1, Block::AIR.default_state());
+    chunk.fill_layer(0, Block::AIR.default_state());
+    assert_eq!(heights(&chunk, 8, 8), [-64; 4]);
+}
+
+#[test]
+fn recompute_matches_incremental_updates() {
+    let mut chunk = FlatGenerator::new().generate_chunk(0, 0);
+    for i in 0..200usize {
+        let state = [Block::OAK_LEAVES, Block::WATER, Block::STONE, Block::AIR][i % 4];
+        chunk.set_block(
+            i % 16,
+            (i * 7 % 90) as i32 - 20,
+            i / 16,
+            state.default_state(),
+        );
+    }
+    let incremental = chunk.heightmaps.clone();
+    chunk.recompute_heightmaps();
+    assert_eq!(chunk.heightmaps, incremental);
+}
+
+#[test]
+fn packed_format() {
+    let mut heightmap = Heightmap::new();
+    heightmap.set(0, 0, 384);
+    heightmap.set(6, 0, 1);
+    heightmap.set(15, 15, 69);
+
+    let packed = heightmap.pack(384);
+    // 9 bits per column, 7 columns per long.
+    assert_eq!(packed.len(), 37);
+    assert_eq!(packed[0], 384 | (1 << 54));
+    assert_eq!(packed[36], 69 << 27);
+    assert_eq!(Heightmap::unpack(&packed, 384), Some(heightmap));
+    assert_eq!(Heightmap::unpack(&packed[1..], 384), None);
+}
@@ -0,0 +1,110 @@
+use world::{
+    Dimension, DimensionId,
+    block::Block,
+    generator::flat::FlatGenerator,
+    light::LightKind::{Block as BlockLight, Sky},
+};
+
+fn flat_dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+fn set_block(dimension: &Dimension, x: i32, y: i32, z: i32, block: Block) {
+    let (chunk_x, chunk_z) = (x >> 4, z >> 4);
+    dimension
+        .chunks
+        .get_mut(&(chunk_x, chunk_z))
+        .unwrap()
+        .set_block(
+            (x & 15) as usize,
+            y,
+            (z & 15) as usize,
+            block.default_state(),
+        );
+    dimension.update_light(chunk_x, chunk_z);
+}
+
+fn light(dimension: &Dimension, kind: world::light::LightKind, x: i32, y: i32, z: i32) -> u8 {
+    dimension.chunks.get(&(x >> 4, z >> 4)).unwrap().light(
+        kind,
+        (x & 15) as usize,
+        y,
+        (z & 15) as usize,
+    )
+}
+
+#[test]
+fn flat_chunk_is_lit_from_the_sky() {
+    let dimension = flat_dimension();
+    dimension.load_chunk(0, 0).unwrap();
+
+    assert_eq!(light(&dimension, Sky, 3, 300, 3), 15);
+    assert_eq!(light(&dimension, Sky, 3, 5, 3), 15);
+    assert_eq!(light(&dimension, Sky, 3, 4, 3), 0);
+    assert_eq!(light(&dimension, Sky, 3, -30, 3), 0);
+    assert_eq!(light(&dimension, BlockLight, 3, 5, 3), 0);
+}
+
+#[test]
+fn roof_shades_and_unshades() {
+    let dimension = flat_dimension();
+    dimension.load_chunk(0, 0).unwrap();
+
+    set_block(&dimension, 8, 10, 8, Block::STONE);
+    assert_eq!(light(&dimension, Sky, 8, 10, 8), 0);
+    // Light comes in from the open columns next to it.
+    assert_eq!(light(&dimension, Sky, 8, 9, 8), 14);
+    assert_eq!(light(&dimension, Sky, 8, 5, 8), 14);
+
+    set_block(&dimension, 8, 10, 8, Block::AIR);
+    assert_eq!(light(&dimension, Sky, 8, 10, 8), 15);
+    assert_eq!(light(&dimension, Sky, 8, 5, 8), 15);
+
+    set_block(&dimension, 8, 10, 8, Block::OAK_LEAVES);
+    assert_eq!(light(&dimension, Sky, 8, 10, 8), 14);
+    assert_eq!(light(&dimension, Sky, 8, 9, 8), 14);
+}
+
+#[test]
+fn torch_light_spreads_and_is_removed() {
+    let dimension = flat_dimension();
+    dimension.load_chunk(0, 0).unwrap();
+
+    set_block(&dimension, 4, 5, 4, Block::TORCH);
+    assert_eq!(light(&dimension, BlockLight, 4, 5, 4), 14);
+    assert_eq!(light(&dimension, BlockLight, 5, 5, 4), 13);
+    assert_eq!(light(&dimension, BlockLight, 4, 8, 7), 8);
+    assert_eq!(light(&dimension, BlockLight, 4, 4, 4), 0);
+
+    set_block(&dimension, 5, 5, 4, Block::STONE);
+    assert_eq!(light(&dimension, BlockLight, 5, 5, 4), 0);
+    assert_eq!(light(&dimension, BlockLight, 6, 5, 4), 10);
+
+    set_block(&dimension, 4, 5, 4, Block::AIR);
+    for x in 0..16 {
+        for y in 5..20 {
+            assert_eq!(light(&dimension, BlockLight, x, y, 4), 0);
+        }
+    }
+}
+
+#[test]
+fn light_crosses_chunk_borders() {
+    let dimension = flat_dimension();
+    dimension.load_chunk(0, 0).unwrap();
+
+    set_block(&dimension, 15, 5, 0, Block::GLOWSTONE);
+    // The neighbor is lit from the border when it loads.
+    dimension.load_chunk(1, 0).unwrap();
+    assert_eq!(light(&dimension, BlockLight, 16, 5, 0), 14);
+    dimension.load_chunk(0, -1).unwrap();
+    assert_eq!(light(&dimension, BlockLight, 15, 5, -1), 14);
+
+    set_block(&dimension, 15, 5, 0, Block::AIR);
+    assert_eq!(light(&dimension, BlockLight, 16, 5, 0), 0);
+    assert_eq!(light(&dimension, BlockLight, 15, 5, -1), 0);
+
+    // And lit directly when it is already loaded.
+    set_block(&dimension, 17, 5, 0, Block::TORCH);
+    assert_eq!(light(&dimension, BlockLight, 15, 5, 0), 12);
+}
@@ -0,0 +1,94 @@
+use bytes::BytesMut;
+use protocol::serial::PacketWrite;
+use world::{
+    block::Block,
+    chunk::ChunkSection,
+    palette::{Palette, PalettedContainer, Strategy},
+};
+
+fn encode(value: &impl PacketWrite) -> Vec<u8> {
+    let mut buffer = BytesMut::new();
+    value.write(&mut buffer).unwrap();
+    buffer.to_vec()
+}
+
+#[test]
+fn grows_from_single_to_direct() {
+    let mut container = PalettedContainer::new(Strategy::BLOCKS, 0);
+    assert!(matches!(container.palette(), Palette::Single(0)));
+    assert_eq!(container.bits(), 0);
+
+    container.set(10, 7);
+    assert!(matches!(container.palette(), Palette::Indirect(p) if p.len() == 2));
+    assert_eq!(container.bits(), 4);
+
+    for i in 0..300u16 {
+        container.set(i as usize * 13, i + 100);
+        if i == 20 {
+            assert_eq!(container.bits(), 5);
+        }
+    }
+    assert!(matches!(container.palette(), Palette::Direct));
+    assert_eq!(container.bits(), 15);
+
+    assert_eq!(container.get(10), 7);
+    for i in 0..300u16 {
+        assert_eq!(container.get(i as usize * 13), i + 100);
+    }
+    assert_eq!(container.get(1), 0);
+    assert_eq!(container.count(|id| id != 0), 301);
+
+    container.fill(3);
+    assert!(matches!(container.palette(), Palette::Single(3)));
+    assert_eq!(container.count(|id| id == 3), 4096);
+}
+
+#[test]
+fn from_values_picks_the_smallest_layout() {
+    let same = PalettedContainer::from_values(Strategy::BIOMES, &[40; 64]);
+    assert!(matches!(same.palette(), Palette::Single(40)));
+
+    let values: Vec<u16> = (0..64).map(|i| (i % 3) as u16).collect();
+    let mixed = PalettedContainer::from_values(Strategy::BIOMES, &values);
+    assert_eq!(mixed.bits(), 2);
+    assert!(mixed.iter().eq(values.iter().copied()));
+
+    let values: Vec<u16> = (0..64).map(|i| (i % 9) as u16).collect();
+    let direct = PalettedContainer::from_values(Strategy::BIOMES, &values);
+    assert!(matches!(direct.palette(), Palette::Direct));
+    assert_eq!(direct.bits(), 7);
+}
+
+#[test]
+fn wire_format() {
+    let single = PalettedContainer::new(Strategy::BLOCKS, 300);
+    assert_eq!(encode(&single), [0, 0xac, 0x02]);
+
+    let mut indirect = PalettedContainer::new(Strategy::BIOMES, 1);
+    indirect.set(1, 2);
+    let bytes = encode(&indirect);
+    // bits, palette length and entries, then 64 one bit entries in one long.
+    assert_eq!(&bytes[..4], [1, 2, 1, 2]);
+    assert_eq!(&bytes[4..], 2u64.to_be_bytes());
+
+    let mut section = ChunkSection::new();
+    section.set_block(0, 0, 0, Block::GRASS_BLOCK.default_state());
+    section.set_block(1, 0, 0, Block::GRASS_BLOCK.default_state());
+    let bytes = encode(&section);
+    assert_eq!(&bytes[..2], [0, 2]);
+    // 4 bit block indices pack 16 per long.
+    assert_eq!(bytes.len(), 2 + 1 + 1 + 2 + 256 * 8 + 2);
+}
+
+#[test]
+fn unknown_state_ids_read_as_air() {
+    let mut section = ChunkSection::new();
+    section.set_block(0, 0, 0, Block::GLOWSTONE.default_state());
+    let changed = section.set_blocks(PalettedContainer::new(Strategy::BLOCKS, u16::MAX));
+    assert_eq!(changed.len(), 4096);
+
+    let state = section.get_block(0, 0, 0);
+    assert_eq!(state.block(), Block::AIR);
+    assert_eq!((state.opacity(), state.light_emission()), (0, 0));
+    assert_eq!(state.value("facing"), None);
+}
@@ -0,0 +1,146 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+};
+
+use events::EventBus;
+use world::{
+    Dimension, DimensionId,
+    block::BlockRegistry,
+    generator::{
+        flat::FlatGenerator,
+        pregen::{PregenJob, PregenProgressEvent, Pregenerator},
+    },
+    loader::RegionStorage,
+};
+
+mod common;
+
+use common::TempDir;
+
+fn stored_dimension(dir: &Path) -> Arc<Dimension> {
+    let storage = RegionStorage::new(dir.join("region"), -64, 384, Arc::new(BlockRegistry));
+    Arc::new(
+        Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+            .with_storage(storage),
+    )
+}
+
+#[test]
+fn spiral_visits_every_chunk_once_nearest_first() {
+    let spiral = Pregenerator::new(6).with_center(10, -3);
+    let chunks = spiral.collect::<Vec<_>>();
+    assert_eq!(chunks.len(), 13 * 13);
+    assert_eq!(chunks.iter().collect::<HashSet<_>>().len(), chunks.len());
+    assert_eq!(chunks[0], (10, -3));
+
+    let rings = chunks
+        .iter()
+        .map(|(x, z)| (x - 10).abs().max((z + 3).abs()))
+        .collect::<Vec<_>>();
+    assert!(rings.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(*rings.last().unwrap(), 6);
+
+    // Consecutive chunks within a ring are neighbors.
+    for pair in chunks[1..9].windows(2) {
+        let (a, b) = (pair[0], pair[1]);
+        assert_eq!((a.0 - b.0).abs() + (a.1 - b.1).abs(), 1);
+    }
+}
+
+#[test]
+fn job_loads_chunks_without_storage() {
+    let dimension = Arc::new(Dimension::new(
+        DimensionId::overworld(),
+        Box::new(FlatGenerator::new()),
+    ));
+    let bus = Arc::new(EventBus::new());
+    let reports = Arc::new(AtomicUsize::new(0));
+    let counter = reports.clone();
+    bus.subscribe(move |event: &mut PregenProgressEvent| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        assert!(event.progress.done <= event.progress.total);
+        Ok(())
+    });
+
+    let handle = PregenJob::new(3)
+        .center(-5, 5)
+        .threads(4)
+        .events(bus)
+        .start(dimension.clone());
+    let progress = handle.wait().unwrap();
+
+    assert!(progress.finished);
+    assert_eq!(
+        (progress.done, progress.total, progress.generated),
+        (49, 49, 49)
+    );
+    assert_eq!(dimension.chunks.len(), 49);
+    assert!(dimension.chunks.contains_key(&(-8, 8)));
+    assert!(reports.load(Ordering::Relaxed) >= 1);
+}
+
+#[test]
+fn job_saves_to_disk_and_skips_existing_chunks() {
+    let dir = TempDir::new("pregen-save");
+    let checkpoint = dir.join("pregen.txt");
+
+    let dimension = stored_dimension(&dir);
+    let handle = PregenJob::new(2)
+        .threads(3)
+        .checkpoint(&checkpoint)
+        .start(dimension.clone());
+    handle.pause();
+    assert!(handle.is_paused());
+    handle.resume();
+    let progress = handle.wait().unwrap();
+    assert_eq!(progress.generated, 25);
+    assert!(dimension.chunks.is_empty());
+    assert!(!checkpoint.exists());
+
+    let storage = dimension.storage.as_ref().unwrap();
+    for (x, z) in Pregenerator::new(2) {
+        assert!(storage.has_chunk(x, z).unwrap());
+    }
+
+    let again = PregenJob::new(2)
+        .start(stored_dimension(&dir))
+        .wait()
+        .unwrap();
+    assert_eq!((again.done, again.generated), (25, 0));
+}
+
+#[test]
+fn job_resumes_from_checkpoint() {
+    let dir = TempDir::new("pregen-resume");
+    let checkpoint = dir.join("pregen.txt");
+    fs::write(&checkpoint, "center 0 0\nradius 2\ndone 9\n").unwrap();
+
+    let dimension = stored_dimension(&dir);
+    let progress = PregenJob::new(2)
+        .checkpoint(&checkpoint)
+        .start(dimension.clone())
+        .wait()
+        .unwrap();
+    assert_eq!((progress.done, progress.generated), (25, 16));
+
+    // The inner ring was already done according to the checkpoint.
+    let storage = dimension.storage.as_ref().unwrap();
+    assert!(!storage.has_chunk(0, 0).unwrap());
+    assert!(storage.has_chunk(2, 2).unwrap());
+    assert!(!checkpoint.exists());
+
+    // A checkpoint for a different job is ignored.
+    fs::write(&checkpoint, "center 4 4\nradius 2\ndone 20\n").unwrap();
+    let progress = PregenJob::new(2)
+        .checkpoint(&checkpoint)
+        .start(stored_dimension(&dir))
+        .wait()
+        .unwrap();
+    assert_eq!(progress.generated, 9);
+}
@@ -0,0 +1,252 @@
+use std::{fs, sync::Arc};
+
+use nbt::{ByteArray, Compound, LongArray, Value, compound};
+use world::{
+    Dimension, DimensionId, biome,
+    block::{Block, BlockRegistry, BlockState},
+    chunk::Chunk,
+    generator::flat::FlatGenerator,
+    loader::{ChunkCompression, LoaderError, RegionFile, RegionStorage, StateMapper},
+};
+
+mod common;
+
+use common::TempDir;
+
+struct TestMapper;
+
+impl StateMapper for TestMapper {
+    fn state_id(&self, state: &Compound) -> Option<u16> {
+        match state.get_str("Name")? {
+            "minecraft:air" => Some(0),
+            name => name.strip_prefix("test:block_")?.parse().ok(),
+        }
+    }
+
+    fn state_nbt(&self, id: u16) -> Option<Compound> {
+        let name = match id {
+            0 => "minecraft:air".to_string(),
+            id => format!("test:block_{}", id),
+        };
+        Some(compound! { "Name" => name })
+    }
+}
+
+fn sample_chunk(x: i32, z: i32) -> Chunk {
+    let mut chunk = Chunk::new(x, z);
+    chunk.fill_layer(-64, Block::BEDROCK.default_state());
+    chunk.fill_layer(-63, Block::DIRT.default_state());
+    chunk.fill_section(10, Block::STONE.default_state());
+    for i in 0..300 {
+        chunk.sections[12].set_block(
+            i % 16,
+            (i / 16) % 16,
+            (i / 7) % 16,
+            BlockState::from_id(i as u16 % 40).unwrap(),
+        );
+    }
+    chunk.sections[3].set_biome(1, 1, 0, biome::biome_id("minecraft:desert").unwrap());
+    chunk.recompute_heightmaps();
+    chunk
+}
+
+fn assert_same(a: &Chunk, b: &Chunk) {
+    assert_eq!((a.x, a.z), (b.x, b.z));
+    assert_eq!(a.heightmaps, b.heightmaps);
+    for (left, right) in a.sections.iter().zip(&b.sections) {
+        assert_eq!(left.blocks, right.blocks);
+        assert_eq!(left.blocks_count, right.blocks_count);
+        assert_eq!(left.biomes, right.biomes);
+    }
+}
+
+#[test]
+fn chunks_round_trip_with_every_compression() {
+    let dir = TempDir::new("round-trip");
+    for compression in [
+        ChunkCompression::Gzip,
+        ChunkCompression::Zlib,
+        ChunkCompression::None,
+        ChunkCompression::Lz4,
+    ] {
+        let storage =
+            RegionStorage::new(&dir, -64, 384, Arc::new(TestMapper)).compression(compression);
+        let chunk = sample_chunk(-33, 5);
+        storage.save_chunk(&chunk).unwrap();
+        storage.flush().unwrap();
+
+        assert!(dir.join("r.-2.0.mca").exists());
+        assert!(storage.has_chunk(-33, 5).unwrap());
+        assert!(!storage.has_chunk(-32, 5).unwrap());
+        let loaded = storage.load_chunk(-33, 5).unwrap().unwrap();
+        assert_same(&chunk, &loaded);
+    }
+    assert!(
+        RegionStorage::new(&dir, -64, 384, Arc::new(TestMapper))
+            .load_chunk(100, 100)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[test]
+fn sectors_are_reused() {
+    let dir = TempDir::new("sectors");
+    let path = dir.join(RegionFile::file_name(0, 0));
+    let mut region = RegionFile::open(&path, 0, 0).unwrap();
+
+    let big =
+        compound! { "data" => ByteArray((0..20_000u32).map(|i| (i * 31 % 251) as u8).collect()) };
+    let small = compound! { "data" => 1 };
+
+    region
+        .write_chunk(0, 0, &big, ChunkCompression::None)
+        .unwrap();
+    region
+        .write_chunk(1, 0, &small, ChunkCompression::None)
+        .unwrap();
+    let len = fs::metadata(&path).unwrap().len();
+    assert_eq!(len % 4096, 0);
+
+    // The new copy never overwrites the old one, so shrinking appends once...
+    region
+        .write_chunk(0, 0, &small, ChunkCompression::None)
+        .unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), len + 4096);
+    // ...and the sectors it released are then reused.
+    region
+        .write_chunk(2, 0, &big, ChunkCompression::None)
+        .unwrap();
+    assert_eq!(fs::metadata(&path).unwrap().len(), len + 4096);
+
+    region.remove_chunk(1, 0).unwrap();
+    assert!(!region.has_chunk(1, 0));
+    drop(region);
+
+    let mut region = RegionFile::open(&path, 0, 0).unwrap();
+    assert_eq!(region.read_chunk(0, 0).unwrap(), Some(small));
+    assert_eq!(region.read_chunk(2, 0).unwrap(), Some(big));
+    assert_eq!(region.read_chunk(1, 0).unwrap(), None);
+    assert!(region.timestamp(2, 0) > 0);
+}
+
+#[test]
+fn overlapping_locations_are_dropped() {
+    let dir = TempDir::new("overlap");
+    let path = dir.join(RegionFile::file_name(0, 0));
+    let mut region = RegionFile::open(&path, 0, 0).unwrap();
+    let first = compound! { "data" => 1 };
+    region
+        .write_chunk(0, 0, &first, ChunkCompression::None)
+        .unwrap();
+    region
+        .write_chunk(1, 0, &compound! { "data" => 2 }, ChunkCompression::None)
+        .unwrap();
+    drop(region);
+
+    // Point chunk 1,0 at the sectors of chunk 0,0.
+    let mut bytes = fs::read(&path).unwrap();
+    bytes.copy_within(0..4, 4);
+    fs::write(&path, bytes).unwrap();
+
+    let mut region = RegionFile::open(&path, 0, 0).unwrap();
+    assert!(!region.has_chunk(1, 0));
+    let other = compound! { "data" => ByteArray(vec![3; 10_000]) };
+    region
+        .write_chunk(1, 0, &other, ChunkCompression::None)
+        .unwrap();
+    assert_eq!(region.read_chunk(0, 0).unwrap(), Some(first));
+    assert_eq!(region.read_chunk(1, 0).unwrap(), Some(other));
+}
+
+#[test]
+fn misplaced_chunks_are_rejected() {
+    let dir = TempDir::new("misplaced");
+    let path = dir.join(RegionFile::file_name(1, 0));
+    let mut region = RegionFile::open(&path, 1, 0).unwrap();
+
+    let placed = compound! { "xPos" => 34, "zPos" => 5 };
+    region
+        .write_chunk(2, 5, &placed, ChunkCompression::Zlib)
+        .unwrap();
+    assert_eq!(region.read_chunk(2, 5).unwrap(), Some(placed));
+
+    let misplaced = compound! { "xPos" => 2, "zPos" => 5 };
+    region
+        .write_chunk(2, 5, &misplaced, ChunkCompression::Zlib)
+        .unwrap();
+    assert!(matches!(
+        region.read_chunk(2, 5),
+        Err(LoaderError::Corrupt(_))
+    ));
+}
+
+#[test]
+fn oversized_chunks_use_external_files() {
+    let dir = TempDir::new("external");
+    let path = dir.join(RegionFile::file_name(1, -1));
+    let mut region = RegionFile::open(&path, 1, -1).unwrap();
+
+    let huge = compound! { "data" => ByteArray(vec![7; 1_200_000]) };
+    region
+        .write_chunk(3, 4, &huge, ChunkCompression::None)
+        .unwrap();
+    let external = dir.join("c.35.-28.mcc");
+    assert!(external.exists());
+    assert!(fs::metadata(&path).unwrap().len() < 16 * 1024);
+    assert_eq!(region.read_chunk(3, 4).unwrap(), Some(huge));
+
+    let small = compound! { "data" => 1 };
+    region
+        .write_chunk(3, 4, &small, ChunkCompression::Lz4)
+        .unwrap();
+    assert!(!external.exists());
+    assert_eq!(region.read_chunk(3, 4).unwrap(), Some(small));
+}
+
+#[test]
+fn unknown_states_keep_the_chunk_on_disk() {
+    let dir = TempDir::new("unknown-states");
+    let regions = dir.join("region");
+    fs::create_dir_all(&regions).unwrap();
+
+    // Laid out as vanilla saves it, with a block the registry doesn't have.
+    let saved = compound! {
+        "DataVersion" => 4440,
+        "Status" => "minecraft:full",
+        "xPos" => 0,
+        "yPos" => -4,
+        "zPos" => 0,
+        "sections" => Value::List(vec![Value::Compound(compound! {
+            "Y" => 0i8,
+            "block_states" => compound! {
+                "palette" => Value::List(vec![
+                    Value::Compound(compound! { "Name" => "minecraft:stone" }),
+                    Value::Compound(compound! {
+                        "Name" => "minecraft:sculk_catalyst",
+                        "Properties" => compound! { "bloom" => "false" },
+                    }),
+                ]),
+                "data" => LongArray(vec![0x0000_0000_0000_0010; 256]),
+            },
+        })]),
+    };
+    let mut region = RegionFile::open(regions.join(RegionFile::file_name(0, 0)), 0, 0).unwrap();
+    region
+        .write_chunk(0, 0, &saved, ChunkCompression::Zlib)
+        .unwrap();
+    drop(region);
+
+    let storage = RegionStorage::new(&regions, -64, 384, Arc::new(BlockRegistry));
+    let dimension = Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+        .with_storage(storage);
+    assert!(matches!(
+        dimension.load_chunk(0, 0),
+        Err(LoaderError::UnknownBlockState(state)) if state.contains("minecraft:sculk_catalyst")
+    ));
+    assert_eq!(dimension.chunks.len(), 0);
+    dimension.save_chunks().unwrap();
+
+    let mut region = RegionFile::open(regions.join(RegionFile::file_name(0, 0)), 0, 0).unwrap();
+    assert_eq!(region.read_chunk(0, 0).unwrap(), Some(saved));
+}
@@ -0,0 +1,259 @@
+use nbt::{Compound, IntArray, Value, compound, list};
+use world::{
+    Dimension, DimensionId,
+    block::{Block, BlockState},
+    generator::flat::FlatGenerator,
+    pos::BlockPos,
+    schematic::{PasteOptions, Schematic, SchematicBlockEntity, SchematicError},
+    transform::{Mirror, Rotation},
+    update::BlockFlags,
+};
+
+// The legacy flat world has grass at y 4 and air from y 5.
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+fn state(input: &str) -> BlockState {
+    input.parse().unwrap()
+}
+
+#[test]
+fn sponge_round_trip() {
+    let mut schematic = Schematic::new(3, 2, 2);
+    schematic.offset = BlockPos::new(-1, 0, 2);
+    schematic.set(BlockPos::new(0, 0, 0), Some(Block::STONE.into()));
+    schematic.set(
+        BlockPos::new(2, 1, 1),
+        Some(state("oak_stairs[facing=west,half=top]")),
+    );
+    schematic.block_entities.push(SchematicBlockEntity {
+        pos: BlockPos::new(1, 0, 1),
+        id: "minecraft:chest".to_string(),
+        data: compound! { "CustomName" => "\"Loot\"" },
+    });
+
+    let path = std::env::temp_dir().join(format!("iridium-{}.schem", std::process::id()));
+    schematic.save(&path).unwrap();
+    let loaded = Schematic::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, schematic);
+}
+
+#[test]
+fn sponge_version_2_and_unknown_blocks() {
+    let root = compound! {
+        "Version" => 2,
+        "Width" => 2i16,
+        "Height" => 1i16,
+        "Length" => 1i16,
+        "Palette" => compound! {
+            "minecraft:air" => 0,
+            "minecraft:oak_stairs[facing=east,waterlogged=false]" => 1,
+            "othermod:gizmo" => 2,
+        },
+        "BlockData" => Value::ByteArray(vec![1, 2]),
+        "BlockEntities" => vec![Value::Compound(compound! {
+            "Pos" => IntArray(vec![0, 0, 0]),
+            "Id" => "minecraft:sign",
+            "Color" => "red",
+        })],
+    };
+    let schematic = Schematic::from_nbt(&root).unwrap();
+    assert_eq!(
+        schematic.get(BlockPos::new(0, 0, 0)),
+        Some(state("oak_stairs[facing=east]"))
+    );
+    assert_eq!(schematic.get(BlockPos::new(1, 0, 0)), Some(BlockState::AIR));
+    assert_eq!(
+        schematic.block_entities[0].data,
+        compound! { "Color" => "red" }
+    );
+}
+
+#[test]
+fn forged_sizes_are_rejected_before_allocating() {
+    let sponge = compound! {
+        "Version" => 2,
+        "Width" => -1i16,
+        "Height" => -1i16,
+        "Length" => -1i16,
+        "Palette" => compound! { "minecraft:stone" => 0 },
+        "BlockData" => Value::ByteArray(vec![0; 16]),
+    };
+    assert!(matches!(
+        Schematic::from_nbt(&sponge),
+        Err(SchematicError::Malformed(_))
+    ));
+
+    let palette_id = compound! {
+        "Version" => 2,
+        "Width" => 1i16,
+        "Height" => 1i16,
+        "Length" => 1i16,
+        "Palette" => compound! { "minecraft:stone" => i32::MAX },
+        "BlockData" => Value::ByteArray(vec![0]),
+    };
+    assert!(matches!(
+        Schematic::from_nbt(&palette_id),
+        Err(SchematicError::Malformed(_))
+    ));
+
+    let structure = compound! {
+        "size" => list![65535, 65535, 65535],
+        "palette" => Vec::<Value>::new(),
+        "blocks" => Vec::<Value>::new(),
+    };
+    assert!(matches!(
+        Schematic::from_nbt(&structure),
+        Err(SchematicError::TooLarge(65535, 65535, 65535))
+    ));
+
+    // Copies are refused before any chunk is loaded.
+    let dimension = dimension();
+    assert!(matches!(
+        Schematic::copy(
+            &dimension,
+            BlockPos::new(0, -64, 0),
+            BlockPos::new(4095, 319, 4095)
+        ),
+        Err(SchematicError::TooLarge(4096, 384, 4096))
+    ));
+    assert!(dimension.chunks.is_empty());
+}
+
+#[test]
+fn structure_files() {
+    let palette = |name: &str, properties: Compound| {
+        Value::Compound(compound! { "Name" => name, "Properties" => properties })
+    };
+    let root = compound! {
+        "DataVersion" => 4671,
+        "size" => list![2, 1, 1],
+        "palette" => vec![
+            palette("minecraft:chest", compound! { "facing" => "south" }),
+            palette("minecraft:stone", Compound::new()),
+        ],
+        "blocks" => vec![Value::Compound(compound! {
+            "state" => 0,
+            "pos" => list![0, 0, 0],
+            "nbt" => compound! { "id" => "minecraft:chest", "Items" => Vec::<Value>::new() },
+        })],
+        "entities" => vec![Value::Compound(compound! {
+            "pos" => list![1.5, 0.0, 0.5],
+            "blockPos" => list![1, 0, 0],
+            "nbt" => compound! { "id" => "minecraft:pig", "Rotation" => list![0.0f32, 0.0f32] },
+        })],
+    };
+    let schematic = Schematic::from_nbt(&root).unwrap();
+    assert_eq!(
+        schematic.get(BlockPos::new(0, 0, 0)),
+        Some(state("chest[facing=south]"))
+    );
+    // Positions without a block are left alone when pasting.
+    assert_eq!(schematic.get(BlockPos::new(1, 0, 0)), None);
+    assert_eq!(schematic.block_entities[0].id, "minecraft:chest");
+    assert!(!schematic.block_entities[0].data.contains_key("id"));
+    assert_eq!(schematic.entities[0].id, "minecraft:pig");
+
+    let dimension = dimension();
+    dimension
+        .set_block(
+            BlockPos::new(11, 5, 0),
+            Block::GLASS.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    let result = schematic
+        .paste(
+            &dimension,
+            BlockPos::new(10, 5, 0),
+            &PasteOptions::new().rotation(Rotation::Clockwise90),
+        )
+        .unwrap();
+    assert_eq!(result.blocks, 1);
+    assert_eq!(
+        dimension.get_block(BlockPos::new(10, 5, 0)),
+        state("chest[facing=west]")
+    );
+    assert_eq!(
+        dimension.get_block(BlockPos::new(11, 5, 0)),
+        Block::GLASS.default_state()
+    );
+    assert_eq!(result.block_entities, 1);
+    assert_eq!(
+        dimension.block_entity(BlockPos::new(10, 5, 0)).unwrap().id,
+        "minecraft:chest"
+    );
+    // The pig half a block past x 1 ends up south of the chest.
+    assert_eq!(result.entities[0].pos, [10.5, 5.0, 1.5]);
+    assert_eq!(
+        result.entities[0].data.get_list("Rotation"),
+        Some(&[Value::Float(90.0), Value::Float(0.0)][..])
+    );
+}
+
+#[test]
+fn copy_and_paste_with_transforms() {
+    let dimension = dimension();
+    let stairs = state("oak_stairs[facing=east,half=bottom]");
+    dimension
+        .set_block(BlockPos::new(0, 5, 0), stairs, BlockFlags::DEFAULT)
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(1, 5, 0),
+            Block::STONE.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+
+    let schematic =
+        Schematic::copy(&dimension, BlockPos::new(1, 6, 1), BlockPos::new(0, 4, 0)).unwrap();
+    assert_eq!(
+        (schematic.width, schematic.height, schematic.length),
+        (2, 3, 2)
+    );
+    assert_eq!(schematic.get(BlockPos::new(0, 1, 0)), Some(stairs));
+    assert_eq!(
+        schematic.get(BlockPos::new(0, 0, 0)),
+        Some(Block::GRASS_BLOCK.default_state())
+    );
+
+    // Turned half around and mirrored back, the copy only flips north and
+    // south.
+    let options = PasteOptions::new()
+        .rotation(Rotation::Clockwise180)
+        .mirror(Mirror::FrontBack)
+        .ignore_air(true);
+    let result = schematic
+        .paste(&dimension, BlockPos::new(20, 0, 20), &options)
+        .unwrap();
+    assert_eq!(result.blocks, 6);
+    assert_eq!(
+        dimension
+            .get_block(BlockPos::new(20, 1, 20))
+            .value("facing"),
+        Some("east")
+    );
+    assert_eq!(
+        dimension.get_block(BlockPos::new(21, 1, 20)),
+        Block::STONE.default_state()
+    );
+
+    let mirrored = PasteOptions::new().mirror(Mirror::FrontBack);
+    schematic
+        .paste(&dimension, BlockPos::new(40, 0, 40), &mirrored)
+        .unwrap();
+    assert_eq!(
+        dimension
+            .get_block(BlockPos::new(40, 1, 40))
+            .value("facing"),
+        Some("west")
+    );
+    assert_eq!(
+        dimension.get_block(BlockPos::new(39, 1, 40)),
+        Block::STONE.default_state()
+    );
+    assert!(dimension.get_block(BlockPos::new(40, 2, 40)).is_air());
+}
@@ -0,0 +1,255 @@
+use world::{
+    Dimension, DimensionId,
+    block::{Block, BlockState, property},
+    generator::flat::FlatGenerator,
+    pos::BlockPos,
+    update::BlockFlags,
+};
+
+// The legacy flat world has grass at y 4 and air from y 5.
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+fn state(input: &str) -> BlockState {
+    input.parse().unwrap()
+}
+
+#[test]
+fn fences_connect_to_fences_and_solid_blocks() {
+    let dimension = dimension();
+    let set = |x, z, state: BlockState| {
+        dimension
+            .set_block(BlockPos::new(x, 5, z), state, BlockFlags::DEFAULT)
+            .unwrap();
+    };
+    let fence = Block::OAK_FENCE.default_state();
+    set(0, 0, fence);
+    set(1, 0, fence);
+    set(0, -1, Block::STONE.into());
+    // Leaves, pumpkins and barriers are full blocks fences don't connect to.
+    set(-1, 0, Block::PUMPKIN.into());
+    set(0, 1, Block::OAK_LEAVES.into());
+    set(1, 1, Block::BARRIER.into());
+    assert_eq!(
+        dimension.get_block(BlockPos::new(0, 5, 0)),
+        state("oak_fence[east=true,north=true]")
+    );
+    assert_eq!(
+        dimension.get_block(BlockPos::new(1, 5, 0)),
+        state("oak_fence[west=true]")
+    );
+
+    // Without neighbor updates the block goes in as given.
+    dimension
+        .set_block(BlockPos::new(2, 5, 0), fence, BlockFlags::NONE)
+        .unwrap();
+    assert_eq!(dimension.get_block(BlockPos::new(2, 5, 0)), fence);
+    assert_eq!(
+        dimension.get_block(BlockPos::new(1, 5, 0)),
+        state("oak_fence[west=true]")
+    );
+}
+
+#[test]
+fn walls_take_posts_and_grow_tall() {
+    let dimension = dimension();
+    let set = |x, y, z, state: BlockState| {
+        dimension
+            .set_block(BlockPos::new(x, y, z), state, BlockFlags::DEFAULT)
+            .unwrap();
+    };
+    let get = |x, y, z| dimension.get_block(BlockPos::new(x, y, z));
+    let wall = Block::COBBLESTONE_WALL.default_state();
+    for x in 4..7 {
+        set(x, 5, 0, wall);
+        set(x, 5, 3, wall);
+    }
+    // Straight runs drop the post, ends keep it.
+    assert_eq!(
+        get(5, 5, 0),
+        state("cobblestone_wall[east=low,west=low,up=false]")
+    );
+    assert_eq!(get(4, 5, 0), state("cobblestone_wall[east=low,up=true]"));
+
+    // A full block on top raises the sides, a torch brings the post back.
+    set(5, 6, 0, Block::STONE.into());
+    assert_eq!(
+        get(5, 5, 0),
+        state("cobblestone_wall[east=tall,west=tall,up=false]")
+    );
+    set(5, 6, 3, Block::TORCH.into());
+    assert_eq!(
+        get(5, 5, 3),
+        state("cobblestone_wall[east=low,west=low,up=true]")
+    );
+
+    set(10, 5, 0, wall);
+    set(9, 5, 0, Block::STONE.into());
+    set(11, 5, 0, Block::PUMPKIN.into());
+    set(10, 5, -1, Block::OAK_LEAVES.into());
+    set(10, 5, 1, Block::BARRIER.into());
+    assert_eq!(get(10, 5, 0), state("cobblestone_wall[west=low,up=true]"));
+}
+
+#[test]
+fn stairs_form_corners_and_grass_turns_snowy() {
+    let dimension = dimension();
+    dimension
+        .set_block(
+            BlockPos::new(0, 5, 0),
+            state("oak_stairs[facing=east,half=bottom]"),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(1, 5, 0),
+            state("oak_stairs[facing=north,half=bottom]"),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    assert_eq!(
+        dimension.get_block(BlockPos::new(0, 5, 0)).value("shape"),
+        Some("outer_left")
+    );
+    assert_eq!(
+        dimension.get_block(BlockPos::new(1, 5, 0)).value("shape"),
+        Some("straight")
+    );
+
+    // Without neighbor updates the block goes in as given.
+    let stairs = state("oak_stairs[facing=south,half=bottom]");
+    dimension
+        .set_block(BlockPos::new(0, 5, -1), stairs, BlockFlags::NONE)
+        .unwrap();
+    assert_eq!(dimension.get_block(BlockPos::new(0, 5, -1)), stairs);
+    assert_eq!(
+        dimension.get_block(BlockPos::new(0, 5, 0)).value("shape"),
+        Some("outer_left")
+    );
+
+    let grass = BlockPos::new(3, 4, 3);
+    dimension
+        .set_block(
+            BlockPos::new(3, 5, 3),
+            Block::SNOW.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    assert_eq!(dimension.get_block(grass).get(property::SNOWY), Some(true));
+    dimension
+        .set_block(BlockPos::new(3, 5, 3), BlockState::AIR, BlockFlags::DEFAULT)
+        .unwrap();
+    assert_eq!(dimension.get_block(grass).get(property::SNOWY), Some(false));
+}
+
+#[test]
+fn changes_are_grouped_by_section() {
+    let dimension = dimension();
+    let old = dimension
+        .set_block(
+            BlockPos::new(17, 5, 2),
+            Block::STONE.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    assert!(old.is_air());
+    dimension
+        .set_block(
+            BlockPos::new(18, 5, 2),
+            Block::GLASS.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(18, 20, 2),
+            Block::GLASS.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    dimension
+        .set_block(
+            BlockPos::new(-1, 5, 0),
+            Block::GLASS.into(),
+            BlockFlags::NEIGHBORS,
+        )
+        .unwrap();
+    // Setting the same state again is no change.
+    dimension
+        .set_block(
+            BlockPos::new(17, 5, 2),
+            Block::STONE.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+
+    let changes = dimension.take_block_changes();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(
+        (changes[0].chunk_x, changes[0].section_y, changes[0].chunk_z),
+        (1, 0, 0)
+    );
+    assert_eq!(
+        changes[0].blocks,
+        vec![
+            (BlockPos::new(17, 5, 2), Block::STONE.default_state()),
+            (BlockPos::new(18, 5, 2), Block::GLASS.default_state()),
+        ]
+    );
+    assert_eq!(changes[1].section_y, 1);
+    assert!(dimension.take_block_changes().is_empty());
+
+    // Outside the dimension's height nothing happens.
+    let old = dimension
+        .set_block(
+            BlockPos::new(0, 400, 0),
+            Block::STONE.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    assert!(old.is_air());
+    assert!(dimension.take_block_changes().is_empty());
+}
+
+#[test]
+fn fluids_and_falling_blocks_get_ticks() {
+    let dimension = dimension();
+    let sand = BlockPos::new(0, 5, 0);
+    let water = BlockPos::new(4, 5, 0);
+    dimension
+        .set_block(sand, Block::SAND.into(), BlockFlags::DEFAULT)
+        .unwrap();
+    dimension
+        .set_block(water, Block::WATER.into(), BlockFlags::NONE)
+        .unwrap();
+    assert_eq!(dimension.scheduled_ticks(), 1);
+
+    // A change next to the water wakes it up.
+    dimension
+        .set_block(
+            BlockPos::new(5, 5, 0),
+            Block::STONE.into(),
+            BlockFlags::DEFAULT,
+        )
+        .unwrap();
+    assert_eq!(dimension.scheduled_ticks(), 2);
+
+    assert!(dimension.tick_blocks().is_empty());
+    let due = dimension.tick_blocks();
+    assert_eq!(due.len(), 1);
+    assert_eq!((due[0].pos, due[0].block), (sand, Block::SAND));
+
+    // Ticks for blocks that were replaced are dropped.
+    dimension
+        .set_block(water, BlockState::AIR, BlockFlags::NONE)
+        .unwrap();
+    for _ in 0..5 {
+        assert!(dimension.tick_blocks().is_empty());
+    }
+    assert_eq!(dimension.scheduled_ticks(), 0);
+
+    assert!(dimension.schedule_tick(sand, Block::SAND, 1));
+    assert!(!dimension.schedule_tick(sand, Block::SAND, 1));
+}
@@ -0,0 +1,85 @@
+use std::{sync::Arc, thread};
+
+use world::{
+    block::Block,
+    chunk::Chunk,
+    generator::{
+        WorldGenerator,
+        terrain::{Biome, NoiseGenerator, SEA_LEVEL},
+    },
+    heightmap::HeightmapKind,
+};
+
+fn blocks(chunk: &Chunk) -> Vec<u16> {
+    chunk
+        .sections
+        .iter()
+        .flat_map(|section| section.blocks.iter().collect::<Vec<_>>())
+        .collect()
+}
+
+#[test]
+fn same_seed_same_chunks() {
+    let a = NoiseGenerator::new(42);
+    let b = NoiseGenerator::new(42);
+    let c = NoiseGenerator::new(43);
+    for (x, z) in [(0, 0), (-3, 7), (100, -250)] {
+        let chunk = a.generate_chunk(x, z);
+        assert_eq!(blocks(&chunk), blocks(&b.generate_chunk(x, z)));
+        assert_eq!(chunk.heightmaps, b.generate_chunk(x, z).heightmaps);
+        assert_ne!(blocks(&chunk), blocks(&c.generate_chunk(x, z)));
+    }
+}
+
+#[test]
+fn chunks_from_threads_match() {
+    let generator = Arc::new(NoiseGenerator::new(7));
+    let expected = (0..8)
+        .map(|i| blocks(&generator.generate_chunk(i, -i)))
+        .collect::<Vec<_>>();
+
+    let handles = (0..8)
+        .map(|i| {
+            let generator = generator.clone();
+            thread::spawn(move || blocks(&generator.generate_chunk(i, -i)))
+        })
+        .collect::<Vec<_>>();
+    for (handle, expected) in handles.into_iter().zip(expected) {
+        assert_eq!(handle.join().unwrap(), expected);
+    }
+}
+
+#[test]
+fn terrain_layers() {
+    let generator = NoiseGenerator::new(1234);
+    let mut saw_ocean = false;
+    let mut saw_land = false;
+    for (cx, cz) in (-4..4).flat_map(|x| (-4..4).map(move |z| (x * 13, z * 13))) {
+        let chunk = generator.generate_chunk(cx, cz);
+        for (x, z) in [(0, 0), (5, 11), (15, 15)] {
+            assert_eq!(chunk.get_block(x, -64, z), Block::BEDROCK.default_state());
+            assert_ne!(chunk.get_block(x, -50, z), Block::BEDROCK.default_state());
+
+            let (wx, wz) = (cx * 16 + x as i32, cz * 16 + z as i32);
+            let surface = generator.surface_height(wx, wz);
+            let biome = generator.biome_at(wx, wz);
+            if surface < SEA_LEVEL {
+                saw_ocean = true;
+                assert!(matches!(
+                    biome,
+                    Biome::Ocean | Biome::DeepOcean | Biome::FrozenOcean
+                ));
+                let top = chunk.get_block(x, SEA_LEVEL - 1, z).block();
+                assert!(top == Block::WATER || top == Block::ICE);
+                // Ice counts as floor too.
+                if top == Block::WATER {
+                    assert_eq!(chunk.height_at(HeightmapKind::OceanFloor, x, z), surface);
+                }
+            } else if surface > SEA_LEVEL {
+                saw_land = true;
+                assert!(chunk.height_at(HeightmapKind::WorldSurface, x, z) <= surface + 9);
+            }
+        }
+    }
+    assert!(saw_ocean && saw_land);
+}
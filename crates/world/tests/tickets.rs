@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use world::{
+    Dimension, DimensionId,
+    block::{Block, BlockRegistry},
+    generator::flat::FlatGenerator,
+    loader::RegionStorage,
+    ticket::Ticket,
+};
+
+mod common;
+
+use common::TempDir;
+
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+#[test]
+fn tickets_keep_chunks_loaded() {
+    let dimension = dimension().with_unload_delay(2);
+    let player = dimension.add_ticket(Ticket::player(0, 0, 2));
+    let forced = dimension.add_ticket(Ticket::forced(2, 2));
+    assert!(dimension.chunks.is_empty());
+    assert_eq!(dimension.load_ticket(player).unwrap(), 25);
+    assert_eq!(dimension.load_ticket(forced).unwrap(), 0);
+    assert_eq!(dimension.chunks.len(), 25);
+    assert!(dimension.is_ticketed(-2, 2));
+    assert!(!dimension.is_ticketed(3, 0));
+
+    // Moving east loads one new column and frees the western one.
+    assert!(dimension.move_ticket(player, 1, 0));
+    assert_eq!(dimension.load_ticket(player).unwrap(), 5);
+    assert_eq!(dimension.chunks.len(), 30);
+    assert!(!dimension.is_ticketed(-2, 0));
+    assert!(dimension.is_ticketed(2, 2));
+
+    assert_eq!(dimension.tick_chunks().unwrap(), 0);
+    assert_eq!(dimension.chunk_stats().idle, 5);
+    assert_eq!(dimension.tick_chunks().unwrap(), 5);
+    assert!(!dimension.chunks.contains_key(&(-2, 0)));
+
+    dimension.remove_ticket(player).unwrap();
+    assert_eq!(dimension.load_ticket(player).unwrap(), 0);
+    dimension.tick_chunks().unwrap();
+    dimension.tick_chunks().unwrap();
+    let stats = dimension.chunk_stats();
+    assert_eq!((stats.loaded, stats.ticketed, stats.tickets), (1, 1, 1));
+    assert!(dimension.chunks.contains_key(&(2, 2)));
+    assert!(stats.memory > 0);
+
+    dimension.remove_ticket(forced).unwrap();
+    assert!(dimension.remove_ticket(forced).is_none());
+}
+
+#[test]
+fn plugin_tickets_expire() {
+    let dimension = dimension().with_unload_delay(0);
+    let ticket = dimension.add_ticket(Ticket::plugin(5, 5, 1, 3));
+    dimension.load_ticket(ticket).unwrap();
+    assert_eq!(dimension.chunks.len(), 9);
+
+    dimension.tick_chunks().unwrap();
+    dimension.tick_chunks().unwrap();
+    assert!(dimension.ticket(ticket).is_some());
+    assert_eq!(dimension.chunks.len(), 9);
+
+    assert_eq!(dimension.tick_chunks().unwrap(), 9);
+    assert!(dimension.ticket(ticket).is_none());
+    assert!(dimension.chunks.is_empty());
+}
+
+#[test]
+fn chunk_limit_evicts_oldest_idle_chunks_first() {
+    let dimension = dimension().with_chunk_limit(4);
+    let spawn = dimension.add_ticket(Ticket::spawn(0, 0, 0));
+    dimension.load_ticket(spawn).unwrap();
+    for x in 1..=6 {
+        dimension.load_chunk(x, 0).unwrap();
+    }
+
+    // Untracked chunks become idle on the first tick, all at once.
+    assert_eq!(dimension.tick_chunks().unwrap(), 3);
+    let stats = dimension.chunk_stats();
+    assert_eq!((stats.loaded, stats.ticketed, stats.idle), (4, 1, 3));
+
+    // Tickets always win over the limit.
+    dimension.remove_ticket(spawn);
+    let player = dimension.add_ticket(Ticket::player(20, 20, 1));
+    dimension.load_ticket(player).unwrap();
+    dimension.tick_chunks().unwrap();
+    let stats = dimension.chunk_stats();
+    assert_eq!((stats.loaded, stats.ticketed), (9, 9));
+    assert!(!dimension.unload_chunk(20, 20).unwrap());
+}
+
+#[test]
+fn evicted_chunks_are_saved() {
+    let dir = TempDir::new("tickets");
+    let storage = RegionStorage::new(&dir, -64, 384, Arc::new(BlockRegistry));
+    let dimension = dimension().with_storage(storage).with_unload_delay(0);
+
+    let ticket = dimension.add_ticket(Ticket::forced(3, -1));
+    dimension.load_ticket(ticket).unwrap();
+    dimension.chunks.get_mut(&(3, -1)).unwrap().set_block(
+        4,
+        10,
+        4,
+        Block::GLOWSTONE.default_state(),
+    );
+    dimension.remove_ticket(ticket);
+    assert_eq!(dimension.tick_chunks().unwrap(), 1);
+    assert!(dimension.chunks.is_empty());
+
+    let ticket = dimension.add_ticket(Ticket::forced(3, -1));
+    dimension.load_ticket(ticket).unwrap();
+    let chunk = dimension.chunks.get(&(3, -1)).unwrap();
+    assert_eq!(chunk.get_block(4, 10, 4), Block::GLOWSTONE.default_state());
+}
@@ -0,0 +1,83 @@
+use world::{
+    Dimension, DimensionId, command, command::CommandError, generator::flat::FlatGenerator,
+    weather::WeatherKind,
+};
+
+fn dimension() -> Dimension {
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()))
+}
+
+#[test]
+fn day_cycle() {
+    let dimension = dimension();
+    // Sent right away on the first tick, then once a second.
+    assert!(dimension.take_time_update().is_some());
+    dimension.tick_time();
+    assert_eq!(dimension.take_time_update(), None);
+
+    dimension.set_time_of_day(23990);
+    assert_eq!(dimension.take_time_update().unwrap().time_of_day, 23990);
+    for _ in 0..19 {
+        dimension.tick_time();
+    }
+    let time = dimension.take_time_update().unwrap();
+    assert_eq!((time.age, time.day(), time.day_time()), (20, 1, 9));
+
+    dimension.set_time_frozen(true);
+    dimension.tick_time();
+    assert_eq!(dimension.time().time_of_day, 24009);
+    assert_eq!(dimension.time().age, 21);
+}
+
+#[test]
+fn commands() {
+    let dimension = dimension();
+    command::run(&dimension, "/time set noon").unwrap();
+    assert_eq!(dimension.time().day_time(), 6000);
+    command::run(&dimension, "time add 1d").unwrap();
+    command::run(&dimension, "time add 5s").unwrap();
+    assert_eq!(dimension.time().time_of_day, 30100);
+    assert_eq!(
+        command::run(&dimension, "time query day").unwrap(),
+        "The time is 1"
+    );
+    assert_eq!(
+        command::run(&dimension, "time set soon"),
+        Err(CommandError::InvalidTime("soon".to_string()))
+    );
+    assert!(matches!(
+        command::run(&dimension, "time"),
+        Err(CommandError::Usage(_))
+    ));
+
+    command::run(&dimension, "weather thunder 600").unwrap();
+    let weather = dimension.weather();
+    assert!(weather.raining && weather.thundering);
+    assert_eq!(weather.rain_time, 600);
+    command::run(&dimension, "weather clear").unwrap();
+    assert!(dimension.weather().clear_time >= 12000);
+    assert!(matches!(
+        command::run(&dimension, "gamemode creative"),
+        Err(CommandError::Unknown(_))
+    ));
+
+    // Dimensions without a sky have no weather.
+    let nether = Dimension::new(DimensionId::nether(), Box::new(FlatGenerator::new()));
+    nether.set_weather(WeatherKind::Rain, None);
+    assert!(!nether.weather().raining);
+}
+
+#[test]
+fn ticks_carry_commands_to_players() {
+    let dimension = dimension();
+    command::run(&dimension, "time set noon").unwrap();
+    command::run(&dimension, "weather rain").unwrap();
+    let tick = dimension.tick().unwrap();
+    assert_eq!(tick.dimension, DimensionId::overworld());
+    assert_eq!(tick.time.unwrap().day_time(), 6001);
+    assert!(!tick.weather.is_empty());
+
+    let tick = dimension.tick().unwrap();
+    assert_eq!(tick.time, None);
+    assert!(tick.blocks.is_empty());
+}
@@ -0,0 +1,96 @@
+use world::view::PlayerView;
+
+fn drain(view: &mut PlayerView) -> Vec<(i32, i32)> {
+    let mut chunks = Vec::new();
+    loop {
+        let batch = view.next_batch();
+        if batch.is_empty() {
+            return chunks;
+        }
+        view.batch_received(64.0);
+        chunks.extend(batch);
+    }
+}
+
+#[test]
+fn chunks_are_sent_nearest_first() {
+    let mut view = PlayerView::new(3);
+    let update = view.move_to(4, -2);
+    assert_eq!(update.center, Some((4, -2)));
+    assert!(update.unload.is_empty());
+    assert_eq!(view.pending_count(), 49);
+
+    let chunks = drain(&mut view);
+    assert_eq!(chunks.len(), 49);
+    assert_eq!(chunks[0], (4, -2));
+    let distances = chunks
+        .iter()
+        .map(|(x, z)| (x - 4).pow(2) + (z + 2).pow(2))
+        .collect::<Vec<_>>();
+    assert!(distances.windows(2).all(|pair| pair[0] <= pair[1]));
+    assert_eq!(view.sent_count(), 49);
+
+    // Moving within the same chunk changes nothing.
+    assert!(view.move_to(4, -2).is_empty());
+}
+
+#[test]
+fn moving_unloads_chunks_out_of_range() {
+    let mut view = PlayerView::new(2);
+    view.move_to(0, 0);
+    drain(&mut view);
+
+    let update = view.move_to(1, 0);
+    assert_eq!(update.center, Some((1, 0)));
+    assert_eq!(update.unload, (-2..=2).map(|z| (-2, z)).collect::<Vec<_>>());
+    let mut sent = drain(&mut view);
+    assert_eq!(sent[0], (3, 0));
+    sent.sort_unstable_by_key(|(_, z)| *z);
+    assert_eq!(sent, (-2..=2).map(|z| (3, z)).collect::<Vec<_>>());
+    assert!(view.is_sent(3, 2));
+    assert!(!view.is_sent(-2, 0));
+}
+
+#[test]
+fn lesser_view_distance_wins() {
+    let mut view = PlayerView::new(10);
+    view.move_to(0, 0);
+    assert_eq!(view.view_distance(), 10);
+
+    let update = view.set_client_view_distance(12);
+    assert!(update.is_empty());
+    let update = view.set_client_view_distance(2);
+    assert_eq!(update.view_distance, Some(2));
+    assert_eq!(view.pending_count(), 25);
+    drain(&mut view);
+
+    let update = view.set_server_view_distance(1);
+    assert_eq!((update.center, update.view_distance), (None, Some(1)));
+    assert_eq!(update.unload.len(), 16);
+    assert_eq!(view.sent_count(), 9);
+}
+
+#[test]
+fn batches_follow_the_client_rate() {
+    let mut view = PlayerView::new(8);
+    view.move_to(0, 0);
+
+    // Only one batch is in flight until the client answers.
+    assert_eq!(view.next_batch().len(), 9);
+    assert!(view.next_batch().is_empty());
+
+    // A slow client gets a chunk every few ticks.
+    view.batch_received(0.5);
+    assert_eq!(view.next_batch().len(), 1);
+    assert!(view.next_batch().is_empty());
+    assert_eq!(view.next_batch().len(), 1);
+
+    // Garbage counts as the slowest rate; no more than 64 per tick.
+    view.batch_received(f32::NAN);
+    view.batch_received(f32::NAN);
+    assert_eq!(view.next_batch().len(), 1);
+    assert!(view.next_batch().is_empty());
+    view.batch_received(1000.0);
+    assert_eq!(view.next_batch().len(), 64);
+    assert_eq!(view.next_batch().len(), 64);
+}
@@ -0,0 +1,72 @@
+use std::{path::Path, sync::Arc};
+
+use world::{
+    Dimension, DimensionId,
+    block::BlockRegistry,
+    generator::flat::FlatGenerator,
+    loader::RegionStorage,
+    weather::{WeatherChange, WeatherKind},
+};
+
+mod common;
+
+use common::TempDir;
+
+fn dimension(dir: &Path) -> Dimension {
+    let storage = RegionStorage::new(dir.join("region"), -64, 384, Arc::new(BlockRegistry));
+    Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new())).with_storage(storage)
+}
+
+#[test]
+fn rain_fades_in_and_out() {
+    let dimension = Dimension::new(DimensionId::overworld(), Box::new(FlatGenerator::new()));
+    dimension.set_weather(WeatherKind::Rain, Some(50));
+    let mut changes = Vec::new();
+    for _ in 0..50 {
+        dimension.tick_weather();
+        changes.extend(dimension.take_weather_changes());
+    }
+    assert_eq!(changes[0], WeatherChange::RainLevel(0.01));
+    let start = changes
+        .iter()
+        .position(|change| *change == WeatherChange::StartRaining)
+        .unwrap();
+    assert!(matches!(changes[start + 1], WeatherChange::RainLevel(level) if level > 0.2));
+
+    // After 50 ticks the rain stops and the level starts falling. Thunder
+    // starts then, as in vanilla, but without rain nobody sees it.
+    let weather = dimension.weather();
+    assert!(!weather.raining && weather.thundering);
+    assert!(weather.rain_level() > 0.4);
+    assert!(!weather.is_thundering());
+
+    for _ in 0..60 {
+        dimension.tick_weather();
+    }
+    let changes = dimension.take_weather_changes();
+    assert!(changes.contains(&WeatherChange::StopRaining));
+    assert!(changes.contains(&WeatherChange::RainLevel(0.0)));
+    assert!(dimension.weather().client_state().is_empty());
+}
+
+#[test]
+fn saved_with_the_dimension() {
+    let dir = TempDir::new("weather-saved");
+    let dimension = dimension(&dir);
+    dimension.set_weather(WeatherKind::Thunder, Some(1000));
+    dimension.set_time_of_day(13000);
+    for _ in 0..10 {
+        dimension.tick_weather();
+        dimension.tick_time();
+    }
+    dimension.save_chunks().unwrap();
+
+    let loaded = self::dimension(&dir);
+    let weather = loaded.weather();
+    assert!(weather.raining && weather.thundering);
+    assert_eq!(weather.thunder_time, 990);
+    // A storm in progress doesn't fade in again.
+    assert!(weather.is_thundering());
+    assert_eq!(weather.client_state().len(), 3);
+    assert_eq!(loaded.time(), dimension.time());
+}